@@ -0,0 +1,14 @@
+//! Request and response types of the Finance Tracker API, shared by the server and its
+//! clients so both sides agree on field names, amounts and validation.
+//!
+//! The `openapi` feature derives `utoipa` schemas for the server's OpenAPI document.
+
+pub mod error;
+pub mod models;
+pub mod validation;
+
+pub use error::{ErrorResponse, FieldError};
+pub use validation::{
+    AccountName, CategoryName, CurrencyCode, HouseholdName, IsoDate, Money, Name, PayeeName,
+    RuleName, TokenName,
+};
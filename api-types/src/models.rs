@@ -0,0 +1,805 @@
+//! JSON bodies of the API, shared by the server and its clients.
+
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+
+use crate::validation::{
+    request, AccountName, CategoryName, CurrencyCode, HouseholdName, IsoDate, Money, PayeeName,
+    RuleName, TokenName,
+};
+
+/// Amounts are written with the two decimal places of the `NUMERIC(12,2)` columns.
+/// Postgres hands back zero without a scale, and sums may carry a different one.
+fn cents<S: serde::Serializer>(amount: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut amount = *amount;
+    amount.rescale(2);
+    Serialize::serialize(&amount, serializer)
+}
+
+fn optional_cents<S: serde::Serializer>(amount: &Option<Decimal>, serializer: S) -> Result<S::Ok, S::Error> {
+    match amount {
+        Some(amount) => cents(amount, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct RegisterRequest {
+        pub username: String,
+        pub password: String,
+    }
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct LoginRequest {
+        pub username: String,
+        pub password: String,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RegisterResponse {
+    pub message: String,
+    pub user_id: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LoginResponse {
+    pub message: String,
+    pub token: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub refresh_token: String,
+    pub refresh_expires_at: chrono::DateTime<chrono::Utc>,
+    pub user_id: i32,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct ChangePasswordRequest {
+        pub current_password: String,
+        pub new_password: String,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ChangePasswordResponse {
+    pub message: String,
+    pub revoked_sessions: i64,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct ChangeUsernameRequest {
+        pub username: String,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ChangeUsernameResponse {
+    pub message: String,
+    pub username: String,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct DeleteMeRequest {
+        pub password: String,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeleteMeResponse {
+    pub message: String,
+}
+
+/// Returned by login instead of a session when the user has two-factor authentication on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TwoFactorChallengeResponse {
+    pub message: String,
+    pub two_factor_required: bool,
+    pub challenge_token: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(untagged)]
+pub enum LoginResult {
+    Session(LoginResponse),
+    TwoFactorRequired(TwoFactorChallengeResponse),
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct TwoFactorVerifyRequest {
+        pub challenge_token: String,
+        pub code: String, // 6-digit TOTP code or a recovery code
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TwoFactorSetupResponse {
+    pub message: String,
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct TwoFactorConfirmRequest {
+        pub code: String,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TwoFactorConfirmResponse {
+    pub message: String,
+    pub recovery_codes: Vec<String>,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct TwoFactorDisableRequest {
+        pub password: String,
+        pub code: String,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TwoFactorDisableResponse {
+    pub message: String,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct RefreshRequest {
+        pub refresh_token: String,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LogoutResponse {
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SessionResponse {
+    pub id: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub current: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RevokeSessionResponse {
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LogoutAllResponse {
+    pub message: String,
+    pub revoked_sessions: i64,
+}
+
+// API token models
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct CreateApiTokenRequest {
+        pub name: TokenName,
+        pub scopes: Vec<String>,
+        pub expires_in_days: Option<i64>,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateApiTokenResponse {
+    pub message: String,
+    pub id: i32,
+    pub token: String,
+    pub scopes: Vec<String>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApiTokenResponse {
+    pub id: i32,
+    pub name: String,
+    pub token_prefix: String,
+    pub scopes: Vec<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeleteApiTokenResponse {
+    pub message: String,
+}
+
+// Household models
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct CreateHouseholdRequest {
+        pub name: HouseholdName,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HouseholdResponse {
+    pub id: i32,
+    pub name: String,
+    pub role: String, // The caller's role: 'owner', 'editor' or 'viewer'
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateHouseholdResponse {
+    pub message: String,
+    pub household: HouseholdResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeleteHouseholdResponse {
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HouseholdMemberResponse {
+    pub user_id: i32,
+    pub username: String,
+    pub role: String,
+    pub joined_at: chrono::DateTime<chrono::Utc>,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct AddHouseholdMemberRequest {
+        pub username: String,
+        pub role: String,
+    }
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct UpdateHouseholdMemberRequest {
+        pub role: String,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HouseholdMemberChangeResponse {
+    pub message: String,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct CreateAccountRequest {
+        pub name: AccountName,
+        pub currency: Option<CurrencyCode>,
+        pub is_liability: Option<bool>,
+        pub household_id: Option<i32>, // None = personal account
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AccountResponse {
+    pub id: i32,
+    pub name: String,
+    pub currency: String,
+    #[serde(serialize_with = "cents")]
+    pub balance: Decimal,
+    pub is_liability: bool,
+    pub household_id: Option<i32>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateAccountResponse {
+    pub message: String,
+    pub account: AccountResponse,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct UpdateAccountRequest {
+        pub name: AccountName,
+        /// Left unchanged when omitted.
+        pub is_liability: Option<bool>,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateAccountResponse {
+    pub message: String,
+    pub account: AccountResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeleteAccountResponse {
+    pub message: String,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct CreateCategoryRequest {
+        pub name: CategoryName,
+        pub parent_id: Option<i32>,
+        pub household_id: Option<i32>, // None = personal category
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CategoryResponse {
+    pub id: i32,
+    pub name: String,
+    pub parent_id: Option<i32>,
+    pub household_id: Option<i32>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateCategoryResponse {
+    pub message: String,
+    pub category: CategoryResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeleteCategoryResponse {
+    pub message: String,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct CreateTransactionRequest {
+        pub account_id: Option<i32>,
+        pub account_name: Option<AccountName>,
+        pub amount: Money,
+        pub date: Option<IsoDate>,
+        pub category_id: Option<i32>,
+        pub category_name: Option<CategoryName>,
+        pub description: Option<String>,
+        pub payee_id: Option<i32>,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TransactionResponse {
+    pub id: i32,
+    pub account_id: i32,
+    pub account_name: String,
+    pub category_id: Option<i32>,
+    pub category_name: Option<String>,
+    #[serde(serialize_with = "cents")]
+    pub amount: Decimal,
+    pub r#type: String,
+    pub date: chrono::NaiveDate,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub payee_id: Option<i32>,
+    pub payee_name: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateTransactionResponse {
+    pub message: String,
+    pub transaction: TransactionResponse,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct TransferRequest {
+        pub from_account_id: i32,
+        pub to_account_id: i32,
+        pub amount: Money,
+        pub date: Option<IsoDate>,
+        pub description: Option<String>,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TransferResponse {
+    pub message: String,
+    pub from_transaction: TransactionResponse,
+    pub to_transaction: TransactionResponse,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct CreateBudgetRequest {
+        pub category_id: Option<i32>,
+        pub household_id: Option<i32>, // None = personal budget
+        pub amount: Money,
+        pub period: Option<String>,
+        pub start_date: Option<IsoDate>,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct BudgetResponse {
+    pub id: i32,
+    pub household_id: Option<i32>,
+    pub category_id: Option<i32>,
+    pub category_name: Option<String>,
+    #[serde(serialize_with = "cents")]
+    pub amount: Decimal,
+    pub period: Option<String>,
+    pub start_date: chrono::NaiveDate,
+    #[serde(serialize_with = "cents")]
+    pub spent: Decimal,
+    #[serde(serialize_with = "cents")]
+    pub remaining: Decimal,
+    pub is_over_budget: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateBudgetResponse {
+    pub message: String,
+    pub budget: BudgetResponse,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct UpdateBudgetRequest {
+        pub amount: Money,
+        pub period: Option<String>,
+        pub start_date: Option<IsoDate>,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateBudgetResponse {
+    pub message: String,
+    pub budget: BudgetResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeleteBudgetResponse {
+    pub message: String,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct CreateRuleRequest {
+        pub name: RuleName,
+        pub priority: Option<i32>,
+        pub stop_processing: Option<bool>,
+        pub description_contains: Option<String>,
+        pub description_regex: Option<String>,
+        pub min_amount: Option<Money>,
+        pub max_amount: Option<Money>,
+        pub account_id: Option<i32>,
+        pub set_category_id: Option<i32>,
+        pub add_tags: Option<Vec<String>>,
+        pub set_description: Option<String>,
+    }
+}
+
+pub type UpdateRuleRequest = CreateRuleRequest;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RuleResponse {
+    pub id: i32,
+    pub name: String,
+    pub priority: i32,
+    pub stop_processing: bool,
+    pub description_contains: Option<String>,
+    pub description_regex: Option<String>,
+    #[serde(serialize_with = "optional_cents")]
+    pub min_amount: Option<Decimal>,
+    #[serde(serialize_with = "optional_cents")]
+    pub max_amount: Option<Decimal>,
+    pub account_id: Option<i32>,
+    pub set_category_id: Option<i32>,
+    pub add_tags: Vec<String>,
+    pub set_description: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateRuleResponse {
+    pub message: String,
+    pub rule: RuleResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdateRuleResponse {
+    pub message: String,
+    pub rule: RuleResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeleteRuleResponse {
+    pub message: String,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct ApplyRulesRequest {
+        pub dry_run: Option<bool>,
+        pub account_id: Option<i32>,
+        pub start_date: Option<IsoDate>,
+        pub end_date: Option<IsoDate>,
+        pub overwrite_categories: Option<bool>,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RuleChange {
+    pub transaction_id: i32,
+    pub matched_rule_ids: Vec<i32>,
+    pub description_before: Option<String>,
+    pub description_after: Option<String>,
+    pub category_id_before: Option<i32>,
+    pub category_id_after: Option<i32>,
+    pub tags_before: Vec<String>,
+    pub tags_after: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ApplyRulesResponse {
+    pub message: String,
+    pub dry_run: bool,
+    pub changed: usize,
+    pub changes: Vec<RuleChange>,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct CreatePayeeRequest {
+        pub name: PayeeName,
+        pub default_category_id: Option<i32>,
+        pub aliases: Option<Vec<String>>,
+    }
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct UpdatePayeeRequest {
+        pub name: PayeeName,
+        pub default_category_id: Option<i32>,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PayeeAliasResponse {
+    pub id: i32,
+    pub alias: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PayeeResponse {
+    pub id: i32,
+    pub name: String,
+    pub default_category_id: Option<i32>,
+    pub aliases: Vec<PayeeAliasResponse>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreatePayeeResponse {
+    pub message: String,
+    pub payee: PayeeResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct UpdatePayeeResponse {
+    pub message: String,
+    pub payee: PayeeResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeletePayeeResponse {
+    pub message: String,
+}
+
+request! {
+    #[derive(Debug, Clone, Serialize)]
+    #[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+    pub struct AddPayeeAliasRequest {
+        pub alias: String,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AddPayeeAliasResponse {
+    pub message: String,
+    pub alias: PayeeAliasResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DeletePayeeAliasResponse {
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MatchPayeesResponse {
+    pub message: String,
+    pub matched: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PayeeSpendingResponse {
+    pub payee_id: Option<i32>,
+    pub payee_name: Option<String>,
+    #[serde(serialize_with = "cents")]
+    pub total_spent: Decimal,
+    pub transaction_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CashflowPeriodResponse {
+    pub period_start: chrono::NaiveDate,
+    #[serde(serialize_with = "cents")]
+    pub income: Decimal,
+    #[serde(serialize_with = "cents")]
+    pub expense: Decimal,
+    #[serde(serialize_with = "cents")]
+    pub net: Decimal,
+    pub savings_rate: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CashflowReportResponse {
+    pub from: chrono::NaiveDate,
+    pub to: chrono::NaiveDate,
+    pub interval: String,
+    #[serde(serialize_with = "cents")]
+    pub total_income: Decimal,
+    #[serde(serialize_with = "cents")]
+    pub total_expense: Decimal,
+    #[serde(serialize_with = "cents")]
+    pub net: Decimal,
+    pub savings_rate: Option<Decimal>,
+    pub periods: Vec<CashflowPeriodResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CategoryTotalResponse {
+    pub category_id: Option<i32>,
+    pub category_name: Option<String>,
+    pub parent_id: Option<i32>,
+    #[serde(serialize_with = "cents")]
+    pub income: Decimal,
+    #[serde(serialize_with = "cents")]
+    pub expense: Decimal,
+    pub transaction_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NetWorthPointResponse {
+    pub date: chrono::NaiveDate,
+    #[serde(serialize_with = "cents")]
+    pub assets: Decimal,
+    #[serde(serialize_with = "cents")]
+    pub liabilities: Decimal,
+    #[serde(serialize_with = "cents")]
+    pub net_worth: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct NetWorthReportResponse {
+    pub from: chrono::NaiveDate,
+    pub to: chrono::NaiveDate,
+    pub interval: String,
+    pub points: Vec<NetWorthPointResponse>,
+}
+
+// Query strings
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+pub struct GetTransactionsQuery {
+    pub account_id: Option<i32>,
+    /// First day included, as YYYY-MM-DD
+    pub start_date: Option<String>,
+    /// Last day included, as YYYY-MM-DD
+    pub end_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+pub struct ReportRangeQuery {
+    /// First day included, as YYYY-MM-DD; unbounded if left out
+    pub from: Option<String>,
+    /// Last day included, as YYYY-MM-DD; unbounded if left out
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::IntoParams), into_params(parameter_in = Query))]
+pub struct TimeSeriesQuery {
+    /// First day, as YYYY-MM-DD; defaults to twelve intervals before `to`
+    pub from: Option<String>,
+    /// Last day, as YYYY-MM-DD; defaults to today
+    pub to: Option<String>,
+    /// `month` (default) or `week`
+    pub interval: Option<String>,
+}
@@ -0,0 +1,473 @@
+//! Newtypes for validated request fields, and the machinery that reports every invalid
+//! field of a request body at once.
+
+use std::{fmt, marker::PhantomData, str::FromStr};
+
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::error::FieldError;
+
+/// Trimmed text of 1 to `MAX` characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Name<const MAX: usize>(String);
+
+pub type AccountName = Name<50>;
+pub type CategoryName = Name<50>;
+pub type HouseholdName = Name<50>;
+pub type RuleName = Name<50>;
+pub type PayeeName = Name<100>;
+pub type TokenName = Name<100>;
+
+impl<const MAX: usize> Name<MAX> {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<const MAX: usize> FromStr for Name<MAX> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        let length = name.chars().count();
+        if length == 0 || length > MAX {
+            return Err(format!("Must be between 1 and {} characters", MAX));
+        }
+        Ok(Self(name.to_string()))
+    }
+}
+
+impl<const MAX: usize> fmt::Display for Name<MAX> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// An amount that fits the `NUMERIC(12,2)` columns: at most two decimal places and
+/// ten integer digits. Accepted as a JSON string (`"12.50"`) or number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Money(Decimal);
+
+impl Money {
+    pub fn value(&self) -> Decimal {
+        self.0
+    }
+}
+
+impl FromStr for Money {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let amount = s.trim().parse::<Decimal>()
+            .map_err(|_| "Invalid amount format".to_string())?
+            .normalize();
+        if amount.scale() > 2 {
+            return Err("Amount can have at most 2 decimal places".to_string());
+        }
+        if amount.abs() >= Decimal::from(10_000_000_000i64) {
+            return Err("Amount is too large".to_string());
+        }
+        Ok(Self(amount))
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A calendar date written as `YYYY-MM-DD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IsoDate(NaiveDate);
+
+impl IsoDate {
+    pub fn value(&self) -> NaiveDate {
+        self.0
+    }
+}
+
+impl FromStr for IsoDate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+            .map(Self)
+            .map_err(|_| "Invalid date format. Use YYYY-MM-DD".to_string())
+    }
+}
+
+impl fmt::Display for IsoDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.format("%Y-%m-%d").fmt(f)
+    }
+}
+
+impl From<NaiveDate> for IsoDate {
+    fn from(date: NaiveDate) -> Self {
+        Self(date)
+    }
+}
+
+/// Three-letter ISO 4217 code, stored upper-case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurrencyCode(String);
+
+impl CurrencyCode {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for CurrencyCode {
+    fn default() -> Self {
+        Self("USD".to_string())
+    }
+}
+
+impl FromStr for CurrencyCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err("Currency must be a three-letter code such as USD".to_string());
+        }
+        Ok(Self(code.to_ascii_uppercase()))
+    }
+}
+
+impl fmt::Display for CurrencyCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Serde support for the newtypes: they serialize as strings and only deserialize from
+/// input their `FromStr` accepts. `Money` additionally takes JSON numbers.
+macro_rules! string_serde {
+    ($($ty:ty => $accept_numbers:expr),* $(,)?) => {
+        $(
+            impl Serialize for $ty {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serializer.collect_str(self)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $ty {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    deserializer.deserialize_any(ParseVisitor { accept_numbers: $accept_numbers, marker: PhantomData })
+                }
+            }
+        )*
+    };
+}
+
+string_serde!(Money => true, IsoDate => false, CurrencyCode => false);
+
+impl<const MAX: usize> Serialize for Name<MAX> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de, const MAX: usize> Deserialize<'de> for Name<MAX> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ParseVisitor { accept_numbers: false, marker: PhantomData })
+    }
+}
+
+/// OpenAPI schemas for the newtypes: all strings, with the constraints `FromStr` checks.
+#[cfg(feature = "openapi")]
+mod schema {
+    use std::borrow::Cow;
+
+    use utoipa::{
+        openapi::{schema::SchemaFormat, KnownFormat, ObjectBuilder, RefOr, Schema, Type},
+        PartialSchema, ToSchema,
+    };
+
+    use super::{CurrencyCode, IsoDate, Money, Name};
+
+    fn string_schema(description: String) -> ObjectBuilder {
+        ObjectBuilder::new().schema_type(Type::String).description(Some(description))
+    }
+
+    impl<const MAX: usize> PartialSchema for Name<MAX> {
+        fn schema() -> RefOr<Schema> {
+            string_schema(format!("Trimmed text of 1 to {} characters", MAX))
+                .min_length(Some(1))
+                .max_length(Some(MAX))
+                .into()
+        }
+    }
+
+    impl<const MAX: usize> ToSchema for Name<MAX> {
+        fn name() -> Cow<'static, str> {
+            Cow::Owned(format!("Name{}", MAX))
+        }
+    }
+
+    impl PartialSchema for Money {
+        fn schema() -> RefOr<Schema> {
+            string_schema("Amount with at most 2 decimal places; a JSON number is accepted too".to_string())
+                .pattern(Some(r"^-?\d{1,10}(\.\d{1,2})?$"))
+                .examples(["12.50"])
+                .into()
+        }
+    }
+
+    impl ToSchema for Money {}
+
+    impl PartialSchema for IsoDate {
+        fn schema() -> RefOr<Schema> {
+            string_schema("Calendar date as YYYY-MM-DD".to_string())
+                .format(Some(SchemaFormat::KnownFormat(KnownFormat::Date)))
+                .into()
+        }
+    }
+
+    impl ToSchema for IsoDate {}
+
+    impl PartialSchema for CurrencyCode {
+        fn schema() -> RefOr<Schema> {
+            string_schema("Three-letter ISO 4217 currency code".to_string())
+                .pattern(Some("^[A-Za-z]{3}$"))
+                .examples(["USD"])
+                .into()
+        }
+    }
+
+    impl ToSchema for CurrencyCode {}
+}
+
+struct ParseVisitor<T> {
+    accept_numbers: bool,
+    marker: PhantomData<T>,
+}
+
+impl<T> ParseVisitor<T> {
+    fn number<E: de::Error>(&self, unexpected: de::Unexpected, text: String) -> Result<T, E>
+    where
+        T: FromStr<Err = String>,
+    {
+        if self.accept_numbers {
+            text.parse().map_err(E::custom)
+        } else {
+            Err(E::invalid_type(unexpected, self))
+        }
+    }
+}
+
+impl<T: FromStr<Err = String>> de::Visitor<'_> for ParseVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.accept_numbers { "a string or number" } else { "a string" })
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<T, E> {
+        self.number(de::Unexpected::Signed(v), v.to_string())
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<T, E> {
+        self.number(de::Unexpected::Unsigned(v), v.to_string())
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<T, E> {
+        self.number(de::Unexpected::Float(v), v.to_string())
+    }
+}
+
+/// The fields of a JSON object, taken out one at a time so that every invalid field is
+/// reported rather than only the first.
+pub struct JsonFields {
+    map: Map<String, Value>,
+    errors: Vec<FieldError>,
+}
+
+impl JsonFields {
+    pub fn new(map: Map<String, Value>) -> Self {
+        Self { map, errors: Vec::new() }
+    }
+
+    /// Deserializes one field; a missing key reads as `null`, so `Option` fields may be
+    /// left out. Failures are recorded and `None` is returned.
+    pub fn take<T: de::DeserializeOwned>(&mut self, field: &str) -> Option<T> {
+        let value = self.map.remove(field).unwrap_or(Value::Null);
+        let missing = value.is_null();
+        match T::deserialize(value) {
+            Ok(v) => Some(v),
+            Err(e) => {
+                let message = if missing { "This field is required".to_string() } else { e.to_string() };
+                self.errors.push(FieldError { field: field.to_string(), message });
+                None
+            }
+        }
+    }
+
+    pub fn into_errors(self) -> Vec<FieldError> {
+        self.errors
+    }
+}
+
+/// Request bodies built from [`JsonFields`]. Implemented by [`request!`].
+pub trait FromJsonFields: Sized {
+    /// Returns `None` if any field failed; the failures are left in `fields`.
+    fn from_json_fields(fields: &mut JsonFields) -> Option<Self>;
+}
+
+/// Declares a request body struct whose fields are validated independently, so the
+/// server can report all invalid fields in one response. It also gets a `Deserialize`
+/// impl that joins the field errors into one message.
+macro_rules! request {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $($(#[$field_meta:meta])* pub $field:ident: $ty:ty),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        pub struct $name {
+            $($(#[$field_meta])* pub $field: $ty),*
+        }
+
+        impl $crate::validation::FromJsonFields for $name {
+            fn from_json_fields(fields: &mut $crate::validation::JsonFields) -> Option<Self> {
+                $(let $field = fields.take::<$ty>(stringify!($field));)*
+                Some(Self { $($field: $field?),* })
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let map = serde_json::Map::deserialize(deserializer)?;
+                let mut fields = $crate::validation::JsonFields::new(map);
+                match <Self as $crate::validation::FromJsonFields>::from_json_fields(&mut fields) {
+                    Some(value) => Ok(value),
+                    None => Err(serde::de::Error::custom(
+                        fields.into_errors().iter()
+                            .map(|e| format!("{}: {}", e.field, e.message))
+                            .collect::<Vec<_>>()
+                            .join("; "),
+                    )),
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use request;
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::models::CreateTransactionRequest;
+
+    fn money(s: &str) -> Result<Money, String> {
+        s.parse()
+    }
+
+    #[test]
+    fn money_allows_at_most_two_decimal_places() {
+        assert_eq!(money("12.5").unwrap().to_string(), "12.5");
+        assert_eq!(money(" -0.01 ").unwrap().value(), Decimal::new(-1, 2));
+        // Trailing zeros don't count
+        assert!(money("1.2300").is_ok());
+        assert_eq!(money("1.234").unwrap_err(), "Amount can have at most 2 decimal places");
+        assert_eq!(money("abc").unwrap_err(), "Invalid amount format");
+        assert!(money("").is_err());
+    }
+
+    #[test]
+    fn money_fits_ten_integer_digits() {
+        assert!(money("9999999999.99").is_ok());
+        assert!(money("-9999999999.99").is_ok());
+        assert_eq!(money("10000000000").unwrap_err(), "Amount is too large");
+        assert_eq!(money("-10000000000.00").unwrap_err(), "Amount is too large");
+    }
+
+    #[test]
+    fn money_deserializes_from_strings_and_numbers() {
+        assert_eq!(serde_json::from_value::<Money>(json!("12.50")).unwrap(), money("12.5").unwrap());
+        assert_eq!(serde_json::from_value::<Money>(json!(12.5)).unwrap(), money("12.5").unwrap());
+        assert_eq!(serde_json::from_value::<Money>(json!(-7)).unwrap(), money("-7").unwrap());
+        assert!(serde_json::from_value::<Money>(json!(1.005)).is_err());
+        assert!(serde_json::from_value::<Money>(json!(true)).is_err());
+        assert_eq!(serde_json::to_value(money("12.5").unwrap()).unwrap(), json!("12.5"));
+    }
+
+    #[test]
+    fn dates_must_be_iso_calendar_dates() {
+        let date: IsoDate = " 2024-02-29 ".parse().unwrap();
+        assert_eq!(date.value(), NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+        assert_eq!(date.to_string(), "2024-02-29");
+        for invalid in ["2023-02-29", "2024/01/05", "05-01-2024", "2024-1-5x", ""] {
+            assert!(invalid.parse::<IsoDate>().is_err(), "{}", invalid);
+        }
+        // Only strings, unlike amounts
+        assert!(serde_json::from_value::<IsoDate>(json!(20240105)).is_err());
+    }
+
+    #[test]
+    fn currency_codes_are_three_letters_stored_upper_case() {
+        assert_eq!("eur".parse::<CurrencyCode>().unwrap().as_str(), "EUR");
+        for invalid in ["EU", "EURO", "E1R", ""] {
+            assert!(invalid.parse::<CurrencyCode>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn names_are_trimmed_and_limited_in_characters() {
+        assert_eq!(" Groceries ".parse::<CategoryName>().unwrap().as_str(), "Groceries");
+        assert!("   ".parse::<CategoryName>().is_err());
+        assert!("é".repeat(50).parse::<CategoryName>().is_ok());
+        assert_eq!("é".repeat(51).parse::<CategoryName>().unwrap_err(), "Must be between 1 and 50 characters");
+        assert!("é".repeat(100).parse::<PayeeName>().is_ok());
+    }
+
+    #[test]
+    fn every_invalid_field_of_a_request_is_reported() {
+        let body = json!({
+            "account_name": "",
+            "amount": "1.234",
+            "date": "yesterday",
+            "category_id": "seven",
+            "description": "Lunch",
+        });
+        let mut fields = JsonFields::new(body.as_object().unwrap().clone());
+        assert!(CreateTransactionRequest::from_json_fields(&mut fields).is_none());
+
+        let errors = fields.into_errors();
+        let names = errors.iter().map(|e| e.field.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["account_name", "amount", "date", "category_id"]);
+        assert_eq!(errors[1].message, "Amount can have at most 2 decimal places");
+
+        // The plain Deserialize impl joins the same errors into one message
+        let message = serde_json::from_value::<CreateTransactionRequest>(body).unwrap_err().to_string();
+        assert!(message.starts_with("account_name: Must be between 1 and 50 characters; amount: "), "{}", message);
+        assert!(message.contains("date: Invalid date format") && message.contains("category_id: "), "{}", message);
+    }
+
+    #[test]
+    fn missing_required_fields_are_reported_and_optional_ones_may_be_omitted() {
+        let mut fields = JsonFields::new(Map::new());
+        assert!(CreateTransactionRequest::from_json_fields(&mut fields).is_none());
+        let errors = fields.into_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].field.as_str(), errors[0].message.as_str()), ("amount", "This field is required"));
+
+        let req: CreateTransactionRequest = serde_json::from_value(json!({"account_id": 3, "amount": -4})).unwrap();
+        assert_eq!(req.amount, money("-4").unwrap());
+        assert!(req.date.is_none() && req.category_name.is_none());
+    }
+}
@@ -2,10 +2,19 @@
 -- This script will delete all local data but keep the table structure
 
 TRUNCATE TABLE 
+    rules,
     transactions,
+    payee_aliases,
+    payees,
     budgets,
     accounts,
     categories,
+    household_members,
+    households,
+    refresh_tokens,
+    recovery_codes,
+    login_challenges,
+    api_tokens,
     sessions,
     users
 CASCADE;
@@ -13,9 +22,17 @@ CASCADE;
 -- Reset all sequences to start from 1
 ALTER SEQUENCE users_id_seq RESTART WITH 1;
 ALTER SEQUENCE sessions_id_seq RESTART WITH 1;
+ALTER SEQUENCE refresh_tokens_id_seq RESTART WITH 1;
+ALTER SEQUENCE recovery_codes_id_seq RESTART WITH 1;
+ALTER SEQUENCE login_challenges_id_seq RESTART WITH 1;
+ALTER SEQUENCE api_tokens_id_seq RESTART WITH 1;
 ALTER SEQUENCE accounts_id_seq RESTART WITH 1;
 ALTER SEQUENCE categories_id_seq RESTART WITH 1;
+ALTER SEQUENCE households_id_seq RESTART WITH 1;
 ALTER SEQUENCE transactions_id_seq RESTART WITH 1;
 ALTER SEQUENCE budgets_id_seq RESTART WITH 1;
+ALTER SEQUENCE rules_id_seq RESTART WITH 1;
+ALTER SEQUENCE payees_id_seq RESTART WITH 1;
+ALTER SEQUENCE payee_aliases_id_seq RESTART WITH 1;
 
 
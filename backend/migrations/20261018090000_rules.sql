@@ -0,0 +1,26 @@
+-- tags assigned to transactions (filled in by auto-categorisation rules)
+ALTER TABLE transactions ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
+
+-- rules table (user-defined auto-categorisation rules)
+-- Rules run in ascending priority order; every condition that is set must match.
+CREATE TABLE rules (
+    id SERIAL PRIMARY KEY,
+    user_id INT REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(50) NOT NULL,
+    priority INT NOT NULL DEFAULT 0, -- Lower values run first
+    stop_processing BOOLEAN NOT NULL DEFAULT FALSE, -- Skip remaining rules once this one matches
+    -- Conditions
+    description_contains TEXT, -- Case-insensitive substring match
+    description_regex TEXT,
+    min_amount NUMERIC(12,2), -- Compared against the signed amount (expenses are negative)
+    max_amount NUMERIC(12,2),
+    account_id INT REFERENCES accounts(id) ON DELETE CASCADE,
+    -- Actions
+    set_category_id INT REFERENCES categories(id) ON DELETE SET NULL,
+    add_tags TEXT[] NOT NULL DEFAULT '{}',
+    set_description TEXT, -- May reference regex capture groups ($1, $name)
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_rules_user_priority ON rules (user_id, priority, id);
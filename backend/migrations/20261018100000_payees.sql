@@ -0,0 +1,22 @@
+-- payees table (normalised merchants / counterparties)
+CREATE TABLE payees (
+    id SERIAL PRIMARY KEY,
+    user_id INT REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    default_category_id INT REFERENCES categories(id) ON DELETE SET NULL, -- Used when a transaction has no category
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE (user_id, name)
+);
+
+-- payee_aliases table (description fragments that identify a payee, e.g. 'AMZN MKTP')
+CREATE TABLE payee_aliases (
+    id SERIAL PRIMARY KEY,
+    payee_id INT REFERENCES payees(id) ON DELETE CASCADE,
+    alias VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE (payee_id, alias)
+);
+
+ALTER TABLE transactions ADD COLUMN payee_id INT REFERENCES payees(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_transactions_payee ON transactions (payee_id);
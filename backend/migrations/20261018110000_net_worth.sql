@@ -0,0 +1,5 @@
+-- Liability accounts (credit cards, loans) reduce net worth.
+-- Their balance always counts negatively, whichever sign convention the user records it with.
+ALTER TABLE accounts ADD COLUMN is_liability BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX idx_transactions_account_date ON transactions (account_id, date);
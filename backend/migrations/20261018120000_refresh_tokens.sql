@@ -0,0 +1,18 @@
+-- A token family is one login: every access/refresh token issued by rotating it shares the family_id
+ALTER TABLE sessions ADD COLUMN family_id VARCHAR(36);
+
+CREATE INDEX idx_sessions_family ON sessions (family_id);
+
+-- refresh_tokens table (long-lived, single-use; each use issues a new pair in the same family)
+CREATE TABLE refresh_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INT REFERENCES users(id) ON DELETE CASCADE,
+    family_id VARCHAR(36) NOT NULL,
+    token VARCHAR(255) UNIQUE NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ, -- Set when rotated; presenting a used token again revokes the family
+    revoked BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_refresh_tokens_family ON refresh_tokens (family_id);
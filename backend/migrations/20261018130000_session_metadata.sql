@@ -0,0 +1,6 @@
+-- Device information shown in the session list
+ALTER TABLE sessions ADD COLUMN last_used_at TIMESTAMPTZ;
+ALTER TABLE sessions ADD COLUMN user_agent VARCHAR(255);
+ALTER TABLE sessions ADD COLUMN ip_address VARCHAR(45); -- Long enough for IPv6
+
+CREATE INDEX idx_sessions_user ON sessions (user_id);
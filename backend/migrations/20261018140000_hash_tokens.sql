@@ -0,0 +1,8 @@
+-- Store only SHA-256 digests (hex) of bearer and refresh tokens, never the tokens themselves
+ALTER TABLE sessions RENAME COLUMN token TO token_hash;
+UPDATE sessions SET token_hash = encode(sha256(convert_to(token_hash, 'UTF8')), 'hex');
+ALTER TABLE sessions ALTER COLUMN token_hash TYPE VARCHAR(64);
+
+ALTER TABLE refresh_tokens RENAME COLUMN token TO token_hash;
+UPDATE refresh_tokens SET token_hash = encode(sha256(convert_to(token_hash, 'UTF8')), 'hex');
+ALTER TABLE refresh_tokens ALTER COLUMN token_hash TYPE VARCHAR(64);
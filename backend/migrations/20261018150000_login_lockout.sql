@@ -0,0 +1,3 @@
+-- Per-username brute-force protection: consecutive failures and temporary lockout
+ALTER TABLE users ADD COLUMN failed_login_attempts INT NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN locked_until TIMESTAMPTZ;
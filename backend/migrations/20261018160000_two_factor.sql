@@ -0,0 +1,26 @@
+-- TOTP two-factor authentication
+ALTER TABLE users ADD COLUMN totp_secret VARCHAR(64); -- Base32 secret, set once enrolment is confirmed
+ALTER TABLE users ADD COLUMN totp_pending_secret VARCHAR(64); -- Secret from /2fa/setup awaiting its first code
+ALTER TABLE users ADD COLUMN totp_enabled BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE users ADD COLUMN totp_last_step BIGINT; -- Last accepted 30s time step, so a code can't be replayed
+
+-- recovery_codes table (single-use fallback codes, stored as SHA-256 hex)
+CREATE TABLE recovery_codes (
+    id SERIAL PRIMARY KEY,
+    user_id INT REFERENCES users(id) ON DELETE CASCADE,
+    code_hash VARCHAR(64) NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_recovery_codes_user ON recovery_codes (user_id);
+
+-- login_challenges table (issued after a correct password when 2FA is on; exchanged for a session with a code)
+CREATE TABLE login_challenges (
+    id SERIAL PRIMARY KEY,
+    user_id INT REFERENCES users(id) ON DELETE CASCADE,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
@@ -0,0 +1,14 @@
+-- api_tokens table (personal access tokens for scripts; stored as SHA-256 hex like session tokens)
+CREATE TABLE api_tokens (
+    id SERIAL PRIMARY KEY,
+    user_id INT REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    token_hash VARCHAR(64) UNIQUE NOT NULL,
+    token_prefix VARCHAR(16) NOT NULL, -- First characters of the token, shown in listings to tell tokens apart
+    scopes TEXT[] NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    last_used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_api_tokens_user ON api_tokens (user_id);
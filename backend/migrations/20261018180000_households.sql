@@ -0,0 +1,51 @@
+-- households table (a shared workspace, e.g. for a couple's joint accounts)
+CREATE TABLE households (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(50) NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- household_members table (who can see and change a household's data)
+CREATE TABLE household_members (
+    household_id INT NOT NULL REFERENCES households(id) ON DELETE CASCADE,
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role VARCHAR(10) NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    PRIMARY KEY (household_id, user_id)
+);
+
+CREATE INDEX idx_household_members_user ON household_members (user_id);
+
+-- Accounts, categories and budgets are either personal (household_id NULL, visible to
+-- user_id only) or shared with a household (user_id just records who created them)
+ALTER TABLE accounts ADD COLUMN household_id INT REFERENCES households(id) ON DELETE CASCADE;
+ALTER TABLE categories ADD COLUMN household_id INT REFERENCES households(id) ON DELETE CASCADE;
+ALTER TABLE budgets ADD COLUMN household_id INT REFERENCES households(id) ON DELETE CASCADE;
+
+CREATE INDEX idx_accounts_household ON accounts (household_id);
+CREATE INDEX idx_categories_household ON categories (household_id);
+CREATE INDEX idx_budgets_household ON budgets (household_id);
+
+-- Access checks used by every query on those tables: personal rows by their owner,
+-- household rows by membership (any role may view; owners and editors may change)
+CREATE FUNCTION user_can_view(p_user_id INT, p_owner_id INT, p_household_id INT) RETURNS BOOLEAN
+LANGUAGE sql STABLE AS $$
+    SELECT CASE
+        WHEN p_household_id IS NULL THEN p_owner_id = p_user_id
+        ELSE EXISTS (
+            SELECT 1 FROM household_members
+            WHERE household_id = p_household_id AND user_id = p_user_id
+        )
+    END
+$$;
+
+CREATE FUNCTION user_can_edit(p_user_id INT, p_owner_id INT, p_household_id INT) RETURNS BOOLEAN
+LANGUAGE sql STABLE AS $$
+    SELECT CASE
+        WHEN p_household_id IS NULL THEN p_owner_id = p_user_id
+        ELSE EXISTS (
+            SELECT 1 FROM household_members
+            WHERE household_id = p_household_id AND user_id = p_user_id AND role IN ('owner', 'editor')
+        )
+    END
+$$;
@@ -0,0 +1,13 @@
+-- idempotency_keys table (responses to write requests sent with an Idempotency-Key header,
+-- replayed when a client retries the same request; kept for 24 hours)
+CREATE TABLE idempotency_keys (
+    user_id INT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    idempotency_key VARCHAR(255) NOT NULL,
+    request_hash VARCHAR(64) NOT NULL, -- SHA-256 hex of method, path and body
+    response_status SMALLINT, -- NULL while the first request is still being handled
+    response_body BYTEA,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, idempotency_key)
+);
+
+CREATE INDEX idx_idempotency_keys_created ON idempotency_keys (created_at);
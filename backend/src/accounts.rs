@@ -1,6 +1,5 @@
 use axum::{
     extract::State,
-    http::HeaderMap,
     Json,
 };
 use sqlx::{PgPool, Row};
@@ -10,29 +9,42 @@ use crate::models::{
     UpdateAccountRequest, UpdateAccountResponse,
     DeleteAccountResponse, Account,
 };
-use crate::auth::AppError;
-use crate::middleware;
-
+use crate::auth::AuthUser;
+use crate::error::AppError;
+use crate::validation::ValidatedJson;
+use crate::api_tokens::Scope;
+use crate::households;
+
+/// Creates an account, personal or in a household the caller can edit.
+#[utoipa::path(
+    post,
+    path = "/api/v1/accounts",
+    tag = "accounts",
+    request_body = CreateAccountRequest,
+    responses((status = 200, body = CreateAccountResponse)),
+)]
 pub async fn create_account(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
-    Json(req): Json<CreateAccountRequest>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<CreateAccountRequest>,
 ) -> Result<Json<CreateAccountResponse>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::AccountsWrite)?;
 
-    if req.name.is_empty() || req.name.len() > 50 {
-        return Err(AppError::BadRequest("Account name must be between 1 and 50 characters".to_string()));
+    if let Some(household_id) = req.household_id {
+        households::require_editor(&pool, household_id, auth.user_id).await?;
     }
 
-    let currency = req.currency.unwrap_or_else(|| "USD".to_string());
+    let currency = req.currency.unwrap_or_default();
 
     // Use account name as type (users can create custom account names)
     let row = sqlx::query(
-        "INSERT INTO accounts (user_id, name, type, currency) VALUES ($1, $2, $2, $3) RETURNING id, user_id, name, currency, balance::text, created_at"
+        "INSERT INTO accounts (user_id, name, type, currency, is_liability, household_id) VALUES ($1, $2, $2, $3, $4, $5) RETURNING id, user_id, name, currency, balance, is_liability, created_at, household_id"
     )
     .bind(auth.user_id)
-    .bind(&req.name)
-    .bind(&currency)
+    .bind(req.name.as_str())
+    .bind(currency.as_str())
+    .bind(req.is_liability.unwrap_or(false))
+    .bind(req.household_id)
     .fetch_one(&pool)
     .await
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
@@ -40,10 +52,12 @@ pub async fn create_account(
     let account = Account {
         id: row.get(0),
         user_id: row.get(1),
+        household_id: row.get(7),
         name: row.get(2),
         currency: row.get(3),
         balance: row.get(4),
-        created_at: row.get(5),
+        is_liability: row.get(5),
+        created_at: row.get(6),
     };
 
     Ok(Json(CreateAccountResponse {
@@ -52,14 +66,21 @@ pub async fn create_account(
     }))
 }
 
+/// Lists the user's personal accounts and those of their households.
+#[utoipa::path(
+    get,
+    path = "/api/v1/accounts",
+    tag = "accounts",
+    responses((status = 200, body = Vec<AccountResponse>)),
+)]
 pub async fn get_accounts(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
+    auth: AuthUser,
 ) -> Result<Json<Vec<AccountResponse>>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::Read)?;
 
     let rows = sqlx::query(
-        "SELECT id, user_id, name, currency, balance::text, created_at FROM accounts WHERE user_id = $1 ORDER BY created_at DESC"
+        "SELECT id, user_id, name, currency, balance, is_liability, created_at, household_id FROM accounts WHERE user_can_view($1, user_id, household_id) ORDER BY created_at DESC"
     )
     .bind(auth.user_id)
     .fetch_all(&pool)
@@ -71,10 +92,12 @@ pub async fn get_accounts(
         .map(|row| Account {
             id: row.get(0),
             user_id: row.get(1),
+            household_id: row.get(7),
             name: row.get(2),
             currency: row.get(3),
             balance: row.get(4),
-            created_at: row.get(5),
+            is_liability: row.get(5),
+            created_at: row.get(6),
         })
         .collect();
 
@@ -86,40 +109,51 @@ pub async fn get_accounts(
     Ok(Json(accounts_response))
 }
 
+/// Renames an account and, if `is_liability` is given, changes whether the net worth report
+/// counts it as a debt.
+#[utoipa::path(
+    put,
+    path = "/api/v1/accounts/{id}",
+    tag = "accounts",
+    params(("id" = i32, Path, description = "Account id")),
+    request_body = UpdateAccountRequest,
+    responses((status = 200, body = UpdateAccountResponse)),
+)]
 pub async fn update_account(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
+    auth: AuthUser,
     axum::extract::Path(account_id): axum::extract::Path<i32>,
-    Json(req): Json<UpdateAccountRequest>,
+    ValidatedJson(req): ValidatedJson<UpdateAccountRequest>,
 ) -> Result<Json<UpdateAccountResponse>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
-
-    if req.name.is_empty() || req.name.len() > 50 {
-        return Err(AppError::BadRequest("Account name must be between 1 and 50 characters".to_string()));
-    }
+    auth.require_scope(Scope::AccountsWrite)?;
 
     let row = sqlx::query(
-        "UPDATE accounts SET name = $1, type = $1 WHERE id = $2 AND user_id = $3 RETURNING id, user_id, name, currency, balance::text, created_at"
+        "UPDATE accounts SET name = $1, type = $1, is_liability = COALESCE($4, is_liability)
+         WHERE id = $2 AND user_can_edit($3, user_id, household_id)
+         RETURNING id, user_id, name, currency, balance, is_liability, created_at, household_id"
     )
-    .bind(&req.name)
+    .bind(req.name.as_str())
     .bind(account_id)
     .bind(auth.user_id)
+    .bind(req.is_liability)
     .fetch_optional(&pool)
     .await
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
     let row = match row {
         Some(r) => r,
-        None => return Err(AppError::BadRequest("Account not found or you don't have permission to update it".to_string())),
+        None => return Err(AppError::NotFound("Account not found or you don't have permission to update it".to_string())),
     };
 
     let account = Account {
         id: row.get(0),
         user_id: row.get(1),
+        household_id: row.get(7),
         name: row.get(2),
         currency: row.get(3),
         balance: row.get(4),
-        created_at: row.get(5),
+        is_liability: row.get(5),
+        created_at: row.get(6),
     };
 
     Ok(Json(UpdateAccountResponse {
@@ -128,15 +162,23 @@ pub async fn update_account(
     }))
 }
 
+/// Deletes an account together with its transactions.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/accounts/{id}",
+    tag = "accounts",
+    params(("id" = i32, Path, description = "Account id")),
+    responses((status = 200, body = DeleteAccountResponse)),
+)]
 pub async fn delete_account(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
+    auth: AuthUser,
     axum::extract::Path(account_id): axum::extract::Path<i32>,
 ) -> Result<Json<DeleteAccountResponse>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::AccountsWrite)?;
 
     let result = sqlx::query(
-        "DELETE FROM accounts WHERE id = $1 AND user_id = $2"
+        "DELETE FROM accounts WHERE id = $1 AND user_can_edit($2, user_id, household_id)"
     )
     .bind(account_id)
     .bind(auth.user_id)
@@ -145,7 +187,7 @@ pub async fn delete_account(
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
     if result.rows_affected() == 0 {
-        return Err(AppError::BadRequest("Account not found or you don't have permission to delete it".to_string()));
+        return Err(AppError::NotFound("Account not found or you don't have permission to delete it".to_string()));
     }
 
     Ok(Json(DeleteAccountResponse {
@@ -160,6 +202,8 @@ fn account_to_response(account: Account) -> AccountResponse {
         name: account.name,
         currency: account.currency,
         balance: account.balance,
+        is_liability: account.is_liability,
+        household_id: account.household_id,
         created_at: account.created_at,
     }
 }
@@ -0,0 +1,204 @@
+use std::sync::Arc;
+use axum::{
+    extract::{State, Path},
+    Json,
+};
+use chrono::{Duration, Utc};
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::models::{
+    CreateApiTokenRequest, CreateApiTokenResponse, ApiTokenResponse, DeleteApiTokenResponse,
+};
+use crate::auth::{self, AuthUser};
+use crate::error::AppError;
+use crate::validation::ValidatedJson;
+use crate::session_cache::SessionCache;
+
+/// Marks personal API tokens so the auth middleware knows which table to look them up in.
+pub const TOKEN_PREFIX: &str = "pft_";
+
+const DEFAULT_EXPIRY_DAYS: i64 = 90;
+const MAX_EXPIRY_DAYS: i64 = 365;
+
+/// What an API token may do. Interactive sessions are not scoped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Read,
+    AccountsWrite,
+    TransactionsWrite,
+    CategoriesWrite,
+    BudgetsWrite,
+    RulesWrite,
+    PayeesWrite,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 7] = [
+        Scope::Read,
+        Scope::AccountsWrite,
+        Scope::TransactionsWrite,
+        Scope::CategoriesWrite,
+        Scope::BudgetsWrite,
+        Scope::RulesWrite,
+        Scope::PayeesWrite,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::AccountsWrite => "accounts:write",
+            Scope::TransactionsWrite => "transactions:write",
+            Scope::CategoriesWrite => "categories:write",
+            Scope::BudgetsWrite => "budgets:write",
+            Scope::RulesWrite => "rules:write",
+            Scope::PayeesWrite => "payees:write",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Scope> {
+        Scope::ALL.into_iter().find(|scope| scope.as_str() == s)
+    }
+}
+
+fn parse_scopes(requested: &[String]) -> Result<Vec<Scope>, AppError> {
+    if requested.is_empty() {
+        return Err(AppError::field("scopes", "At least one scope is required"));
+    }
+
+    let mut scopes = Vec::new();
+    for name in requested {
+        let scope = Scope::parse(name.trim()).ok_or_else(|| {
+            let known: Vec<&str> = Scope::ALL.iter().map(Scope::as_str).collect();
+            AppError::field("scopes", format!("Unknown scope '{}'. Valid scopes: {}", name, known.join(", ")))
+        })?;
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+    Ok(scopes)
+}
+
+/// Creates a token. The plaintext is returned only in this response.
+#[utoipa::path(
+    post,
+    path = "/api/v1/tokens",
+    tag = "tokens",
+    request_body = CreateApiTokenRequest,
+    responses((status = 200, body = CreateApiTokenResponse)),
+)]
+pub async fn create_token(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<CreateApiTokenRequest>,
+) -> Result<Json<CreateApiTokenResponse>, AppError> {
+    auth.require_session()?;
+
+    let name = req.name.as_str();
+
+    let scopes = parse_scopes(&req.scopes)?;
+    let scope_names: Vec<String> = scopes.iter().map(|s| s.as_str().to_string()).collect();
+
+    let days = req.expires_in_days.unwrap_or(DEFAULT_EXPIRY_DAYS);
+    if !(1..=MAX_EXPIRY_DAYS).contains(&days) {
+        return Err(AppError::field("expires_in_days", format!("expires_in_days must be between 1 and {}", MAX_EXPIRY_DAYS)));
+    }
+    let expires_at = Utc::now() + Duration::days(days);
+
+    let token = format!("{}{}", TOKEN_PREFIX, Uuid::new_v4().simple());
+    let token_prefix: String = token.chars().take(TOKEN_PREFIX.len() + 6).collect();
+
+    let id: i32 = sqlx::query_scalar(
+        "INSERT INTO api_tokens (user_id, name, token_hash, token_prefix, scopes, expires_at)
+         VALUES ($1, $2, $3, $4, $5, $6) RETURNING id"
+    )
+    .bind(auth.user_id)
+    .bind(name)
+    .bind(auth::hash_token(&token))
+    .bind(&token_prefix)
+    .bind(&scope_names)
+    .bind(expires_at)
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(Json(CreateApiTokenResponse {
+        message: "API token created. Copy it now, it will not be shown again".to_string(),
+        id,
+        token,
+        scopes: scope_names,
+        expires_at,
+    }))
+}
+
+/// Lists the user's API tokens without their secrets.
+#[utoipa::path(
+    get,
+    path = "/api/v1/tokens",
+    tag = "tokens",
+    responses((status = 200, body = Vec<ApiTokenResponse>)),
+)]
+pub async fn get_tokens(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+) -> Result<Json<Vec<ApiTokenResponse>>, AppError> {
+    auth.require_session()?;
+
+    let rows = sqlx::query(
+        "SELECT id, name, token_prefix, scopes, created_at, expires_at, last_used_at
+         FROM api_tokens WHERE user_id = $1 ORDER BY created_at DESC"
+    )
+    .bind(auth.user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let tokens = rows
+        .into_iter()
+        .map(|row| ApiTokenResponse {
+            id: row.get(0),
+            name: row.get(1),
+            token_prefix: row.get(2),
+            scopes: row.get(3),
+            created_at: row.get(4),
+            expires_at: row.get(5),
+            last_used_at: row.get(6),
+        })
+        .collect();
+
+    Ok(Json(tokens))
+}
+
+/// Revokes an API token.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tokens/{id}",
+    tag = "tokens",
+    params(("id" = i32, Path, description = "API token id")),
+    responses((status = 200, body = DeleteApiTokenResponse)),
+)]
+pub async fn delete_token(
+    State(pool): State<PgPool>,
+    State(cache): State<Arc<SessionCache>>,
+    auth: AuthUser,
+    Path(token_id): Path<i32>,
+) -> Result<Json<DeleteApiTokenResponse>, AppError> {
+    auth.require_session()?;
+
+    let result = sqlx::query("DELETE FROM api_tokens WHERE id = $1 AND user_id = $2")
+        .bind(token_id)
+        .bind(auth.user_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("API token not found or you don't have permission to delete it".to_string()));
+    }
+
+    cache.invalidate_user(auth.user_id);
+
+    Ok(Json(DeleteApiTokenResponse {
+        message: "API token deleted".to_string(),
+    }))
+}
@@ -1,29 +1,50 @@
 use axum::{
-    extract::State,
-    http::{HeaderMap, StatusCode},
-    response::{IntoResponse, Response},
+    extract::{ConnectInfo, State},
+    http::HeaderMap,
     Json,
 };
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::{net::SocketAddr, sync::{Arc, LazyLock}};
 use uuid::Uuid;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Utc};
 
 use crate::models::{
     RegisterRequest, RegisterResponse, LoginRequest, LoginResponse,
-    LogoutResponse, ErrorResponse, User,
+    LogoutResponse, User, RefreshRequest, LoginResult,
 };
+
+use crate::config::{AuthConfig, Config, LoginConfig};
 use crate::middleware;
+use crate::rate_limit::{self, LoginRateLimiter};
+use crate::password_policy::PasswordPolicy;
+use crate::two_factor;
+use crate::error::AppError;
+use crate::validation::ValidatedJson;
+use crate::session_cache::SessionCache;
+use crate::api_tokens::Scope;
 
+/// Creates a user. The password must satisfy the server's password policy. Refused with
+/// `403` when registration is turned off in the server configuration.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/register",
+    tag = "auth",
+    request_body = RegisterRequest,
+    responses((status = 200, body = RegisterResponse)),
+    security(()),
+)]
 pub async fn register(
     State(pool): State<PgPool>,
-    Json(req): Json<RegisterRequest>,
+    State(config): State<Arc<Config>>,
+    State(policy): State<Arc<PasswordPolicy>>,
+    ValidatedJson(req): ValidatedJson<RegisterRequest>,
 ) -> Result<Json<RegisterResponse>, AppError> {
-    if req.username.is_empty() || req.username.len() > 50 {
-        return Err(AppError::BadRequest("Username must be between 1 and 50 characters".to_string()));
-    }
-    if req.password.is_empty() || req.password.len() < 6 {
-        return Err(AppError::BadRequest("Password must be at least 6 characters".to_string()));
+    if !config.features.registration {
+        return Err(AppError::Forbidden("Registration is disabled on this server".to_string()));
     }
+    validate_username(&req.username)?;
+    policy.validate(&req.password, &[&req.username])
+        .map_err(|msg| AppError::field("password", msg))?;
 
     let password_hash = hash_password(&req.password)?;
 
@@ -35,10 +56,10 @@ pub async fn register(
     .fetch_one(&pool)
     .await
     .map_err(|e| {
-        if let sqlx::Error::Database(db_err) = &e {
-            if db_err.constraint() == Some("users_username_key") {
-                return AppError::BadRequest("Username already exists".to_string());
-            }
+        if let sqlx::Error::Database(db_err) = &e
+            && db_err.constraint() == Some("users_username_key")
+        {
+            return AppError::Conflict("Username already exists".to_string());
         }
         AppError::InternalServerError(format!("Database error: {}", e))
     })?;
@@ -75,67 +96,364 @@ pub async fn register(
     }))
 }
 
+/// Signs in with username and password. Users with 2FA get a challenge to finish with `/api/auth/2fa/verify` instead of a session.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses((status = 200, body = LoginResult)),
+    security(()),
+)]
 pub async fn login(
     State(pool): State<PgPool>,
-    Json(req): Json<LoginRequest>,
-) -> Result<Json<LoginResponse>, AppError> {
+    State(config): State<Arc<Config>>,
+    State(limiter): State<Arc<LoginRateLimiter>>,
+    State(policy): State<Arc<PasswordPolicy>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    ValidatedJson(req): ValidatedJson<LoginRequest>,
+) -> Result<Json<LoginResult>, AppError> {
+    let client = ClientInfo::from_request(&headers, &addr);
+    let ip = rate_limit::client_key(&headers, &addr, &config.server.trusted_proxies);
+
+    if let Err(wait) = limiter.check(ip) {
+        tracing::warn!("Login rate limit hit for {}", ip);
+        return Err(AppError::RateLimited {
+            message: "Too many login attempts, please slow down".to_string(),
+            retry_after_secs: wait.as_secs().max(1),
+        });
+    }
+
     let user = sqlx::query_as::<_, User>(
-        "SELECT id, username, password_hash, created_at FROM users WHERE username = $1"
+        "SELECT id, username, password_hash, created_at, failed_login_attempts, locked_until, totp_enabled
+         FROM users WHERE username = $1"
     )
     .bind(&req.username)
     .fetch_optional(&pool)
     .await
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
-    let user = match user {
-        Some(u) => u,
-        None => return Err(AppError::Unauthorized("Invalid username or password".to_string())),
+    if let Some(user) = &user
+        && let Some(locked_until) = user.locked_until
+        && locked_until > Utc::now()
+    {
+        tracing::warn!("Login to locked account '{}' refused (from {})", user.username, client.ip_address);
+        return Err(account_locked(locked_until));
+    }
+
+    // Over-long input is rejected without hashing it; no valid password can be that long.
+    // Unknown usernames are checked against a dummy hash so wrong passwords and unknown
+    // usernames take about as long to answer
+    let password_ok = req.password.chars().count() <= policy.max_length()
+        && verify_password(&req.password, user.as_ref().map_or(&*DUMMY_PASSWORD_HASH, |u| &u.password_hash))?;
+
+    let Some(user) = user else {
+        return Err(AppError::Unauthorized("Invalid username or password".to_string()));
     };
 
-    if !verify_password(&req.password, &user.password_hash)? {
+    if !password_ok {
+        record_failed_login(&pool, &config.login, user.id, &req.username, &client.ip_address).await?;
         return Err(AppError::Unauthorized("Invalid username or password".to_string()));
     }
 
+    // The failure count is only cleared once the second factor has been checked too,
+    // otherwise knowing the password would allow unlimited guesses at the code
+    if user.totp_enabled {
+        let challenge = two_factor::create_challenge(&pool, user.id).await?;
+        return Ok(Json(LoginResult::TwoFactorRequired(challenge)));
+    }
+
+    sqlx::query("UPDATE users SET failed_login_attempts = 0, locked_until = NULL WHERE id = $1")
+        .bind(user.id)
+        .execute(&pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let response = start_session(&pool, &config.auth, user.id, &client, "Login successful").await?;
+    Ok(Json(LoginResult::Session(response)))
+}
+
+/// Issues the first access/refresh pair of a new token family.
+pub async fn start_session(
+    pool: &PgPool,
+    auth: &AuthConfig,
+    user_id: i32,
+    client: &ClientInfo,
+    message: &str,
+) -> Result<LoginResponse, AppError> {
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let family_id = Uuid::new_v4().to_string();
+    let response = issue_token_pair(&mut tx, auth, user_id, &family_id, client, message).await?;
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(response)
+}
+
+/// `429` for a locked username, with `Retry-After` set to when the lockout ends. Attempts
+/// during the lockout don't extend it.
+pub fn account_locked(locked_until: DateTime<Utc>) -> AppError {
+    AppError::RateLimited {
+        message: "Account temporarily locked after repeated failed logins".to_string(),
+        retry_after_secs: (locked_until - Utc::now()).num_seconds().max(1) as u64,
+    }
+}
+
+/// Bumps the consecutive-failure counter and, past the threshold, locks the username
+/// with a lockout that doubles on every further failure. Wrong 2FA codes count too.
+pub async fn record_failed_login(
+    pool: &PgPool,
+    config: &LoginConfig,
+    user_id: i32,
+    username: &str,
+    ip: &str,
+) -> Result<(), AppError> {
+    let failures = sqlx::query_scalar::<_, i32>(
+        "UPDATE users SET failed_login_attempts = failed_login_attempts + 1
+         WHERE id = $1 RETURNING failed_login_attempts"
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    if let Some(minutes) = rate_limit::lockout_minutes(config, failures) {
+        sqlx::query("UPDATE users SET locked_until = NOW() + make_interval(mins => $1) WHERE id = $2")
+            .bind(minutes as i32)
+            .bind(user_id)
+            .execute(pool)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+        tracing::warn!(
+            "Account '{}' locked for {} minute(s) after {} failed logins (last from {})",
+            username, minutes, failures, ip
+        );
+    }
+
+    Ok(())
+}
+
+/// Exchanges a refresh token for a new access/refresh pair. Refresh tokens are single-use:
+/// presenting one that was already rotated is treated as theft and revokes the whole family.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/refresh",
+    tag = "auth",
+    request_body = RefreshRequest,
+    responses((status = 200, body = LoginResponse)),
+    security(()),
+)]
+pub async fn refresh(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<Config>>,
+    State(cache): State<Arc<SessionCache>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    ValidatedJson(req): ValidatedJson<RefreshRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let row = sqlx::query(
+        "SELECT id, user_id, family_id, expires_at, used_at IS NOT NULL, revoked
+         FROM refresh_tokens WHERE token_hash = $1 FOR UPDATE"
+    )
+    .bind(hash_token(&req.refresh_token))
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?
+    .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".to_string()))?;
+
+    let id: i32 = row.get(0);
+    let user_id: i32 = row.get(1);
+    let family_id: String = row.get(2);
+    let expires_at: chrono::DateTime<Utc> = row.get(3);
+    let used: bool = row.get(4);
+    let revoked: bool = row.get(5);
+
+    if used {
+        revoke_family(&mut tx, &family_id).await?;
+        tx.commit().await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+        tracing::warn!("Refresh token reuse detected for user {}; revoked token family {}", user_id, family_id);
+        cache.invalidate_user(user_id);
+        return Err(AppError::Unauthorized("Refresh token has already been used; all sessions from this login have been revoked".to_string()));
+    }
+    if revoked {
+        return Err(AppError::Unauthorized("Refresh token has been revoked".to_string()));
+    }
+    if expires_at < Utc::now() {
+        return Err(AppError::Unauthorized("Refresh token has expired".to_string()));
+    }
+
+    sqlx::query("UPDATE refresh_tokens SET used_at = NOW() WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    // The access token issued alongside the old refresh token is superseded by the new one
+    sqlx::query("UPDATE sessions SET is_valid = FALSE WHERE family_id = $1")
+        .bind(&family_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let client = ClientInfo::from_request(&headers, &addr);
+    let response = issue_token_pair(&mut tx, &config.auth, user_id, &family_id, &client, "Token refreshed").await?;
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    cache.invalidate_user(user_id);
+
+    Ok(Json(response))
+}
+
+/// Where a login or refresh came from, recorded on the session for the device list.
+pub struct ClientInfo {
+    pub ip_address: String,
+    pub user_agent: Option<String>,
+}
+
+impl ClientInfo {
+    pub fn from_request(headers: &HeaderMap, addr: &SocketAddr) -> Self {
+        Self {
+            ip_address: middleware::client_ip(headers, addr),
+            user_agent: middleware::user_agent(headers),
+        }
+    }
+}
+
+async fn issue_token_pair(
+    tx: &mut Transaction<'_, Postgres>,
+    auth: &AuthConfig,
+    user_id: i32,
+    family_id: &str,
+    client: &ClientInfo,
+    message: &str,
+) -> Result<LoginResponse, AppError> {
     let token = Uuid::new_v4().to_string();
-    let expires_at = Utc::now() + Duration::hours(24);
+    let expires_at = Utc::now() + auth.access_token_ttl();
+    let refresh_token = Uuid::new_v4().to_string();
+    let refresh_expires_at = Utc::now() + auth.refresh_token_ttl();
 
     sqlx::query(
-        "INSERT INTO sessions (user_id, token, expires_at) VALUES ($1, $2, $3)"
+        "INSERT INTO sessions (user_id, token_hash, expires_at, family_id, user_agent, ip_address, last_used_at)
+         VALUES ($1, $2, $3, $4, $5, $6, NOW())"
     )
-    .bind(user.id)
-    .bind(&token)
+    .bind(user_id)
+    .bind(hash_token(&token))
     .bind(expires_at)
-    .execute(&pool)
+    .bind(family_id)
+    .bind(&client.user_agent)
+    .bind(&client.ip_address)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    sqlx::query(
+        "INSERT INTO refresh_tokens (user_id, family_id, token_hash, expires_at) VALUES ($1, $2, $3, $4)"
+    )
+    .bind(user_id)
+    .bind(family_id)
+    .bind(hash_token(&refresh_token))
+    .bind(refresh_expires_at)
+    .execute(&mut **tx)
     .await
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
-    Ok(Json(LoginResponse {
-        message: "Login successful".to_string(),
+    Ok(LoginResponse {
+        message: message.to_string(),
         token,
-        user_id: user.id,
-    }))
+        expires_at,
+        refresh_token,
+        refresh_expires_at,
+        user_id,
+    })
 }
 
+/// Invalidates every access and refresh token descended from one login.
+pub async fn revoke_family(tx: &mut Transaction<'_, Postgres>, family_id: &str) -> Result<(), AppError> {
+    sqlx::query("UPDATE refresh_tokens SET revoked = TRUE WHERE family_id = $1")
+        .bind(family_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    sqlx::query("UPDATE sessions SET is_valid = FALSE WHERE family_id = $1")
+        .bind(family_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(())
+}
+
+/// Signs out the calling session, including its refresh tokens.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout",
+    tag = "auth",
+    responses((status = 200, body = LogoutResponse)),
+)]
 pub async fn logout(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
+    State(cache): State<Arc<SessionCache>>,
+    auth: AuthUser,
 ) -> Result<Json<LogoutResponse>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
-    
-    sqlx::query(
-        "UPDATE sessions SET is_valid = FALSE WHERE token = $1"
+    let session_id = auth.require_session()?;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let family_id = sqlx::query_scalar::<_, Option<String>>(
+        "UPDATE sessions SET is_valid = FALSE WHERE id = $1 RETURNING family_id"
     )
-    .bind(&auth.token)
-    .execute(&pool)
+    .bind(session_id)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
+    // Logging out also ends the refresh chain, otherwise the session could be revived
+    if let Some(family_id) = family_id {
+        revoke_family(&mut tx, &family_id).await?;
+    }
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    cache.invalidate_user(auth.user_id);
+
     Ok(Json(LogoutResponse {
         message: "Logout successful".to_string(),
     }))
 }
 
-fn hash_password(password: &str) -> Result<String, AppError> {
+/// Hex SHA-256 of a bearer or refresh token; only this digest is stored in the database.
+/// Tokens are random UUIDs, so an unsalted fast hash is enough to make a leaked table useless.
+pub fn hash_token(token: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+pub fn validate_username(username: &str) -> Result<(), AppError> {
+    if username.is_empty() || username.chars().count() > 50 {
+        return Err(AppError::field("username", "Username must be between 1 and 50 characters"));
+    }
+    Ok(())
+}
+
+pub fn hash_password(password: &str) -> Result<String, AppError> {
     use argon2::{
         password_hash::{rand_core::OsRng, PasswordHasher, SaltString},
         Argon2,
@@ -150,7 +468,13 @@ fn hash_password(password: &str) -> Result<String, AppError> {
     Ok(password_hash.to_string())
 }
 
-fn verify_password(password: &str, hash: &str) -> Result<bool, AppError> {
+/// Hash of a random password, verified against when the username doesn't exist so that
+/// unknown usernames take as long to reject as wrong passwords.
+static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| {
+    hash_password(&Uuid::new_v4().to_string()).expect("hashing a fixed-length password cannot fail")
+});
+
+pub fn verify_password(password: &str, hash: &str) -> Result<bool, AppError> {
     use argon2::{
         password_hash::{PasswordHash, PasswordVerifier},
         Argon2,
@@ -167,38 +491,45 @@ fn verify_password(password: &str, hash: &str) -> Result<bool, AppError> {
     }
 }
 
+/// The caller behind a request: an interactive login session or a personal API token.
 #[derive(Debug, Clone)]
-#[allow(dead_code)]
 pub struct AuthUser {
     pub user_id: i32,
-    pub token: String,
+    pub session_id: Option<i32>,
+    /// `None` for sessions, which may do anything the user can.
+    pub scopes: Option<Vec<Scope>>,
 }
 
-#[derive(Debug)]
-pub enum AppError {
-    BadRequest(String),
-    Unauthorized(String),
-    InternalServerError(String),
+impl AuthUser {
+    pub fn require_scope(&self, scope: Scope) -> Result<(), AppError> {
+        match &self.scopes {
+            Some(scopes) if !scopes.contains(&scope) => Err(AppError::Forbidden(format!(
+                "This API token lacks the '{}' scope", scope.as_str()
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Account, credential and token management is only open to interactive logins,
+    /// so a leaked API token can't be used to lock the owner out.
+    pub fn require_session(&self) -> Result<i32, AppError> {
+        self.session_id.ok_or_else(|| {
+            AppError::Forbidden("API tokens cannot be used for this endpoint, log in instead".to_string())
+        })
+    }
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
-            AppError::InternalServerError(msg) => {
-                eprintln!("Internal server error: {}", msg);
-                let is_dev = std::env::var("RUST_ENV").unwrap_or_default() == "development";
-                let response_msg = if is_dev {
-                    msg
-                } else {
-                    "Internal server error".to_string()
-                };
-                (StatusCode::INTERNAL_SERVER_ERROR, response_msg)
-            }
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        (status, Json(ErrorResponse { error: error_message })).into_response()
+    #[test]
+    fn username_length_counts_characters() {
+        assert!(validate_username("").is_err());
+        assert!(validate_username(&"a".repeat(50)).is_ok());
+        assert!(validate_username(&"a".repeat(51)).is_err());
+        // 50 characters but 100 bytes, which still fits the VARCHAR(50) column
+        assert!(validate_username(&"é".repeat(50)).is_ok());
+        assert!(validate_username(&"é".repeat(51)).is_err());
     }
 }
-
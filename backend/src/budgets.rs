@@ -1,82 +1,88 @@
 use axum::{
     extract::{State, Path},
-    http::HeaderMap,
     Json,
 };
 use sqlx::{PgPool, Row};
 use chrono::{NaiveDate, Utc};
+use rust_decimal::Decimal;
 
 use crate::models::{
     CreateBudgetRequest, CreateBudgetResponse, BudgetResponse,
     UpdateBudgetRequest, UpdateBudgetResponse,
     DeleteBudgetResponse,
 };
-use crate::auth::AppError;
-use crate::middleware;
-
+use crate::auth::AuthUser;
+use crate::error::AppError;
+use crate::validation::ValidatedJson;
+use crate::api_tokens::Scope;
+use crate::households;
+
+/// Creates a budget for a category, or for all spending when no category is given.
+#[utoipa::path(
+    post,
+    path = "/api/v1/budgets",
+    tag = "budgets",
+    request_body = CreateBudgetRequest,
+    responses((status = 200, body = CreateBudgetResponse)),
+)]
 pub async fn create_budget(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
-    Json(req): Json<CreateBudgetRequest>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<CreateBudgetRequest>,
 ) -> Result<Json<CreateBudgetResponse>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::BudgetsWrite)?;
 
-    // Validate amount
-    let amount_str = req.amount.trim();
-    let _amount_decimal: rust_decimal::Decimal = amount_str.parse()
-        .map_err(|_| AppError::BadRequest("Invalid amount format".to_string()))?;
+    let amount = req.amount.value();
+    if amount <= Decimal::ZERO {
+        return Err(AppError::field("amount", "Budget amount must be positive"));
+    }
 
-    if amount_str.parse::<f64>().unwrap_or(0.0) <= 0.0 {
-        return Err(AppError::BadRequest("Budget amount must be positive".to_string()));
+    if let Some(household_id) = req.household_id {
+        households::require_editor(&pool, household_id, auth.user_id).await?;
     }
 
-    // If category_id is provided, verify it belongs to user
+    // If category_id is provided, verify the user can see it and it lives in the same household
     if let Some(category_id) = req.category_id {
         let category_exists = sqlx::query_scalar::<_, bool>(
-            "SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1 AND user_id = $2)"
+            "SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1 AND household_id IS NOT DISTINCT FROM $3 AND user_can_view($2, user_id, household_id))"
         )
         .bind(category_id)
         .bind(auth.user_id)
+        .bind(req.household_id)
         .fetch_one(&pool)
         .await
         .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
         if !category_exists {
-            return Err(AppError::BadRequest("Category not found or you don't have permission to use it".to_string()));
+            return Err(AppError::field("category_id", "Category not found or you don't have permission to use it"));
         }
     }
 
-    // Parse start_date
-    let start_date = if let Some(date_str) = req.start_date {
-        NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-            .map_err(|_| AppError::BadRequest("Invalid start_date format. Use YYYY-MM-DD".to_string()))?
-    } else {
-        Utc::now().date_naive()
-    };
+    let start_date = req.start_date.map_or_else(|| Utc::now().date_naive(), |d| d.value());
 
     let period = req.period.unwrap_or_else(|| "monthly".to_string());
 
     // Insert budget
     let row = sqlx::query(
-        "INSERT INTO budgets (user_id, category_id, amount, period, start_date) 
-         VALUES ($1, $2, $3::numeric, $4, $5) 
-         RETURNING id, user_id, category_id, amount::text, period, start_date, created_at, updated_at"
+        "INSERT INTO budgets (user_id, category_id, amount, period, start_date, household_id) 
+         VALUES ($1, $2, $3, $4, $5, $6) 
+         RETURNING id, user_id, category_id, amount, period, start_date, created_at, updated_at"
     )
     .bind(auth.user_id)
-    .bind(&req.category_id)
-    .bind(&amount_str)
+    .bind(req.category_id)
+    .bind(amount)
     .bind(&period)
     .bind(start_date)
+    .bind(req.household_id)
     .fetch_one(&pool)
     .await
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
     let budget_id: i32 = row.get(0);
-    let spent = calculate_spent(&pool, auth.user_id, req.category_id, &start_date, &period).await?;
-    let budget_amount: rust_decimal::Decimal = amount_str.parse().unwrap();
-    let spent_decimal: rust_decimal::Decimal = spent.parse().unwrap_or(rust_decimal::Decimal::ZERO);
-    let remaining = budget_amount - spent_decimal;
-    let is_over_budget = spent_decimal > budget_amount;
+    let spent = calculate_spent(&pool, auth.user_id, req.household_id, req.category_id, &start_date, &period).await?;
+    let budget_amount: Decimal = row.get(3);
+    let remaining = budget_amount - spent;
+    let is_over_budget = spent > budget_amount;
 
     let category_name = if let Some(cat_id) = req.category_id {
         sqlx::query_scalar::<_, Option<String>>(
@@ -93,13 +99,14 @@ pub async fn create_budget(
 
     let budget = BudgetResponse {
         id: budget_id,
+        household_id: req.household_id,
         category_id: req.category_id,
         category_name,
-        amount: amount_str.to_string(),
+        amount: budget_amount,
         period: Some(period),
         start_date,
         spent,
-        remaining: remaining.to_string(),
+        remaining,
         is_over_budget,
         created_at: row.get(6),
         updated_at: row.get(7),
@@ -111,15 +118,22 @@ pub async fn create_budget(
     }))
 }
 
+/// Lists budgets with what has been spent against them in the current period.
+#[utoipa::path(
+    get,
+    path = "/api/v1/budgets",
+    tag = "budgets",
+    responses((status = 200, body = Vec<BudgetResponse>)),
+)]
 pub async fn get_budgets(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
+    auth: AuthUser,
 ) -> Result<Json<Vec<BudgetResponse>>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::Read)?;
 
     let rows = sqlx::query(
-        "SELECT id, user_id, category_id, amount::text, period, start_date, created_at, updated_at 
-         FROM budgets WHERE user_id = $1 ORDER BY created_at DESC"
+        "SELECT id, user_id, category_id, amount, period, start_date, created_at, updated_at, household_id 
+         FROM budgets WHERE user_can_view($1, user_id, household_id) ORDER BY created_at DESC"
     )
     .bind(auth.user_id)
     .fetch_all(&pool)
@@ -131,11 +145,13 @@ pub async fn get_budgets(
     for row in rows {
         let budget_id: i32 = row.get(0);
         let category_id: Option<i32> = row.get(2);
-        let amount_str: String = row.get(3);
+        let budget_amount: Decimal = row.get(3);
         let period: Option<String> = row.get(4);
         let start_date: NaiveDate = row.get(5);
+        let owner_id: i32 = row.get(1);
+        let household_id: Option<i32> = row.get(8);
 
-        let spent = calculate_spent(&pool, auth.user_id, category_id, &start_date, period.as_deref().unwrap_or("monthly")).await?;
+        let spent = calculate_spent(&pool, owner_id, household_id, category_id, &start_date, period.as_deref().unwrap_or("monthly")).await?;
 
         let category_name = if let Some(cat_id) = category_id {
             sqlx::query_scalar::<_, Option<String>>(
@@ -150,20 +166,19 @@ pub async fn get_budgets(
             None
         };
 
-        let budget_amount: rust_decimal::Decimal = amount_str.parse().unwrap_or(rust_decimal::Decimal::ZERO);
-        let spent_decimal: rust_decimal::Decimal = spent.parse().unwrap_or(rust_decimal::Decimal::ZERO);
-        let remaining = budget_amount - spent_decimal;
-        let is_over_budget = spent_decimal > budget_amount;
+        let remaining = budget_amount - spent;
+        let is_over_budget = spent > budget_amount;
 
         budgets_response.push(BudgetResponse {
             id: budget_id,
+            household_id,
             category_id,
             category_name,
-            amount: amount_str,
+            amount: budget_amount,
             period,
             start_date,
             spent,
-            remaining: remaining.to_string(),
+            remaining,
             is_over_budget,
             created_at: row.get(6),
             updated_at: row.get(7),
@@ -173,38 +188,38 @@ pub async fn get_budgets(
     Ok(Json(budgets_response))
 }
 
+/// Changes a budget's amount, period or start date.
+#[utoipa::path(
+    put,
+    path = "/api/v1/budgets/{id}",
+    tag = "budgets",
+    params(("id" = i32, Path, description = "Budget id")),
+    request_body = UpdateBudgetRequest,
+    responses((status = 200, body = UpdateBudgetResponse)),
+)]
 pub async fn update_budget(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
+    auth: AuthUser,
     Path(budget_id): Path<i32>,
-    Json(req): Json<UpdateBudgetRequest>,
+    ValidatedJson(req): ValidatedJson<UpdateBudgetRequest>,
 ) -> Result<Json<UpdateBudgetResponse>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::BudgetsWrite)?;
 
-    // Validate amount
-    let amount_str = req.amount.trim();
-    let _amount_decimal: rust_decimal::Decimal = amount_str.parse()
-        .map_err(|_| AppError::BadRequest("Invalid amount format".to_string()))?;
-
-    if amount_str.parse::<f64>().unwrap_or(0.0) <= 0.0 {
-        return Err(AppError::BadRequest("Budget amount must be positive".to_string()));
+    let amount = req.amount.value();
+    if amount <= Decimal::ZERO {
+        return Err(AppError::field("amount", "Budget amount must be positive"));
     }
 
     let period = req.period.unwrap_or_else(|| "monthly".to_string());
-    let start_date = if let Some(date_str) = req.start_date {
-        NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-            .map_err(|_| AppError::BadRequest("Invalid start_date format. Use YYYY-MM-DD".to_string()))?
-    } else {
-        Utc::now().date_naive()
-    };
+    let start_date = req.start_date.map_or_else(|| Utc::now().date_naive(), |d| d.value());
 
     // Update budget
     let row = sqlx::query(
-        "UPDATE budgets SET amount = $1::numeric, period = $2, start_date = $3, updated_at = NOW() 
-         WHERE id = $4 AND user_id = $5 
-         RETURNING id, user_id, category_id, amount::text, period, start_date, created_at, updated_at"
+        "UPDATE budgets SET amount = $1, period = $2, start_date = $3, updated_at = NOW() 
+         WHERE id = $4 AND user_can_edit($5, user_id, household_id) 
+         RETURNING id, user_id, category_id, amount, period, start_date, created_at, updated_at, household_id"
     )
-    .bind(&amount_str)
+    .bind(amount)
     .bind(&period)
     .bind(start_date)
     .bind(budget_id)
@@ -215,11 +230,13 @@ pub async fn update_budget(
 
     let row = match row {
         Some(r) => r,
-        None => return Err(AppError::BadRequest("Budget not found or you don't have permission to update it".to_string())),
+        None => return Err(AppError::NotFound("Budget not found or you don't have permission to update it".to_string())),
     };
 
     let category_id: Option<i32> = row.get(2);
-    let spent = calculate_spent(&pool, auth.user_id, category_id, &start_date, &period).await?;
+    let owner_id: i32 = row.get(1);
+    let household_id: Option<i32> = row.get(8);
+    let spent = calculate_spent(&pool, owner_id, household_id, category_id, &start_date, &period).await?;
 
     let category_name = if let Some(cat_id) = category_id {
         sqlx::query_scalar::<_, Option<String>>(
@@ -234,20 +251,20 @@ pub async fn update_budget(
         None
     };
 
-    let budget_amount: rust_decimal::Decimal = amount_str.parse().unwrap_or(rust_decimal::Decimal::ZERO);
-    let spent_decimal: rust_decimal::Decimal = spent.parse().unwrap_or(rust_decimal::Decimal::ZERO);
-    let remaining = budget_amount - spent_decimal;
-    let is_over_budget = spent_decimal > budget_amount;
+    let budget_amount: Decimal = row.get(3);
+    let remaining = budget_amount - spent;
+    let is_over_budget = spent > budget_amount;
 
     let budget = BudgetResponse {
         id: budget_id,
+        household_id,
         category_id,
         category_name,
-        amount: amount_str.to_string(),
+        amount: budget_amount,
         period: Some(period),
         start_date,
         spent,
-        remaining: remaining.to_string(),
+        remaining,
         is_over_budget,
         created_at: row.get(6),
         updated_at: row.get(7),
@@ -259,15 +276,23 @@ pub async fn update_budget(
     }))
 }
 
+/// Deletes a budget.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/budgets/{id}",
+    tag = "budgets",
+    params(("id" = i32, Path, description = "Budget id")),
+    responses((status = 200, body = DeleteBudgetResponse)),
+)]
 pub async fn delete_budget(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
+    auth: AuthUser,
     Path(budget_id): Path<i32>,
 ) -> Result<Json<DeleteBudgetResponse>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::BudgetsWrite)?;
 
     let result = sqlx::query(
-        "DELETE FROM budgets WHERE id = $1 AND user_id = $2"
+        "DELETE FROM budgets WHERE id = $1 AND user_can_edit($2, user_id, household_id)"
     )
     .bind(budget_id)
     .bind(auth.user_id)
@@ -276,7 +301,7 @@ pub async fn delete_budget(
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
     if result.rows_affected() == 0 {
-        return Err(AppError::BadRequest("Budget not found or you don't have permission to delete it".to_string()));
+        return Err(AppError::NotFound("Budget not found or you don't have permission to delete it".to_string()));
     }
 
     Ok(Json(DeleteBudgetResponse {
@@ -284,43 +309,50 @@ pub async fn delete_budget(
     }))
 }
 
+/// Spending on the accounts the budget covers: the owner's personal accounts for a
+/// personal budget, or every account in the household for a shared one.
 async fn calculate_spent(
     pool: &PgPool,
     user_id: i32,
+    household_id: Option<i32>,
     category_id: Option<i32>,
     start_date: &NaiveDate,
     period: &str,
-) -> Result<String, AppError> {
+) -> Result<Decimal, AppError> {
     // Build query based on category_id and period
     let query = if let Some(cat_id) = category_id {
         // Category-specific budget: sum expenses for this category
         if period == "monthly" {
             // For monthly budgets, calculate current month's spending (automatically rolls over each month)
             // This ensures the budget resets every month automatically
-            sqlx::query_scalar::<_, Option<String>>(
-                "SELECT COALESCE(SUM(ABS(amount))::text, '0') 
-                 FROM transactions 
-                 WHERE user_id = $1 
-                 AND category_id = $2 
-                 AND type = 'expense' 
-                 AND date >= DATE_TRUNC('month', CURRENT_DATE)
-                 AND date < DATE_TRUNC('month', CURRENT_DATE) + INTERVAL '1 month'"
+            sqlx::query_scalar::<_, Decimal>(
+                "SELECT COALESCE(SUM(ABS(t.amount)), 0.00) 
+                 FROM transactions t
+                 JOIN accounts a ON a.id = t.account_id
+                 WHERE a.household_id IS NOT DISTINCT FROM $2 AND ($2 IS NOT NULL OR a.user_id = $1)
+                 AND t.category_id = $3 
+                 AND t.type = 'expense' 
+                 AND t.date >= DATE_TRUNC('month', CURRENT_DATE)
+                 AND t.date < DATE_TRUNC('month', CURRENT_DATE) + INTERVAL '1 month'"
             )
             .bind(user_id)
+            .bind(household_id)
             .bind(cat_id)
             .fetch_one(pool)
             .await
         } else {
             // For other periods, calculate from start_date onwards
-            sqlx::query_scalar::<_, Option<String>>(
-                "SELECT COALESCE(SUM(ABS(amount))::text, '0') 
-                 FROM transactions 
-                 WHERE user_id = $1 
-                 AND category_id = $2 
-                 AND type = 'expense' 
-                 AND date >= $3"
+            sqlx::query_scalar::<_, Decimal>(
+                "SELECT COALESCE(SUM(ABS(t.amount)), 0.00) 
+                 FROM transactions t
+                 JOIN accounts a ON a.id = t.account_id
+                 WHERE a.household_id IS NOT DISTINCT FROM $2 AND ($2 IS NOT NULL OR a.user_id = $1)
+                 AND t.category_id = $3 
+                 AND t.type = 'expense' 
+                 AND t.date >= $4"
             )
             .bind(user_id)
+            .bind(household_id)
             .bind(cat_id)
             .bind(*start_date)
             .fetch_one(pool)
@@ -331,36 +363,37 @@ async fn calculate_spent(
         if period == "monthly" {
             // For monthly budgets, calculate current month's spending (automatically rolls over each month)
             // This ensures the budget resets every month automatically
-            sqlx::query_scalar::<_, Option<String>>(
-                "SELECT COALESCE(SUM(ABS(amount))::text, '0') 
-                 FROM transactions 
-                 WHERE user_id = $1 
-                 AND type = 'expense' 
-                 AND date >= DATE_TRUNC('month', CURRENT_DATE)
-                 AND date < DATE_TRUNC('month', CURRENT_DATE) + INTERVAL '1 month'"
+            sqlx::query_scalar::<_, Decimal>(
+                "SELECT COALESCE(SUM(ABS(t.amount)), 0.00) 
+                 FROM transactions t
+                 JOIN accounts a ON a.id = t.account_id
+                 WHERE a.household_id IS NOT DISTINCT FROM $2 AND ($2 IS NOT NULL OR a.user_id = $1)
+                 AND t.type = 'expense' 
+                 AND t.date >= DATE_TRUNC('month', CURRENT_DATE)
+                 AND t.date < DATE_TRUNC('month', CURRENT_DATE) + INTERVAL '1 month'"
             )
             .bind(user_id)
+            .bind(household_id)
             .fetch_one(pool)
             .await
         } else {
             // For other periods, calculate from start_date onwards
-            sqlx::query_scalar::<_, Option<String>>(
-                "SELECT COALESCE(SUM(ABS(amount))::text, '0') 
-                 FROM transactions 
-                 WHERE user_id = $1 
-                 AND type = 'expense' 
-                 AND date >= $2"
+            sqlx::query_scalar::<_, Decimal>(
+                "SELECT COALESCE(SUM(ABS(t.amount)), 0.00) 
+                 FROM transactions t
+                 JOIN accounts a ON a.id = t.account_id
+                 WHERE a.household_id IS NOT DISTINCT FROM $2 AND ($2 IS NOT NULL OR a.user_id = $1)
+                 AND t.type = 'expense' 
+                 AND t.date >= $3"
             )
             .bind(user_id)
+            .bind(household_id)
             .bind(*start_date)
             .fetch_one(pool)
             .await
         }
     };
 
-    let result = query
-        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
-
-    Ok(result.unwrap_or_else(|| "0".to_string()))
+    query.map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))
 }
 
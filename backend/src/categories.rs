@@ -1,6 +1,5 @@
 use axum::{
     extract::State,
-    http::HeaderMap,
     Json,
 };
 use sqlx::{PgPool, Row};
@@ -9,42 +8,55 @@ use crate::models::{
     CreateCategoryRequest, CreateCategoryResponse, CategoryResponse,
     DeleteCategoryResponse, Category,
 };
-use crate::auth::AppError;
-use crate::middleware;
-
+use crate::auth::AuthUser;
+use crate::error::AppError;
+use crate::validation::ValidatedJson;
+use crate::api_tokens::Scope;
+use crate::households;
+
+/// Creates a category, optionally under a parent category.
+#[utoipa::path(
+    post,
+    path = "/api/v1/categories",
+    tag = "categories",
+    request_body = CreateCategoryRequest,
+    responses((status = 200, body = CreateCategoryResponse)),
+)]
 pub async fn create_category(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
-    Json(req): Json<CreateCategoryRequest>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<CreateCategoryRequest>,
 ) -> Result<Json<CreateCategoryResponse>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::CategoriesWrite)?;
 
-    if req.name.is_empty() || req.name.len() > 50 {
-        return Err(AppError::BadRequest("Category name must be between 1 and 50 characters".to_string()));
+    if let Some(household_id) = req.household_id {
+        households::require_editor(&pool, household_id, auth.user_id).await?;
     }
 
-    // If parent_id is provided, verify it belongs to the user
+    // If parent_id is provided, verify the user can see it and it lives in the same household
     if let Some(parent_id) = req.parent_id {
         let parent_exists = sqlx::query_scalar::<_, bool>(
-            "SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1 AND user_id = $2)"
+            "SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1 AND household_id IS NOT DISTINCT FROM $3 AND user_can_view($2, user_id, household_id))"
         )
         .bind(parent_id)
         .bind(auth.user_id)
+        .bind(req.household_id)
         .fetch_one(&pool)
         .await
         .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
         if !parent_exists {
-            return Err(AppError::BadRequest("Parent category not found or you don't have permission to use it".to_string()));
+            return Err(AppError::field("parent_id", "Parent category not found or you don't have permission to use it"));
         }
     }
 
     let row = sqlx::query(
-        "INSERT INTO categories (user_id, name, parent_id) VALUES ($1, $2, $3) RETURNING id, user_id, name, parent_id, created_at"
+        "INSERT INTO categories (user_id, name, parent_id, household_id) VALUES ($1, $2, $3, $4) RETURNING id, user_id, name, parent_id, created_at, household_id"
     )
     .bind(auth.user_id)
-    .bind(&req.name)
-    .bind(&req.parent_id)
+    .bind(req.name.as_str())
+    .bind(req.parent_id)
+    .bind(req.household_id)
     .fetch_one(&pool)
     .await
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
@@ -52,6 +64,7 @@ pub async fn create_category(
     let category = Category {
         id: row.get(0),
         user_id: row.get(1),
+        household_id: row.get(5),
         name: row.get(2),
         parent_id: row.get(3),
         created_at: row.get(4),
@@ -63,14 +76,21 @@ pub async fn create_category(
     }))
 }
 
+/// Lists the user's personal categories and those of their households.
+#[utoipa::path(
+    get,
+    path = "/api/v1/categories",
+    tag = "categories",
+    responses((status = 200, body = Vec<CategoryResponse>)),
+)]
 pub async fn get_categories(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
+    auth: AuthUser,
 ) -> Result<Json<Vec<CategoryResponse>>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::Read)?;
 
     let rows = sqlx::query(
-        "SELECT id, user_id, name, parent_id, created_at FROM categories WHERE user_id = $1 ORDER BY created_at DESC"
+        "SELECT id, user_id, name, parent_id, created_at, household_id FROM categories WHERE user_can_view($1, user_id, household_id) ORDER BY created_at DESC"
     )
     .bind(auth.user_id)
     .fetch_all(&pool)
@@ -82,6 +102,7 @@ pub async fn get_categories(
         .map(|row| Category {
             id: row.get(0),
             user_id: row.get(1),
+            household_id: row.get(5),
             name: row.get(2),
             parent_id: row.get(3),
             created_at: row.get(4),
@@ -96,12 +117,20 @@ pub async fn get_categories(
     Ok(Json(categories_response))
 }
 
+/// Deletes a category that has no subcategories.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/categories/{id}",
+    tag = "categories",
+    params(("id" = i32, Path, description = "Category id")),
+    responses((status = 200, body = DeleteCategoryResponse)),
+)]
 pub async fn delete_category(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
+    auth: AuthUser,
     axum::extract::Path(category_id): axum::extract::Path<i32>,
 ) -> Result<Json<DeleteCategoryResponse>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::CategoriesWrite)?;
 
     // Check if category has child categories
     let has_children = sqlx::query_scalar::<_, bool>(
@@ -113,11 +142,11 @@ pub async fn delete_category(
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
     if has_children {
-        return Err(AppError::BadRequest("Cannot delete category with child categories".to_string()));
+        return Err(AppError::Conflict("Cannot delete category with child categories".to_string()));
     }
 
     let result = sqlx::query(
-        "DELETE FROM categories WHERE id = $1 AND user_id = $2"
+        "DELETE FROM categories WHERE id = $1 AND user_can_edit($2, user_id, household_id)"
     )
     .bind(category_id)
     .bind(auth.user_id)
@@ -126,7 +155,7 @@ pub async fn delete_category(
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
     if result.rows_affected() == 0 {
-        return Err(AppError::BadRequest("Category not found or you don't have permission to delete it".to_string()));
+        return Err(AppError::NotFound("Category not found or you don't have permission to delete it".to_string()));
     }
 
     Ok(Json(DeleteCategoryResponse {
@@ -139,6 +168,7 @@ fn category_to_response(category: Category) -> CategoryResponse {
         id: category.id,
         name: category.name,
         parent_id: category.parent_id,
+        household_id: category.household_id,
         created_at: category.created_at,
     }
 }
@@ -0,0 +1,712 @@
+//! Server settings, read once at startup: built-in defaults, then the TOML file named by
+//! `CONFIG_FILE` (default `config.toml`, optional), then environment variables. See
+//! `config.example.toml` for every key and the variable that overrides it.
+
+use std::{
+    env, fmt::Display, fs,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use axum::http::HeaderValue;
+use serde::Deserialize;
+use tracing_subscriber::EnvFilter;
+
+use crate::password_policy::PasswordPolicy;
+
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+/// Token lifetimes are capped well below where `chrono::Duration` and expiry timestamps
+/// would overflow.
+const MAX_ACCESS_TOKEN_TTL_MINUTES: i64 = 366 * 24 * 60;
+const MAX_REFRESH_TOKEN_TTL_DAYS: i64 = 10 * 366;
+const MAX_LOCKOUT_MINUTES: u32 = 366 * 24 * 60;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub tls: TlsConfig,
+    pub database: DatabaseConfig,
+    pub cors: CorsConfig,
+    pub auth: AuthConfig,
+    pub login: LoginConfig,
+    pub password: PasswordConfig,
+    pub log: LogConfig,
+    pub features: FeaturesConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind: SocketAddr,
+    /// Requests still running after this long get `408`; 0 disables the limit.
+    pub request_timeout_secs: u64,
+    /// Reverse proxies whose `X-Forwarded-For` header is believed when rate limiting logins.
+    /// Requests from anywhere else are limited by their own address.
+    pub trusted_proxies: Vec<IpAddr>,
+    pub environment: Environment,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind: ([0, 0, 0, 0], 3000).into(),
+            request_timeout_secs: 30,
+            trusted_proxies: Vec::new(),
+            environment: Environment::Production,
+        }
+    }
+}
+
+/// `development` puts the underlying error message in `500` responses; `production` hides it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    Production,
+    Development,
+}
+
+impl FromStr for Environment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "production" => Ok(Self::Production),
+            "development" => Ok(Self::Development),
+            _ => Err("expected production or development".to_string()),
+        }
+    }
+}
+
+/// HTTPS is served on `server.bind` once both files are set; otherwise plain HTTP.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM certificate chain, leaf first.
+    pub cert_file: Option<PathBuf>,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1).
+    pub key_file: Option<PathBuf>,
+    /// How often the two files are checked for changes, so renewed certificates are picked
+    /// up without a restart; 0 turns reloading off.
+    pub reload_interval_secs: u64,
+    /// Optional plain-HTTP listener, e.g. `0.0.0.0:80`, that redirects every request to HTTPS.
+    pub redirect_http_bind: Option<SocketAddr>,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self { cert_file: None, key_file: None, reload_interval_secs: 60, redirect_http_bind: None }
+    }
+}
+
+impl TlsConfig {
+    /// The certificate and key paths, if HTTPS is on.
+    pub fn files(&self) -> Option<(&Path, &Path)> {
+        Some((self.cert_file.as_deref()?, self.key_file.as_deref()?))
+    }
+
+    pub fn reload_interval(&self) -> Option<Duration> {
+        (self.reload_interval_secs > 0).then(|| Duration::from_secs(self.reload_interval_secs))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// Required; usually given as `DATABASE_URL` so the password stays out of the file.
+    pub url: Option<String>,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    /// How long a request waits for a free connection before failing.
+    pub acquire_timeout_secs: u64,
+    /// Idle connections above `min_connections` are closed after this long.
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self { url: None, max_connections: 5, min_connections: 0, acquire_timeout_secs: 30, idle_timeout_secs: 600 }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CorsConfig {
+    /// Origins such as `https://finance.example.com` that browsers may call the API from.
+    /// `["*"]` allows any origin; an empty list sends no CORS headers at all.
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self { allowed_origins: vec!["*".to_string()] }
+    }
+}
+
+impl CorsConfig {
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|o| o == "*")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    pub access_token_ttl_minutes: i64,
+    pub refresh_token_ttl_days: i64,
+    /// See `SessionCache`; 0 turns the cache off.
+    pub session_cache_ttl_secs: u64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self { access_token_ttl_minutes: 24 * 60, refresh_token_ttl_days: 30, session_cache_ttl_secs: 0 }
+    }
+}
+
+impl AuthConfig {
+    pub fn access_token_ttl(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.access_token_ttl_minutes)
+    }
+
+    pub fn refresh_token_ttl(&self) -> chrono::Duration {
+        chrono::Duration::days(self.refresh_token_ttl_days)
+    }
+}
+
+/// Brute-force protection for `/auth/login` and `/auth/2fa/verify`.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoginConfig {
+    /// Attempts allowed in a burst from one client address.
+    pub ip_burst: u32,
+    /// Sustained attempts per minute from one client address.
+    pub ip_per_minute: u32,
+    /// Consecutive failures (wrong passwords or 2FA codes) before a username is locked.
+    pub lockout_threshold: u32,
+    /// The first lockout lasts one minute and doubles with every further failure, up to this.
+    pub max_lockout_minutes: u32,
+}
+
+impl Default for LoginConfig {
+    fn default() -> Self {
+        Self { ip_burst: 10, ip_per_minute: 10, lockout_threshold: 5, max_lockout_minutes: 60 }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PasswordConfig {
+    /// Length in characters, not bytes.
+    pub min_length: usize,
+    /// Bounds the work a single login or registration can make Argon2 do.
+    pub max_length: usize,
+    /// Minimum zxcvbn score from 0 to 4; 2 means "somewhat guessable", roughly 10^8 guesses.
+    pub min_strength: u8,
+    /// Optional file with one SHA-1 hex digest per line (`HASH` or `HASH:count`, as in
+    /// the Have I Been Pwned downloads).
+    pub breached_passwords_file: Option<PathBuf>,
+}
+
+impl Default for PasswordConfig {
+    fn default() -> Self {
+        Self { min_length: 8, max_length: 128, min_strength: 2, breached_passwords_file: None }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// A level (`info`) or a filter in `RUST_LOG` syntax (`info,sqlx=warn`).
+    pub level: String,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self { level: "info".to_string() }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeaturesConfig {
+    /// When off, `POST /auth/register` is refused and accounts have to be created some other way.
+    pub registration: bool,
+    /// Swagger UI and the OpenAPI document.
+    pub api_docs: bool,
+    /// The hourly cleanup of expired sessions and tokens. With several instances it only
+    /// needs to run on one.
+    pub purge_job: bool,
+}
+
+impl Default for FeaturesConfig {
+    fn default() -> Self {
+        Self { registration: true, api_docs: true, purge_job: true }
+    }
+}
+
+impl Config {
+    /// Reads and validates the configuration. The error lists every problem found, one per line.
+    pub fn load() -> Result<Self, String> {
+        let mut config = match env::var("CONFIG_FILE") {
+            Ok(path) if !path.trim().is_empty() => Self::from_file(Path::new(path.trim()))?,
+            _ if Path::new(DEFAULT_CONFIG_FILE).exists() => Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?,
+            _ => Self::default(),
+        };
+
+        let mut errors = config.apply_env();
+        errors.extend(config.validate());
+        if errors.is_empty() { Ok(config) } else { Err(errors.join("\n")) }
+    }
+
+    fn from_file(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file {}: {}", path.display(), e))?;
+        toml::from_str(&text).map_err(|e| format!("Invalid config file {}: {}", path.display(), e))
+    }
+
+    /// Environment variables win over the file. The older variable names from before the
+    /// config file existed keep working.
+    fn apply_env(&mut self) -> Vec<String> {
+        self.apply_overrides(|name| env::var(name).ok())
+    }
+
+    fn apply_overrides(&mut self, lookup: impl Fn(&str) -> Option<String>) -> Vec<String> {
+        let mut vars = Overrides { lookup, errors: Vec::new() };
+
+        vars.set("BIND_ADDRESS", &mut self.server.bind);
+        vars.set("REQUEST_TIMEOUT_SECS", &mut self.server.request_timeout_secs);
+        vars.set_list("TRUSTED_PROXIES", &mut self.server.trusted_proxies);
+        vars.set("RUST_ENV", &mut self.server.environment);
+        vars.set_opt("TLS_CERT_FILE", &mut self.tls.cert_file);
+        vars.set_opt("TLS_KEY_FILE", &mut self.tls.key_file);
+        vars.set("TLS_RELOAD_INTERVAL_SECS", &mut self.tls.reload_interval_secs);
+        vars.set_opt("TLS_REDIRECT_HTTP_BIND", &mut self.tls.redirect_http_bind);
+        vars.set_opt("DATABASE_URL", &mut self.database.url);
+        vars.set("DATABASE_MAX_CONNECTIONS", &mut self.database.max_connections);
+        vars.set("DATABASE_MIN_CONNECTIONS", &mut self.database.min_connections);
+        vars.set("DATABASE_ACQUIRE_TIMEOUT_SECS", &mut self.database.acquire_timeout_secs);
+        vars.set("DATABASE_IDLE_TIMEOUT_SECS", &mut self.database.idle_timeout_secs);
+        vars.set_list("CORS_ALLOWED_ORIGINS", &mut self.cors.allowed_origins);
+        vars.set("ACCESS_TOKEN_TTL_MINUTES", &mut self.auth.access_token_ttl_minutes);
+        vars.set("REFRESH_TOKEN_TTL_DAYS", &mut self.auth.refresh_token_ttl_days);
+        vars.set("SESSION_CACHE_TTL_SECS", &mut self.auth.session_cache_ttl_secs);
+        vars.set("LOGIN_IP_BURST", &mut self.login.ip_burst);
+        vars.set("LOGIN_IP_PER_MINUTE", &mut self.login.ip_per_minute);
+        vars.set("LOGIN_LOCKOUT_THRESHOLD", &mut self.login.lockout_threshold);
+        vars.set("LOGIN_MAX_LOCKOUT_MINUTES", &mut self.login.max_lockout_minutes);
+        vars.set("PASSWORD_MIN_LENGTH", &mut self.password.min_length);
+        vars.set("PASSWORD_MAX_LENGTH", &mut self.password.max_length);
+        vars.set("PASSWORD_MIN_STRENGTH", &mut self.password.min_strength);
+        vars.set_opt("BREACHED_PASSWORDS_FILE", &mut self.password.breached_passwords_file);
+        vars.set("RUST_LOG", &mut self.log.level);
+        vars.set("ENABLE_REGISTRATION", &mut self.features.registration);
+        vars.set("ENABLE_API_DOCS", &mut self.features.api_docs);
+        vars.set("ENABLE_PURGE_JOB", &mut self.features.purge_job);
+        vars.errors
+    }
+
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        match (&self.tls.cert_file, &self.tls.key_file) {
+            (Some(_), None) => errors.push("tls.key_file (TLS_KEY_FILE) is required when tls.cert_file is set".to_string()),
+            (None, Some(_)) => errors.push("tls.cert_file (TLS_CERT_FILE) is required when tls.key_file is set".to_string()),
+            _ => {}
+        }
+        for (key, path) in [("tls.cert_file (TLS_CERT_FILE)", &self.tls.cert_file), ("tls.key_file (TLS_KEY_FILE)", &self.tls.key_file)] {
+            if let Some(path) = path
+                && !path.is_file()
+            {
+                errors.push(format!("{}: {} does not exist", key, path.display()));
+            }
+        }
+        match self.tls.redirect_http_bind {
+            Some(_) if self.tls.files().is_none() => {
+                errors.push("tls.redirect_http_bind (TLS_REDIRECT_HTTP_BIND) needs tls.cert_file and tls.key_file".to_string());
+            }
+            Some(addr) if addr.port() == self.server.bind.port() => {
+                errors.push(format!("tls.redirect_http_bind (TLS_REDIRECT_HTTP_BIND) must use a different port than server.bind ({})", self.server.bind));
+            }
+            _ => {}
+        }
+
+        match self.database.url.as_deref() {
+            None => errors.push("database.url is not set; set DATABASE_URL or [database] url".to_string()),
+            Some(url) if !(url.starts_with("postgres://") || url.starts_with("postgresql://")) => {
+                errors.push("database.url (DATABASE_URL) must start with postgres:// or postgresql://".to_string());
+            }
+            Some(_) => {}
+        }
+        if self.database.max_connections == 0 {
+            errors.push("database.max_connections (DATABASE_MAX_CONNECTIONS) must be at least 1".to_string());
+        }
+        if self.database.min_connections > self.database.max_connections {
+            errors.push(format!(
+                "database.min_connections (DATABASE_MIN_CONNECTIONS) must not exceed database.max_connections ({})",
+                self.database.max_connections
+            ));
+        }
+        if self.database.acquire_timeout_secs == 0 {
+            errors.push("database.acquire_timeout_secs (DATABASE_ACQUIRE_TIMEOUT_SECS) must be at least 1".to_string());
+        }
+
+        if self.cors.allows_any_origin() && self.cors.allowed_origins.len() > 1 {
+            errors.push("cors.allowed_origins (CORS_ALLOWED_ORIGINS) can't combine \"*\" with specific origins".to_string());
+        }
+        for origin in self.cors.allowed_origins.iter().filter(|o| *o != "*") {
+            let scheme_ok = origin.starts_with("http://") || origin.starts_with("https://");
+            let host = origin.split_once("://").map(|(_, host)| host).unwrap_or_default();
+            if !scheme_ok || host.is_empty() || host.contains('/') || HeaderValue::from_str(origin).is_err() {
+                errors.push(format!(
+                    "cors.allowed_origins (CORS_ALLOWED_ORIGINS): '{}' is not an origin like https://example.com (no path or trailing slash)",
+                    origin
+                ));
+            }
+        }
+
+        if !(1..=MAX_ACCESS_TOKEN_TTL_MINUTES).contains(&self.auth.access_token_ttl_minutes) {
+            errors.push(format!(
+                "auth.access_token_ttl_minutes (ACCESS_TOKEN_TTL_MINUTES) must be between 1 and {} (one year)",
+                MAX_ACCESS_TOKEN_TTL_MINUTES
+            ));
+        }
+        if !(1..=MAX_REFRESH_TOKEN_TTL_DAYS).contains(&self.auth.refresh_token_ttl_days) {
+            errors.push(format!(
+                "auth.refresh_token_ttl_days (REFRESH_TOKEN_TTL_DAYS) must be between 1 and {} (ten years)",
+                MAX_REFRESH_TOKEN_TTL_DAYS
+            ));
+        }
+
+        for (key, value) in [
+            ("login.ip_burst (LOGIN_IP_BURST)", self.login.ip_burst),
+            ("login.ip_per_minute (LOGIN_IP_PER_MINUTE)", self.login.ip_per_minute),
+            ("login.lockout_threshold (LOGIN_LOCKOUT_THRESHOLD)", self.login.lockout_threshold),
+        ] {
+            if value == 0 {
+                errors.push(format!("{} must be at least 1", key));
+            }
+        }
+        if !(1..=MAX_LOCKOUT_MINUTES).contains(&self.login.max_lockout_minutes) {
+            errors.push(format!(
+                "login.max_lockout_minutes (LOGIN_MAX_LOCKOUT_MINUTES) must be between 1 and {} (one year)",
+                MAX_LOCKOUT_MINUTES
+            ));
+        }
+
+        if self.password.min_length == 0 || self.password.min_length > self.password.max_length {
+            errors.push(format!(
+                "password.min_length (PASSWORD_MIN_LENGTH) must be between 1 and password.max_length ({})",
+                self.password.max_length
+            ));
+        }
+        if self.password.min_strength > 4 {
+            errors.push("password.min_strength (PASSWORD_MIN_STRENGTH) must be between 0 and 4".to_string());
+        }
+        // Reads and parses the breached-password file, so a missing or malformed one is
+        // reported here rather than when the server builds its policy
+        if self.password.breached_passwords_file.is_some()
+            && let Err(e) = PasswordPolicy::new(&self.password)
+        {
+            errors.push(format!("password.breached_passwords_file (BREACHED_PASSWORDS_FILE): {}", e));
+        }
+
+        if let Err(e) = EnvFilter::try_new(&self.log.level) {
+            errors.push(format!("log.level (RUST_LOG): invalid filter '{}': {}", self.log.level, e));
+        }
+
+        errors
+    }
+
+    pub fn request_timeout(&self) -> Option<Duration> {
+        (self.server.request_timeout_secs > 0).then(|| Duration::from_secs(self.server.request_timeout_secs))
+    }
+}
+
+/// Environment overrides, read through `lookup` so tests don't have to touch the real
+/// environment. Blank variables count as unset; unparsable ones are collected in `errors`.
+struct Overrides<F> {
+    lookup: F,
+    errors: Vec<String>,
+}
+
+impl<F: Fn(&str) -> Option<String>> Overrides<F> {
+    fn value(&self, name: &str) -> Option<String> {
+        (self.lookup)(name).map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+    }
+
+    fn parse<T: FromStr>(&mut self, name: &str, value: &str) -> Option<T>
+    where
+        T::Err: Display,
+    {
+        value.parse()
+            .map_err(|e| self.errors.push(format!("{}: invalid value '{}': {}", name, value, e)))
+            .ok()
+    }
+
+    fn set<T: FromStr>(&mut self, name: &str, target: &mut T)
+    where
+        T::Err: Display,
+    {
+        if let Some(value) = self.value(name)
+            && let Some(parsed) = self.parse(name, &value)
+        {
+            *target = parsed;
+        }
+    }
+
+    fn set_opt<T: FromStr>(&mut self, name: &str, target: &mut Option<T>)
+    where
+        T::Err: Display,
+    {
+        if let Some(value) = self.value(name)
+            && let Some(parsed) = self.parse(name, &value)
+        {
+            *target = Some(parsed);
+        }
+    }
+
+    /// A comma-separated list; empty items are skipped.
+    fn set_list<T: FromStr>(&mut self, name: &str, target: &mut Vec<T>)
+    where
+        T::Err: Display,
+    {
+        let Some(value) = self.value(name) else { return };
+        let items = value.split(',').map(str::trim).filter(|item| !item.is_empty())
+            .map(|item| self.parse(name, item))
+            .collect::<Option<Vec<_>>>();
+        if let Some(items) = items {
+            *target = items;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// The defaults plus the one required setting.
+    fn valid() -> Config {
+        let mut config = Config::default();
+        config.database.url = Some("postgres://localhost/finance".to_string());
+        config
+    }
+
+    fn from_toml(text: &str) -> Config {
+        toml::from_str(text).unwrap()
+    }
+
+    fn with_env(mut config: Config, vars: &[(&str, &str)]) -> (Config, Vec<String>) {
+        let vars = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>();
+        let errors = config.apply_overrides(|name| vars.get(name).cloned());
+        (config, errors)
+    }
+
+    fn assert_rejected(config: &Config, key: &str) {
+        let errors = config.validate();
+        assert!(errors.iter().any(|e| e.starts_with(key)), "expected an error for {}, got {:?}", key, errors);
+    }
+
+    #[test]
+    fn defaults_need_only_a_database_url() {
+        assert!(valid().validate().is_empty());
+        assert_rejected(&Config::default(), "database.url");
+    }
+
+    #[test]
+    fn every_problem_is_reported_at_once() {
+        let mut config = Config::default();
+        config.database.max_connections = 0;
+        config.password.min_strength = 5;
+        config.log.level = "[".to_string();
+        assert_eq!(config.validate().len(), 4);
+    }
+
+    #[test]
+    fn pool_limits_must_be_consistent() {
+        let mut config = valid();
+        config.database.min_connections = 6;
+        config.database.max_connections = 5;
+        assert_rejected(&config, "database.min_connections");
+
+        config.database.url = Some("mysql://localhost/finance".to_string());
+        assert_rejected(&config, "database.url");
+    }
+
+    #[test]
+    fn cors_origins_are_checked() {
+        let mut config = valid();
+        config.cors.allowed_origins = vec!["*".to_string(), "https://finance.example.com".to_string()];
+        assert_rejected(&config, "cors.allowed_origins");
+
+        for origin in ["https://finance.example.com/", "https://finance.example.com/app", "finance.example.com", "https://"] {
+            config.cors.allowed_origins = vec![origin.to_string()];
+            assert_rejected(&config, "cors.allowed_origins");
+        }
+
+        config.cors.allowed_origins = vec!["https://finance.example.com".to_string(), "http://localhost:5173".to_string()];
+        assert!(config.validate().is_empty());
+        config.cors.allowed_origins = Vec::new();
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn token_lifetimes_are_bounded() {
+        let mut config = valid();
+        config.auth.access_token_ttl_minutes = 0;
+        assert_rejected(&config, "auth.access_token_ttl_minutes");
+        config.auth.access_token_ttl_minutes = i64::MAX;
+        assert_rejected(&config, "auth.access_token_ttl_minutes");
+        config.auth.access_token_ttl_minutes = MAX_ACCESS_TOKEN_TTL_MINUTES;
+        config.auth.refresh_token_ttl_days = i64::MAX / 2;
+        assert_rejected(&config, "auth.refresh_token_ttl_days");
+        config.auth.refresh_token_ttl_days = MAX_REFRESH_TOKEN_TTL_DAYS;
+        assert!(config.validate().is_empty());
+
+        // The largest accepted values still produce usable expiry times
+        let _ = chrono::Utc::now() + config.auth.access_token_ttl() + config.auth.refresh_token_ttl();
+    }
+
+    #[test]
+    fn login_limits_are_checked() {
+        let mut config = valid();
+        config.login.ip_burst = 0;
+        config.login.lockout_threshold = 0;
+        assert_rejected(&config, "login.ip_burst");
+        assert_rejected(&config, "login.lockout_threshold");
+
+        let mut config = valid();
+        config.login.max_lockout_minutes = MAX_LOCKOUT_MINUTES + 1;
+        assert_rejected(&config, "login.max_lockout_minutes");
+        config.login.max_lockout_minutes = MAX_LOCKOUT_MINUTES;
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn breached_password_file_is_parsed_with_the_rest() {
+        let mut config = valid();
+        config.password.breached_passwords_file = Some(PathBuf::from("/nonexistent/pwned.txt"));
+        config.database.max_connections = 0;
+        assert_eq!(config.validate().len(), 2);
+        assert_rejected(&config, "password.breached_passwords_file");
+
+        let path = env::temp_dir().join(format!("backend-config-{}.txt", uuid::Uuid::new_v4()));
+        fs::write(&path, "not a hash\n").unwrap();
+        config.password.breached_passwords_file = Some(path.clone());
+        let errors = config.validate();
+        fs::remove_file(path).unwrap();
+        assert!(errors.iter().any(|e| e.contains(":1: expected a 40-character SHA-1 hex digest")), "{:?}", errors);
+    }
+
+    #[test]
+    fn tls_settings_are_checked() {
+        let mut config = valid();
+        config.tls.cert_file = Some(PathBuf::from("/nonexistent/cert.pem"));
+        assert_rejected(&config, "tls.key_file");
+        assert_rejected(&config, "tls.cert_file");
+
+        let mut config = valid();
+        config.tls.redirect_http_bind = Some(([0, 0, 0, 0], 80).into());
+        assert_rejected(&config, "tls.redirect_http_bind");
+
+        // Both files exist here, so only the port clash is left
+        let file = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"));
+        config.tls.cert_file = Some(file.clone());
+        config.tls.key_file = Some(file);
+        assert!(config.validate().is_empty());
+        config.tls.redirect_http_bind = Some(([127, 0, 0, 1], config.server.bind.port()).into());
+        assert_eq!(config.validate().len(), 1);
+        assert_rejected(&config, "tls.redirect_http_bind");
+    }
+
+    #[test]
+    fn file_settings_fill_in_over_defaults() {
+        let config = from_toml(
+            r#"
+            [server]
+            bind = "127.0.0.1:8080"
+            trusted_proxies = ["10.0.0.1"]
+
+            [database]
+            url = "postgres://db/finance"
+            max_connections = 3
+            "#,
+        );
+        assert_eq!(config.server.bind, "127.0.0.1:8080".parse().unwrap());
+        assert_eq!(config.server.trusted_proxies, ["10.0.0.1".parse::<IpAddr>().unwrap()]);
+        assert_eq!(config.database.max_connections, 3);
+        assert_eq!(config.database.min_connections, 0);
+        assert_eq!(config.server.request_timeout_secs, 30);
+        assert!(config.features.registration);
+    }
+
+    #[test]
+    fn unknown_file_keys_are_rejected() {
+        let error = toml::from_str::<Config>("[database]\nmax_conections = 3\n").unwrap_err().to_string();
+        assert!(error.contains("max_conections"), "{}", error);
+        assert!(toml::from_str::<Config>("[databse]\n").is_err());
+    }
+
+    #[test]
+    fn environment_overrides_the_file() {
+        let file = from_toml(
+            r#"
+            [server]
+            bind = "127.0.0.1:8080"
+            [database]
+            url = "postgres://file/finance"
+            max_connections = 3
+            [cors]
+            allowed_origins = ["https://file.example.com"]
+            [features]
+            registration = true
+            "#,
+        );
+        let (config, errors) = with_env(file, &[
+            ("BIND_ADDRESS", "0.0.0.0:9000"),
+            ("DATABASE_URL", "postgres://env/finance"),
+            ("CORS_ALLOWED_ORIGINS", "https://a.example.com, ,https://b.example.com"),
+            ("TRUSTED_PROXIES", "10.0.0.1,::1"),
+            ("ENABLE_REGISTRATION", "false"),
+            ("RUST_ENV", "development"),
+            ("LOGIN_LOCKOUT_THRESHOLD", "3"),
+            // Blank variables leave the file's value alone
+            ("DATABASE_MAX_CONNECTIONS", "  "),
+        ]);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(config.server.bind, "0.0.0.0:9000".parse().unwrap());
+        assert_eq!(config.database.url.as_deref(), Some("postgres://env/finance"));
+        assert_eq!(config.cors.allowed_origins, ["https://a.example.com", "https://b.example.com"]);
+        assert_eq!(config.server.trusted_proxies.len(), 2);
+        assert!(!config.features.registration);
+        assert_eq!(config.server.environment, Environment::Development);
+        assert_eq!(config.login.lockout_threshold, 3);
+        assert_eq!(config.database.max_connections, 3);
+    }
+
+    #[test]
+    fn unparsable_variables_are_reported_by_name() {
+        let (config, errors) = with_env(valid(), &[
+            ("DATABASE_MAX_CONNECTIONS", "many"),
+            ("TRUSTED_PROXIES", "10.0.0.1,proxy.local"),
+            ("ENABLE_API_DOCS", "yes"),
+            ("RUST_ENV", "staging"),
+        ]);
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        for expected in [
+            "DATABASE_MAX_CONNECTIONS: invalid value 'many'",
+            "TRUSTED_PROXIES: invalid value 'proxy.local'",
+            "ENABLE_API_DOCS: ",
+            "RUST_ENV: invalid value 'staging': expected production or development",
+        ] {
+            assert!(errors.iter().any(|e| e.starts_with(expected)), "{} not in {:?}", expected, errors);
+        }
+        // Settings that failed to parse keep their previous value
+        assert_eq!(config.database.max_connections, 5);
+        assert!(config.server.trusted_proxies.is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+pub use finance_api_types::{ErrorResponse, FieldError};
+
+static EXPOSE_INTERNAL_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Whether `500` responses carry the underlying error message. Set once at startup from
+/// `server.environment`; responses are built far from any state, hence the global.
+pub fn expose_internal_errors(expose: bool) {
+    EXPOSE_INTERNAL_ERRORS.store(expose, Ordering::Relaxed);
+}
+
+#[derive(Debug)]
+pub enum AppError {
+    /// The request makes no sense as a whole (e.g. conflicting options).
+    BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
+    /// The resource in the path doesn't exist or isn't visible to the caller; the two
+    /// are deliberately indistinguishable.
+    NotFound(String),
+    /// The request clashes with existing state (duplicate name, last owner, ...).
+    Conflict(String),
+    /// One or more request fields are invalid.
+    Validation(Vec<FieldError>),
+    RateLimited { message: String, retry_after_secs: u64 },
+    InternalServerError(String),
+}
+
+impl AppError {
+    /// A validation error for a single field.
+    pub fn field(field: &str, message: impl Into<String>) -> Self {
+        AppError::Validation(vec![FieldError { field: field.to_string(), message: message.into() }])
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::BadRequest(_) => "bad_request",
+            AppError::Unauthorized(_) => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::NotFound(_) => "not_found",
+            AppError::Conflict(_) => "conflict",
+            AppError::Validation(_) => "validation_failed",
+            AppError::RateLimited { .. } => "rate_limited",
+            AppError::InternalServerError(_) => "internal_error",
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let code = self.code().to_string();
+        let (status, error_message, fields) = match self {
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg, Vec::new()),
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg, Vec::new()),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg, Vec::new()),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg, Vec::new()),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg, Vec::new()),
+            AppError::Validation(fields) => {
+                // Older clients only read `error`, so it carries the field messages too.
+                let msg = fields.iter().map(|f| format!("{}: {}", f.field, f.message)).collect::<Vec<_>>().join("; ");
+                (StatusCode::UNPROCESSABLE_ENTITY, msg, fields)
+            }
+            AppError::RateLimited { message, retry_after_secs } => {
+                return (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(header::RETRY_AFTER, retry_after_secs.to_string())],
+                    Json(ErrorResponse { error: message, code, fields: Vec::new() }),
+                )
+                    .into_response();
+            }
+            AppError::InternalServerError(msg) => {
+                tracing::error!("Internal server error: {}", msg);
+                let response_msg = if EXPOSE_INTERNAL_ERRORS.load(Ordering::Relaxed) {
+                    msg
+                } else {
+                    "Internal server error".to_string()
+                };
+                (StatusCode::INTERNAL_SERVER_ERROR, response_msg, Vec::new())
+            }
+        };
+
+        (status, Json(ErrorResponse { error: error_message, code, fields })).into_response()
+    }
+}
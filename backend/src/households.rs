@@ -0,0 +1,478 @@
+use axum::{
+    extract::{State, Path},
+    Json,
+};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+
+use crate::models::{
+    CreateHouseholdRequest, CreateHouseholdResponse, HouseholdResponse, DeleteHouseholdResponse,
+    HouseholdMemberResponse, AddHouseholdMemberRequest, UpdateHouseholdMemberRequest,
+    HouseholdMemberChangeResponse,
+};
+use crate::auth::AuthUser;
+use crate::error::AppError;
+use crate::validation::ValidatedJson;
+use crate::api_tokens::Scope;
+
+const ROLES: [&str; 3] = ["owner", "editor", "viewer"];
+
+fn validate_role(role: &str) -> Result<String, AppError> {
+    let role = role.trim().to_lowercase();
+    if !ROLES.contains(&role.as_str()) {
+        return Err(AppError::field("role", "Role must be one of: owner, editor, viewer"));
+    }
+    Ok(role)
+}
+
+async fn member_role(pool: &PgPool, household_id: i32, user_id: i32) -> Result<Option<String>, AppError> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT role FROM household_members WHERE household_id = $1 AND user_id = $2"
+    )
+    .bind(household_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))
+}
+
+/// Checks that the user may add data to the household (owners and editors).
+pub async fn require_editor(pool: &PgPool, household_id: i32, user_id: i32) -> Result<(), AppError> {
+    match member_role(pool, household_id, user_id).await?.as_deref() {
+        Some("owner") | Some("editor") => Ok(()),
+        Some(_) => Err(AppError::Forbidden("Viewers can't change this household's data".to_string())),
+        None => Err(AppError::NotFound("Household not found or you don't have permission to use it".to_string())),
+    }
+}
+
+async fn require_owner(pool: &PgPool, household_id: i32, user_id: i32) -> Result<(), AppError> {
+    match member_role(pool, household_id, user_id).await?.as_deref() {
+        Some("owner") => Ok(()),
+        Some(_) => Err(AppError::Forbidden("Only household owners can do this".to_string())),
+        None => Err(AppError::NotFound("Household not found or you don't have permission to manage it".to_string())),
+    }
+}
+
+/// Creates a household with the caller as its owner.
+#[utoipa::path(
+    post,
+    path = "/api/v1/households",
+    tag = "households",
+    request_body = CreateHouseholdRequest,
+    responses((status = 200, body = CreateHouseholdResponse)),
+)]
+pub async fn create_household(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<CreateHouseholdRequest>,
+) -> Result<Json<CreateHouseholdResponse>, AppError> {
+    auth.require_session()?;
+
+    let name = req.name.as_str();
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let row = sqlx::query("INSERT INTO households (name) VALUES ($1) RETURNING id, name, created_at")
+        .bind(name)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    let household_id: i32 = row.get(0);
+
+    sqlx::query("INSERT INTO household_members (household_id, user_id, role) VALUES ($1, $2, 'owner')")
+        .bind(household_id)
+        .bind(auth.user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(Json(CreateHouseholdResponse {
+        message: "Household created successfully".to_string(),
+        household: HouseholdResponse {
+            id: household_id,
+            name: row.get(1),
+            role: "owner".to_string(),
+            created_at: row.get(2),
+        },
+    }))
+}
+
+/// Lists the households the user belongs to, with their role in each.
+#[utoipa::path(
+    get,
+    path = "/api/v1/households",
+    tag = "households",
+    responses((status = 200, body = Vec<HouseholdResponse>)),
+)]
+pub async fn get_households(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+) -> Result<Json<Vec<HouseholdResponse>>, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let rows = sqlx::query(
+        "SELECT h.id, h.name, m.role, h.created_at
+         FROM households h
+         JOIN household_members m ON m.household_id = h.id
+         WHERE m.user_id = $1
+         ORDER BY h.created_at"
+    )
+    .bind(auth.user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let households = rows
+        .into_iter()
+        .map(|row| HouseholdResponse {
+            id: row.get(0),
+            name: row.get(1),
+            role: row.get(2),
+            created_at: row.get(3),
+        })
+        .collect();
+
+    Ok(Json(households))
+}
+
+/// Deletes the household together with its shared accounts, categories and budgets.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/households/{id}",
+    tag = "households",
+    params(("id" = i32, Path, description = "Household id")),
+    responses((status = 200, body = DeleteHouseholdResponse)),
+)]
+pub async fn delete_household(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Path(household_id): Path<i32>,
+) -> Result<Json<DeleteHouseholdResponse>, AppError> {
+    auth.require_session()?;
+    require_owner(&pool, household_id, auth.user_id).await?;
+
+    sqlx::query("DELETE FROM households WHERE id = $1")
+        .bind(household_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(Json(DeleteHouseholdResponse {
+        message: "Household and its shared data deleted".to_string(),
+    }))
+}
+
+/// Lists a household's members; any member may look.
+#[utoipa::path(
+    get,
+    path = "/api/v1/households/{id}/members",
+    tag = "households",
+    params(("id" = i32, Path, description = "Household id")),
+    responses((status = 200, body = Vec<HouseholdMemberResponse>)),
+)]
+pub async fn get_members(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Path(household_id): Path<i32>,
+) -> Result<Json<Vec<HouseholdMemberResponse>>, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    if member_role(&pool, household_id, auth.user_id).await?.is_none() {
+        return Err(AppError::NotFound("Household not found or you don't have permission to view it".to_string()));
+    }
+
+    let rows = sqlx::query(
+        "SELECT m.user_id, u.username, m.role, m.created_at
+         FROM household_members m
+         JOIN users u ON u.id = m.user_id
+         WHERE m.household_id = $1
+         ORDER BY m.created_at"
+    )
+    .bind(household_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let members = rows
+        .into_iter()
+        .map(|row| HouseholdMemberResponse {
+            user_id: row.get(0),
+            username: row.get(1),
+            role: row.get(2),
+            joined_at: row.get(3),
+        })
+        .collect();
+
+    Ok(Json(members))
+}
+
+/// Adds a user to the household by username. Owners only.
+#[utoipa::path(
+    post,
+    path = "/api/v1/households/{id}/members",
+    tag = "households",
+    params(("id" = i32, Path, description = "Household id")),
+    request_body = AddHouseholdMemberRequest,
+    responses((status = 200, body = HouseholdMemberChangeResponse)),
+)]
+pub async fn add_member(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Path(household_id): Path<i32>,
+    ValidatedJson(req): ValidatedJson<AddHouseholdMemberRequest>,
+) -> Result<Json<HouseholdMemberChangeResponse>, AppError> {
+    auth.require_session()?;
+    require_owner(&pool, household_id, auth.user_id).await?;
+
+    let role = validate_role(&req.role)?;
+
+    let user_id = sqlx::query_scalar::<_, i32>("SELECT id FROM users WHERE username = $1")
+        .bind(req.username.trim())
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| AppError::field("username", "User not found"))?;
+
+    let result = sqlx::query(
+        "INSERT INTO household_members (household_id, user_id, role) VALUES ($1, $2, $3)
+         ON CONFLICT (household_id, user_id) DO NOTHING"
+    )
+    .bind(household_id)
+    .bind(user_id)
+    .bind(&role)
+    .execute(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::Conflict("User is already a member of this household".to_string()));
+    }
+
+    Ok(Json(HouseholdMemberChangeResponse {
+        message: format!("{} added as {}", req.username.trim(), role),
+    }))
+}
+
+/// Changes a member's role. Owners only; a household always keeps one owner.
+#[utoipa::path(
+    put,
+    path = "/api/v1/households/{id}/members/{user_id}",
+    tag = "households",
+    params(
+        ("id" = i32, Path, description = "Household id"),
+        ("user_id" = i32, Path, description = "Member's user id"),
+    ),
+    request_body = UpdateHouseholdMemberRequest,
+    responses((status = 200, body = HouseholdMemberChangeResponse)),
+)]
+pub async fn update_member(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Path((household_id, member_id)): Path<(i32, i32)>,
+    ValidatedJson(req): ValidatedJson<UpdateHouseholdMemberRequest>,
+) -> Result<Json<HouseholdMemberChangeResponse>, AppError> {
+    auth.require_session()?;
+    require_owner(&pool, household_id, auth.user_id).await?;
+
+    let role = validate_role(&req.role)?;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let current_role = sqlx::query_scalar::<_, String>(
+        "SELECT role FROM household_members WHERE household_id = $1 AND user_id = $2 FOR UPDATE"
+    )
+    .bind(household_id)
+    .bind(member_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?
+    .ok_or_else(|| AppError::NotFound("Member not found in this household".to_string()))?;
+
+    if current_role == "owner" && role != "owner" && owner_count(&mut tx, household_id).await? <= 1 {
+        return Err(AppError::Conflict("A household needs at least one owner".to_string()));
+    }
+
+    sqlx::query("UPDATE household_members SET role = $1 WHERE household_id = $2 AND user_id = $3")
+        .bind(&role)
+        .bind(household_id)
+        .bind(member_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(Json(HouseholdMemberChangeResponse {
+        message: format!("Role changed to {}", role),
+    }))
+}
+
+/// Removes a member. Owners can remove anyone; everyone else can only remove themselves.
+/// Shared data the member created stays in the household.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/households/{id}/members/{user_id}",
+    tag = "households",
+    params(
+        ("id" = i32, Path, description = "Household id"),
+        ("user_id" = i32, Path, description = "Member's user id"),
+    ),
+    responses((status = 200, body = HouseholdMemberChangeResponse)),
+)]
+pub async fn remove_member(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Path((household_id, member_id)): Path<(i32, i32)>,
+) -> Result<Json<HouseholdMemberChangeResponse>, AppError> {
+    auth.require_session()?;
+    if member_id != auth.user_id {
+        require_owner(&pool, household_id, auth.user_id).await?;
+    }
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let role = sqlx::query_scalar::<_, String>(
+        "SELECT role FROM household_members WHERE household_id = $1 AND user_id = $2 FOR UPDATE"
+    )
+    .bind(household_id)
+    .bind(member_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?
+    .ok_or_else(|| AppError::NotFound("Member not found in this household".to_string()))?;
+
+    if role == "owner" && owner_count(&mut tx, household_id).await? <= 1 {
+        return Err(AppError::Conflict(
+            "The last owner can't leave; make someone else owner or delete the household".to_string()
+        ));
+    }
+
+    hand_over_rows(&mut tx, household_id, member_id).await?;
+
+    sqlx::query("DELETE FROM household_members WHERE household_id = $1 AND user_id = $2")
+        .bind(household_id)
+        .bind(member_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(Json(HouseholdMemberChangeResponse {
+        message: "Member removed from household".to_string(),
+    }))
+}
+
+/// Takes the user out of every household before their account is deleted. Households they
+/// were alone in are deleted; otherwise the longest-standing member inherits ownership if
+/// needed, and the shared rows they created are handed over so the cascade on `users`
+/// doesn't take them along.
+pub async fn leave_all_households(tx: &mut Transaction<'_, Postgres>, user_id: i32) -> Result<(), AppError> {
+    let memberships = sqlx::query_as::<_, (i32, String)>(
+        "SELECT household_id, role FROM household_members WHERE user_id = $1 FOR UPDATE"
+    )
+    .bind(user_id)
+    .fetch_all(&mut **tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    for (household_id, role) in memberships {
+        let others = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM household_members WHERE household_id = $1 AND user_id <> $2"
+        )
+        .bind(household_id)
+        .bind(user_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+        if others == 0 {
+            sqlx::query("DELETE FROM households WHERE id = $1")
+                .bind(household_id)
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+            continue;
+        }
+
+        if role == "owner" && owner_count(tx, household_id).await? <= 1 {
+            sqlx::query(
+                "UPDATE household_members SET role = 'owner'
+                 WHERE household_id = $1 AND user_id = (
+                     SELECT user_id FROM household_members
+                     WHERE household_id = $1 AND user_id <> $2
+                     ORDER BY created_at, user_id LIMIT 1
+                 )"
+            )
+            .bind(household_id)
+            .bind(user_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+        }
+
+        hand_over_rows(tx, household_id, user_id).await?;
+    }
+
+    Ok(())
+}
+
+async fn owner_count(tx: &mut Transaction<'_, Postgres>, household_id: i32) -> Result<i64, AppError> {
+    sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM household_members WHERE household_id = $1 AND role = 'owner'"
+    )
+    .bind(household_id)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))
+}
+
+/// Re-attributes the household rows `user_id` created to one of the remaining owners.
+async fn hand_over_rows(tx: &mut Transaction<'_, Postgres>, household_id: i32, user_id: i32) -> Result<(), AppError> {
+    let new_owner = sqlx::query_scalar::<_, i32>(
+        "SELECT user_id FROM household_members
+         WHERE household_id = $1 AND user_id <> $2 AND role = 'owner'
+         ORDER BY created_at, user_id LIMIT 1"
+    )
+    .bind(household_id)
+    .bind(user_id)
+    .fetch_optional(&mut **tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let Some(new_owner) = new_owner else { return Ok(()) };
+
+    for table in ["accounts", "categories", "budgets"] {
+        sqlx::query(&format!(
+            "UPDATE {} SET user_id = $1 WHERE household_id = $2 AND user_id = $3",
+            table
+        ))
+        .bind(new_owner)
+        .bind(household_id)
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    }
+
+    sqlx::query(
+        "UPDATE transactions t SET user_id = $1
+         FROM accounts a
+         WHERE a.id = t.account_id AND a.household_id = $2 AND t.user_id = $3"
+    )
+    .bind(new_owner)
+    .bind(household_id)
+    .bind(user_id)
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(())
+}
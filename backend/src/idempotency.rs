@@ -0,0 +1,187 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use crate::auth::AuthUser;
+use crate::error::AppError;
+
+pub const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+/// Set on responses that were replayed rather than produced by the handler.
+const REPLAYED: &str = "idempotent-replayed";
+const MAX_KEY_LENGTH: usize = 255;
+/// Same as axum's default limit for extracted bodies.
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Layer for write routes that clients may retry. A request carrying an `Idempotency-Key`
+/// header is handled once; repeating it with the same key and body within 24 hours
+/// returns the stored response instead of running the handler again. Requests without
+/// the header pass straight through.
+///
+/// Must run inside `require_auth`, since keys are scoped to the caller.
+pub async fn idempotent(
+    State(pool): State<PgPool>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY) else {
+        return Ok(next.run(request).await);
+    };
+    let key = key.to_str().ok()
+        .map(str::trim)
+        .filter(|k| !k.is_empty() && k.len() <= MAX_KEY_LENGTH)
+        .ok_or_else(|| AppError::BadRequest(format!(
+            "{} must be 1 to {} visible ASCII characters", IDEMPOTENCY_KEY, MAX_KEY_LENGTH
+        )))?
+        .to_string();
+    let user_id = request.extensions().get::<AuthUser>()
+        .map(|user| user.user_id)
+        .ok_or_else(|| AppError::InternalServerError("Idempotency layer used without require_auth".to_string()))?;
+
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_BODY_BYTES).await
+        .map_err(|e| AppError::BadRequest(format!("Could not read request body: {}", e)))?;
+    let path = parts.uri.path_and_query().map_or(parts.uri.path(), |p| p.as_str());
+    let request_hash = hash_request(&parts.method, path, &body);
+
+    // Claim the key. An old claim is taken over once it has expired, or when its request
+    // never finished because the server stopped mid-way (which rolled its changes back)
+    let claimed = sqlx::query(
+        "INSERT INTO idempotency_keys (user_id, idempotency_key, request_hash) VALUES ($1, $2, $3)
+         ON CONFLICT (user_id, idempotency_key) DO UPDATE
+         SET request_hash = EXCLUDED.request_hash, response_status = NULL, response_body = NULL, created_at = NOW()
+         WHERE idempotency_keys.created_at < NOW() - INTERVAL '24 hours'
+            OR (idempotency_keys.response_status IS NULL AND idempotency_keys.created_at < NOW() - INTERVAL '5 minutes')"
+    )
+    .bind(user_id)
+    .bind(&key)
+    .bind(&request_hash)
+    .execute(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?
+    .rows_affected() == 1;
+
+    if !claimed {
+        return replay(&pool, user_id, &key, &request_hash).await;
+    }
+
+    // Spawned so that the outcome is recorded even if the client hangs up, which is
+    // exactly when it is going to retry
+    let request = Request::from_parts(parts, Body::from(body));
+    tokio::spawn(async move {
+        let response = next.run(request).await;
+        store_response(&pool, user_id, &key, response).await
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Request task failed: {}", e)))
+}
+
+async fn replay(pool: &PgPool, user_id: i32, key: &str, request_hash: &str) -> Result<Response, AppError> {
+    let stored = sqlx::query_as::<_, (String, Option<i16>, Option<Vec<u8>>)>(
+        "SELECT request_hash, response_status, response_body FROM idempotency_keys
+         WHERE user_id = $1 AND idempotency_key = $2"
+    )
+    .bind(user_id)
+    .bind(key)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let (stored_hash, status, body) = match stored {
+        Some(row) => row,
+        // Released by a failed request between our claim attempt and this lookup
+        None => return Err(AppError::Conflict("The request with this Idempotency-Key failed; retry it".to_string())),
+    };
+
+    if stored_hash != request_hash {
+        return Err(AppError::Conflict("This Idempotency-Key was already used for a different request".to_string()));
+    }
+
+    let status = match status.and_then(|s| StatusCode::from_u16(s as u16).ok()) {
+        Some(status) => status,
+        None => return Err(AppError::Conflict("A request with this Idempotency-Key is still in progress".to_string())),
+    };
+
+    Ok((
+        status,
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static("application/json")),
+            (HeaderName::from_static(REPLAYED), HeaderValue::from_static("true")),
+        ],
+        body.unwrap_or_default(),
+    )
+        .into_response())
+}
+
+/// Saves the handler's response for replay. Server errors release the key instead, so a
+/// retry runs the request again.
+async fn store_response(pool: &PgPool, user_id: i32, key: &str, response: Response) -> Response {
+    if response.status().is_server_error() {
+        if let Err(e) = sqlx::query("DELETE FROM idempotency_keys WHERE user_id = $1 AND idempotency_key = $2")
+            .bind(user_id)
+            .bind(key)
+            .execute(pool)
+            .await
+        {
+            tracing::error!("Could not release idempotency key: {}", e);
+        }
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => return AppError::InternalServerError(format!("Could not read response body: {}", e)).into_response(),
+    };
+
+    // The handler's changes are already committed, so its response goes out regardless
+    if let Err(e) = sqlx::query(
+        "UPDATE idempotency_keys SET response_status = $3, response_body = $4
+         WHERE user_id = $1 AND idempotency_key = $2"
+    )
+    .bind(user_id)
+    .bind(key)
+    .bind(parts.status.as_u16() as i16)
+    .bind(body.as_ref())
+    .execute(pool)
+    .await
+    {
+        tracing::error!("Could not store idempotent response: {}", e);
+    }
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Identifies a request for comparing retries. The layer runs inside the `/api/v1` and
+/// `/api` nests, which strip their prefix, so `path` is the same under either and a key
+/// reused across them still replays.
+fn hash_request(method: &Method, path: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_covers_method_path_and_body() {
+        let body = br#"{"account_id":1,"amount":"5"}"#;
+        let hash = hash_request(&Method::POST, "/transactions", body);
+        assert_eq!(hash_request(&Method::POST, "/transactions", body), hash);
+
+        assert_ne!(hash_request(&Method::POST, "/transactions/transfer", body), hash);
+        assert_ne!(hash_request(&Method::POST, "/transactions", br#"{"account_id":1,"amount":"6"}"#), hash);
+        assert_ne!(hash_request(&Method::PUT, "/transactions", body), hash);
+    }
+}
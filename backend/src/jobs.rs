@@ -0,0 +1,64 @@
+use std::time::Duration;
+use sqlx::PgPool;
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Periodically deletes rows that can never be used again, so the auth tables and the
+/// stored idempotent responses don't grow without bound.
+pub fn spawn_purge_job(pool: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+            match purge_expired(&pool).await {
+                Ok((0, 0)) => {}
+                Ok((sessions, refresh_tokens)) => {
+                    tracing::info!("Purged {} sessions and {} refresh tokens", sessions, refresh_tokens);
+                }
+                Err(e) => tracing::error!("Purge of expired rows failed: {}", e),
+            }
+        }
+    });
+}
+
+/// Deletes dead sessions, refresh tokens, login challenges and API tokens, and idempotency
+/// keys past their replay window. Returns the session and refresh token counts for the log.
+async fn purge_expired(pool: &PgPool) -> Result<(u64, u64), sqlx::Error> {
+    // An expired access token is kept while its login can still be refreshed, so the
+    // device stays in the session list
+    let sessions = sqlx::query(
+        "DELETE FROM sessions s
+         WHERE s.is_valid = FALSE
+            OR (s.expires_at < NOW() AND NOT EXISTS (
+                SELECT 1 FROM refresh_tokens r
+                WHERE r.family_id = s.family_id AND r.revoked = FALSE AND r.used_at IS NULL AND r.expires_at > NOW()
+            ))"
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    // Used tokens are kept until they expire so that replaying one is still detected
+    let refresh_tokens = sqlx::query(
+        "DELETE FROM refresh_tokens WHERE revoked = TRUE OR expires_at < NOW()"
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    // Unanswered two-factor login challenges and expired API tokens
+    sqlx::query("DELETE FROM login_challenges WHERE expires_at < NOW()")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("DELETE FROM api_tokens WHERE expires_at < NOW()")
+        .execute(pool)
+        .await?;
+
+    // Stored responses are only replayed for a day
+    sqlx::query("DELETE FROM idempotency_keys WHERE created_at < NOW() - INTERVAL '24 hours'")
+        .execute(pool)
+        .await?;
+
+    Ok((sessions, refresh_tokens))
+}
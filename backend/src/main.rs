@@ -1,66 +1,236 @@
 mod auth;
+mod config;
+mod error;
 mod models;
 mod middleware;
 mod accounts;
 mod categories;
 mod transactions;
 mod budgets;
+mod rules;
+mod payees;
+mod reports;
+mod sessions;
+mod users;
+mod jobs;
+mod rate_limit;
+mod password_policy;
+mod two_factor;
+mod api_tokens;
+mod households;
+mod state;
+mod session_cache;
+mod validation;
+mod idempotency;
+mod openapi;
+mod tls;
 
 use axum::{Router, routing::{get, post, put, delete}, Json};
+use axum::http::{header, HeaderName, Method, StatusCode};
+use axum_server::tls_rustls::RustlsConfig;
 use sqlx::postgres::PgPoolOptions;
 use serde::Serialize;
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
-use tower_http::cors::CorsLayer;
+use tower_http::{cors::{AllowOrigin, CorsLayer}, timeout::TimeoutLayer, trace::TraceLayer};
+use std::{sync::Arc, time::Duration};
+use tracing_subscriber::EnvFilter;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
-#[derive(Serialize)]
+use crate::config::{Config, CorsConfig, Environment};
+use crate::password_policy::PasswordPolicy;
+use crate::rate_limit::LoginRateLimiter;
+use crate::session_cache::SessionCache;
+use crate::state::AppState;
+
+#[derive(Serialize, utoipa::ToSchema)]
 struct Health {
     status: &'static str,
 }
 
+#[utoipa::path(get, path = "/health", tag = "health", responses((status = 200, body = Health)), security(()))]
 async fn health_check() -> Json<Health> {
     Json(Health { status: "ok" })
 }
 
+/// `None` when no origins are configured, so browsers fall back to same-origin only.
+fn cors_layer(config: &CorsConfig) -> Option<CorsLayer> {
+    if config.allows_any_origin() {
+        return Some(CorsLayer::permissive());
+    }
+    if config.allowed_origins.is_empty() {
+        return None;
+    }
+    // Origins were checked when the configuration was loaded
+    let origins = config.allowed_origins.iter().filter_map(|o| o.parse().ok()).collect::<Vec<_>>();
+    Some(
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::list(origins))
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
+            .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE, HeaderName::from_static("idempotency-key")])
+            .expose_headers([header::RETRY_AFTER, HeaderName::from_static("idempotent-replayed")]),
+    )
+}
+
 #[tokio::main]
 async fn main() -> Result<(), sqlx::Error> {
     dotenvy::dotenv().ok();
 
-    let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL not set");
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("Invalid configuration:\n{}", e);
+        std::process::exit(2);
+    });
+    tracing_subscriber::fmt().with_env_filter(EnvFilter::new(&config.log.level)).init();
+    error::expose_internal_errors(config.server.environment == Environment::Development);
+
+    // Already checked by Config::load; only fails if the breached-password file changed since
+    let password_policy = PasswordPolicy::new(&config.password).unwrap_or_else(|e| {
+        eprintln!("Invalid configuration:\n{}", e);
+        std::process::exit(2);
+    });
 
+    let db = &config.database;
     let pool = PgPoolOptions::new()
-        .max_connections(5)
-        .connect(&db_url)
+        .max_connections(db.max_connections)
+        .min_connections(db.min_connections)
+        .acquire_timeout(Duration::from_secs(db.acquire_timeout_secs))
+        .idle_timeout((db.idle_timeout_secs > 0).then(|| Duration::from_secs(db.idle_timeout_secs)))
+        .connect(db.url.as_deref().unwrap_or_default())
         .await?;
 
-    println!("Connected to PostgreSQL");
+    tracing::info!("Connected to PostgreSQL");
+
+    if config.features.purge_job {
+        jobs::spawn_purge_job(pool.clone());
+    }
+
+    let session_cache = SessionCache::new(Duration::from_secs(config.auth.session_cache_ttl_secs));
+    if session_cache.is_enabled() {
+        tracing::info!("Session cache enabled");
+    }
+
+    let config = Arc::new(config);
+    let state = AppState {
+        pool,
+        config: config.clone(),
+        login_limiter: Arc::new(LoginRateLimiter::new(&config.login)),
+        password_policy: Arc::new(password_policy),
+        session_cache: Arc::new(session_cache),
+    };
+
+    // Routes reachable without a token. Everything else goes in `protected` below.
+    let public = Router::new()
+        .route("/auth/register", post(auth::register))
+        .route("/auth/login", post(auth::login))
+        .route("/auth/refresh", post(auth::refresh))
+        .route("/auth/2fa/verify", post(two_factor::verify_login));
 
-    let app = Router::new()
+    // Write routes that clients may retry after a lost response
+    let idempotent = axum::middleware::from_fn_with_state(state.clone(), idempotency::idempotent);
+
+    let protected = Router::new()
+        .route("/auth/logout", post(auth::logout))
+        .route("/auth/2fa/setup", post(two_factor::setup))
+        .route("/auth/2fa/confirm", post(two_factor::confirm))
+        .route("/auth/2fa/disable", post(two_factor::disable))
+        .route("/auth/logout-all", post(sessions::logout_all))
+        .route("/auth/change-password", post(users::change_password))
+        .route("/auth/sessions", get(sessions::get_sessions))
+        .route("/auth/sessions/{id}", delete(sessions::revoke_session))
+        .route("/me", delete(users::delete_me))
+        .route("/me/username", put(users::change_username))
+        .route("/tokens", get(api_tokens::get_tokens))
+        .route("/tokens", post(api_tokens::create_token))
+        .route("/tokens/{id}", delete(api_tokens::delete_token))
+        .route("/households", get(households::get_households))
+        .route("/households", post(households::create_household))
+        .route("/households/{id}", delete(households::delete_household))
+        .route("/households/{id}/members", get(households::get_members))
+        .route("/households/{id}/members", post(households::add_member))
+        .route("/households/{id}/members/{user_id}", put(households::update_member))
+        .route("/households/{id}/members/{user_id}", delete(households::remove_member))
+        .route("/accounts", get(accounts::get_accounts))
+        .route("/accounts", post(accounts::create_account).layer(idempotent.clone()))
+        .route("/accounts/{id}", put(accounts::update_account))
+        .route("/accounts/{id}", delete(accounts::delete_account))
+        .route("/categories", get(categories::get_categories))
+        .route("/categories", post(categories::create_category))
+        .route("/categories/{id}", delete(categories::delete_category))
+        .route("/transactions", get(transactions::get_transactions))
+        .route("/transactions", post(transactions::create_transaction).layer(idempotent.clone()))
+        .route("/transactions/transfer", post(transactions::transfer).layer(idempotent))
+        .route("/budgets", get(budgets::get_budgets))
+        .route("/budgets", post(budgets::create_budget))
+        .route("/budgets/{id}", put(budgets::update_budget))
+        .route("/budgets/{id}", delete(budgets::delete_budget))
+        .route("/rules", get(rules::get_rules))
+        .route("/rules", post(rules::create_rule))
+        .route("/rules/apply", post(rules::apply_rules_to_existing))
+        .route("/rules/{id}", put(rules::update_rule))
+        .route("/rules/{id}", delete(rules::delete_rule))
+        .route("/payees", get(payees::get_payees))
+        .route("/payees", post(payees::create_payee))
+        .route("/payees/match", post(payees::match_transactions))
+        .route("/payees/{id}", put(payees::update_payee))
+        .route("/payees/{id}", delete(payees::delete_payee))
+        .route("/payees/{id}/aliases", post(payees::add_alias))
+        .route("/payees/{id}/aliases/{alias_id}", delete(payees::delete_alias))
+        .route("/reports/cashflow", get(reports::cashflow))
+        .route("/reports/by-category", get(reports::by_category))
+        .route("/reports/net-worth", get(reports::net_worth))
+        .route("/reports/by-payee", get(reports::spending_by_payee))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), middleware::require_auth));
+
+    let api = public.merge(protected);
+    let mut app = Router::new()
         .route("/health", get(health_check))
-        .route("/api/auth/register", post(auth::register))
-        .route("/api/auth/login", post(auth::login))
-        .route("/api/auth/logout", post(auth::logout))
-        .route("/api/accounts", get(accounts::get_accounts))
-        .route("/api/accounts", post(accounts::create_account))
-        .route("/api/accounts/{id}", put(accounts::update_account))
-        .route("/api/accounts/{id}", delete(accounts::delete_account))
-        .route("/api/categories", get(categories::get_categories))
-        .route("/api/categories", post(categories::create_category))
-        .route("/api/categories/{id}", delete(categories::delete_category))
-        .route("/api/transactions", get(transactions::get_transactions))
-        .route("/api/transactions", post(transactions::create_transaction))
-        .route("/api/transactions/transfer", post(transactions::transfer))
-        .route("/api/budgets", get(budgets::get_budgets))
-        .route("/api/budgets", post(budgets::create_budget))
-        .route("/api/budgets/{id}", put(budgets::update_budget))
-        .route("/api/budgets/{id}", delete(budgets::delete_budget))
-        .layer(CorsLayer::permissive())
-        .with_state(pool);
-
-    let addr: SocketAddr = "0.0.0.0:3000".parse().unwrap();
-    let listener = TcpListener::bind(addr).await?;
-    println!("Server running at http://{addr}");
-
-    axum::serve(listener, app).await.unwrap();
+        .nest("/api/v1", api.clone())
+        // Unversioned paths from before /api/v1, kept for clients that haven't moved yet
+        .nest("/api", api);
+
+    if config.features.api_docs {
+        // Swagger UI at /api/v1/docs, reading the spec it also serves at /api/v1/openapi.json.
+        // The pre-versioning locations stay too; the spec is the same and lists /api/v1 paths.
+        let spec = openapi::ApiDoc::openapi();
+        app = app
+            .merge(SwaggerUi::new("/api/v1/docs").url("/api/v1/openapi.json", spec.clone()))
+            .merge(SwaggerUi::new("/api/docs").url("/api/openapi.json", spec));
+    }
+    if let Some(timeout) = config.request_timeout() {
+        app = app.layer(TimeoutLayer::with_status_code(StatusCode::REQUEST_TIMEOUT, timeout));
+    }
+    if let Some(cors) = cors_layer(&config.cors) {
+        app = app.layer(cors);
+    }
+    let app = app.layer(TraceLayer::new_for_http()).with_state(state);
+
+    let addr = config.server.bind;
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+
+    let Some((cert_file, key_file)) = config.tls.files() else {
+        let listener = TcpListener::bind(addr).await?;
+        tracing::info!("Server running at http://{addr}");
+        axum::serve(listener, app).await.unwrap();
+        return Ok(());
+    };
+
+    let server_config = tls::load_server_config(cert_file, key_file).unwrap_or_else(|e| {
+        eprintln!("Invalid TLS configuration:\n{}", e);
+        std::process::exit(2);
+    });
+    let rustls = RustlsConfig::from_config(Arc::new(server_config));
+    if let Some(interval) = config.tls.reload_interval() {
+        tls::spawn_reload(rustls.clone(), cert_file.to_path_buf(), key_file.to_path_buf(), interval);
+    }
+
+    if let Some(redirect_addr) = config.tls.redirect_http_bind {
+        let listener = TcpListener::bind(redirect_addr).await?;
+        tracing::info!("Redirecting http://{redirect_addr} to HTTPS");
+        tokio::spawn(async move { axum::serve(listener, tls::redirect_router(addr.port())).await });
+    }
+
+    tracing::info!("Server running at https://{addr}");
+    axum_server::bind_rustls(addr, rustls).serve(app).await?;
     Ok(())
 }
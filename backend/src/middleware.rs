@@ -1,27 +1,94 @@
-use axum::http::HeaderMap;
+use axum::{
+    extract::{FromRef, FromRequestParts, Request, State},
+    http::{request::Parts, HeaderMap},
+    middleware::Next,
+    response::Response,
+};
 use sqlx::PgPool;
-use crate::auth::{AuthUser, AppError};
+use std::net::SocketAddr;
+use subtle::ConstantTimeEq;
+use crate::auth::{self, AuthUser};
+use crate::error::AppError;
+use crate::api_tokens::{self, Scope};
+use crate::session_cache::SessionCache;
+use crate::state::AppState;
 
-pub async fn verify_auth(
+/// Layer for the protected part of the router: rejects requests without a valid token
+/// and stores the caller in the request extensions for the [`AuthUser`] extractor.
+pub async fn require_auth(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let user = authenticate(&state.pool, &state.session_cache, request.headers()).await?;
+    request.extensions_mut().insert(user);
+    Ok(next.run(request).await)
+}
+
+impl<S> FromRequestParts<S> for AuthUser
+where
+    S: Send + Sync,
+    AppState: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(user) = parts.extensions.get::<AuthUser>() {
+            return Ok(user.clone());
+        }
+        // Only reached on routes outside `require_auth`
+        let state = AppState::from_ref(state);
+        authenticate(&state.pool, &state.session_cache, &parts.headers).await
+    }
+}
+
+async fn authenticate(
     pool: &PgPool,
+    cache: &SessionCache,
     headers: &HeaderMap,
 ) -> Result<AuthUser, AppError> {
     let token = extract_token(headers)
         .ok_or_else(|| AppError::Unauthorized("Missing or invalid authorization header".to_string()))?;
 
-    let session = sqlx::query_as::<_, (i32, String, bool, chrono::DateTime<chrono::Utc>)>(
-        "SELECT user_id, token, is_valid, expires_at FROM sessions WHERE token = $1"
+    let token_hash = auth::hash_token(&token);
+
+    if let Some(user) = cache.get(&token_hash) {
+        return Ok(user);
+    }
+
+    let (user, expires_at) = if token.starts_with(api_tokens::TOKEN_PREFIX) {
+        verify_api_token(pool, &token_hash).await?
+    } else {
+        verify_session(pool, &token_hash).await?
+    };
+
+    cache.insert(token_hash, user.clone(), expires_at);
+    Ok(user)
+}
+
+async fn verify_session(
+    pool: &PgPool,
+    token_hash: &str,
+) -> Result<(AuthUser, chrono::DateTime<chrono::Utc>), AppError> {
+
+    let session = sqlx::query_as::<_, (i32, i32, String, bool, chrono::DateTime<chrono::Utc>)>(
+        "SELECT id, user_id, token_hash, is_valid, expires_at FROM sessions WHERE token_hash = $1"
     )
-    .bind(&token)
+    .bind(token_hash)
     .fetch_optional(pool)
     .await
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
-    let (user_id, token, is_valid, expires_at) = match session {
+    let (session_id, user_id, stored_hash, is_valid, expires_at) = match session {
         Some(s) => s,
         None => return Err(AppError::Unauthorized("Invalid session token".to_string())),
     };
 
+    // The index lookup already matched; re-check without an early-exit comparison
+    if !bool::from(stored_hash.as_bytes().ct_eq(token_hash.as_bytes())) {
+        return Err(AppError::Unauthorized("Invalid session token".to_string()));
+    }
+
     if !is_valid {
         return Err(AppError::Unauthorized("Session has been invalidated".to_string()));
     }
@@ -30,14 +97,82 @@ pub async fn verify_auth(
         return Err(AppError::Unauthorized("Session has expired".to_string()));
     }
 
-    Ok(AuthUser { user_id, token })
+    // Throttled so that a burst of requests doesn't turn every read into a write
+    sqlx::query(
+        "UPDATE sessions SET last_used_at = NOW()
+         WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')"
+    )
+    .bind(session_id)
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok((AuthUser { user_id, session_id: Some(session_id), scopes: None }, expires_at))
+}
+
+async fn verify_api_token(
+    pool: &PgPool,
+    token_hash: &str,
+) -> Result<(AuthUser, chrono::DateTime<chrono::Utc>), AppError> {
+    let token = sqlx::query_as::<_, (i32, i32, String, Vec<String>, chrono::DateTime<chrono::Utc>)>(
+        "SELECT id, user_id, token_hash, scopes, expires_at FROM api_tokens WHERE token_hash = $1"
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let (token_id, user_id, stored_hash, scopes, expires_at) = match token {
+        Some(t) => t,
+        None => return Err(AppError::Unauthorized("Invalid API token".to_string())),
+    };
+
+    if !bool::from(stored_hash.as_bytes().ct_eq(token_hash.as_bytes())) {
+        return Err(AppError::Unauthorized("Invalid API token".to_string()));
+    }
+
+    if expires_at < chrono::Utc::now() {
+        return Err(AppError::Unauthorized("API token has expired".to_string()));
+    }
+
+    sqlx::query(
+        "UPDATE api_tokens SET last_used_at = NOW()
+         WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')"
+    )
+    .bind(token_id)
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    // Scopes are validated on creation; ignore any that a newer build no longer knows
+    let scopes = scopes.iter().filter_map(|s| Scope::parse(s)).collect();
+
+    Ok((AuthUser { user_id, session_id: None, scopes: Some(scopes) }, expires_at))
+}
+
+/// Client address for the session list, preferring the first `X-Forwarded-For` hop
+/// when the server runs behind a reverse proxy. The client controls that header, so this
+/// is for display only; rate limiting uses [`crate::rate_limit::client_key`].
+pub fn client_ip(headers: &HeaderMap, addr: &SocketAddr) -> String {
+    headers
+        .get("X-Forwarded-For")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .map(|ip| ip.trim().to_string())
+        .filter(|ip| !ip.is_empty() && ip.len() <= 45)
+        .unwrap_or_else(|| addr.ip().to_string())
+}
+
+pub fn user_agent(headers: &HeaderMap) -> Option<String> {
+    let ua = headers.get("User-Agent")?.to_str().ok()?;
+    Some(ua.chars().take(255).collect())
 }
 
 fn extract_token(headers: &HeaderMap) -> Option<String> {
     let auth_header = headers.get("Authorization")?.to_str().ok()?;
     
-    if auth_header.starts_with("Bearer ") {
-        Some(auth_header[7..].to_string())
+    if let Some(token) = auth_header.strip_prefix("Bearer ") {
+        Some(token.to_string())
     } else {
         Some(auth_header.to_string())
     }
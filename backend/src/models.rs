@@ -1,40 +1,10 @@
-use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
 use sqlx::FromRow;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct RegisterRequest {
-    pub username: String,
-    pub password: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct LoginRequest {
-    pub username: String,
-    pub password: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct RegisterResponse {
-    pub message: String,
-    pub user_id: i32,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct LoginResponse {
-    pub message: String,
-    pub token: String,
-    pub user_id: i32,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct LogoutResponse {
-    pub message: String,
-}
+// Request and response bodies are shared with the clients
+pub use finance_api_types::models::*;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ErrorResponse {
-    pub error: String,
-}
+// Database rows
 
 #[derive(Debug, FromRow)]
 pub struct User {
@@ -44,6 +14,10 @@ pub struct User {
     pub password_hash: String,
     #[allow(dead_code)]
     pub created_at: chrono::DateTime<chrono::Utc>,
+    #[allow(dead_code)]
+    pub failed_login_attempts: i32,
+    pub locked_until: Option<chrono::DateTime<chrono::Utc>>,
+    pub totp_enabled: bool,
 }
 
 #[derive(Debug, FromRow)]
@@ -51,47 +25,14 @@ pub struct User {
 pub struct Session {
     pub id: i32,
     pub user_id: i32,
-    pub token: String,
+    pub token_hash: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub expires_at: chrono::DateTime<chrono::Utc>,
     pub is_valid: bool,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateAccountRequest {
-    pub name: String,
-    pub currency: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct AccountResponse {
-    pub id: i32,
-    pub name: String,
-    pub currency: String,
-    pub balance: String,
-    pub created_at: chrono::DateTime<chrono::Utc>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateAccountResponse {
-    pub message: String,
-    pub account: AccountResponse,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UpdateAccountRequest {
-    pub name: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UpdateAccountResponse {
-    pub message: String,
-    pub account: AccountResponse,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DeleteAccountResponse {
-    pub message: String,
+    pub family_id: Option<String>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
 }
 
 #[derive(Debug)]
@@ -99,94 +40,25 @@ pub struct Account {
     pub id: i32,
     #[allow(dead_code)]
     pub user_id: i32,
+    pub household_id: Option<i32>,
     pub name: String,
     pub currency: String,
-    pub balance: String,
+    pub balance: Decimal,
+    pub is_liability: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateCategoryRequest {
-    pub name: String,
-    pub parent_id: Option<i32>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CategoryResponse {
-    pub id: i32,
-    pub name: String,
-    pub parent_id: Option<i32>,
-    pub created_at: chrono::DateTime<chrono::Utc>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateCategoryResponse {
-    pub message: String,
-    pub category: CategoryResponse,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DeleteCategoryResponse {
-    pub message: String,
-}
-
 #[derive(Debug, FromRow)]
 pub struct Category {
     pub id: i32,
     #[allow(dead_code)]
     pub user_id: i32,
+    pub household_id: Option<i32>,
     pub name: String,
     pub parent_id: Option<i32>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateTransactionRequest {
-    pub account_id: Option<i32>,
-    pub account_name: Option<String>,
-    pub amount: String,
-    pub date: Option<String>,
-    pub category_id: Option<i32>,
-    pub category_name: Option<String>,
-    pub description: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TransactionResponse {
-    pub id: i32,
-    pub account_id: i32,
-    pub account_name: String,
-    pub category_id: Option<i32>,
-    pub category_name: Option<String>,
-    pub amount: String,
-    pub r#type: String,
-    pub date: chrono::NaiveDate,
-    pub description: Option<String>,
-    pub created_at: chrono::DateTime<chrono::Utc>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateTransactionResponse {
-    pub message: String,
-    pub transaction: TransactionResponse,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TransferRequest {
-    pub from_account_id: i32,
-    pub to_account_id: i32,
-    pub amount: String,
-    pub date: Option<String>,
-    pub description: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TransferResponse {
-    pub message: String,
-    pub from_transaction: TransactionResponse,
-    pub to_transaction: TransactionResponse,
-}
-
 #[derive(Debug)]
 #[allow(dead_code)]
 pub struct Transaction {
@@ -194,70 +66,44 @@ pub struct Transaction {
     pub user_id: i32,
     pub account_id: i32,
     pub category_id: Option<i32>,
-    pub amount: String,
+    pub amount: Decimal,
     pub r#type: String,
     pub date: chrono::NaiveDate,
     pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub payee_id: Option<i32>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateBudgetRequest {
-    pub category_id: Option<i32>,
-    pub amount: String,
-    pub period: Option<String>,
-    pub start_date: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BudgetResponse {
+#[derive(Debug, FromRow)]
+#[allow(dead_code)]
+pub struct Budget {
     pub id: i32,
+    pub user_id: i32,
     pub category_id: Option<i32>,
-    pub category_name: Option<String>,
-    pub amount: String,
+    pub amount: Decimal,
     pub period: Option<String>,
     pub start_date: chrono::NaiveDate,
-    pub spent: String,
-    pub remaining: String,
-    pub is_over_budget: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateBudgetResponse {
-    pub message: String,
-    pub budget: BudgetResponse,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UpdateBudgetRequest {
-    pub amount: String,
-    pub period: Option<String>,
-    pub start_date: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UpdateBudgetResponse {
-    pub message: String,
-    pub budget: BudgetResponse,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DeleteBudgetResponse {
-    pub message: String,
-}
-
-#[derive(Debug, FromRow)]
+#[derive(Debug)]
 #[allow(dead_code)]
-pub struct Budget {
+pub struct Rule {
     pub id: i32,
     pub user_id: i32,
-    pub category_id: Option<i32>,
-    pub amount: String,
-    pub period: Option<String>,
-    pub start_date: chrono::NaiveDate,
+    pub name: String,
+    pub priority: i32,
+    pub stop_processing: bool,
+    pub description_contains: Option<String>,
+    pub description_regex: Option<String>,
+    pub min_amount: Option<Decimal>,
+    pub max_amount: Option<Decimal>,
+    pub account_id: Option<i32>,
+    pub set_category_id: Option<i32>,
+    pub add_tags: Vec<String>,
+    pub set_description: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
-
@@ -0,0 +1,144 @@
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    openapi::{ContentBuilder, OpenApi as OpenApiDoc, RefOr, ResponseBuilder},
+    Modify, OpenApi,
+};
+
+use crate::error::ErrorResponse;
+use crate::{
+    accounts, api_tokens, auth, budgets, categories, households, payees, reports, rules, sessions,
+    transactions, two_factor, users,
+};
+
+/// The API description served at `/api/v1/openapi.json`, generated from the handlers'
+/// `#[utoipa::path]` attributes and the request/response types. A copy is committed as
+/// `backend/openapi.json`; see the test below.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Finance Tracker API",
+        description = "Accounts, transactions, budgets, rules, payees and reports. Errors share the `ErrorResponse` shape.",
+    ),
+    paths(
+        crate::health_check,
+        auth::register, auth::login, auth::refresh, auth::logout,
+        two_factor::verify_login, two_factor::setup, two_factor::confirm, two_factor::disable,
+        sessions::get_sessions, sessions::revoke_session, sessions::logout_all,
+        users::change_password, users::change_username, users::delete_me,
+        api_tokens::get_tokens, api_tokens::create_token, api_tokens::delete_token,
+        households::get_households, households::create_household, households::delete_household,
+        households::get_members, households::add_member, households::update_member, households::remove_member,
+        accounts::get_accounts, accounts::create_account, accounts::update_account, accounts::delete_account,
+        categories::get_categories, categories::create_category, categories::delete_category,
+        transactions::get_transactions, transactions::create_transaction, transactions::transfer,
+        budgets::get_budgets, budgets::create_budget, budgets::update_budget, budgets::delete_budget,
+        rules::get_rules, rules::create_rule, rules::apply_rules_to_existing, rules::update_rule, rules::delete_rule,
+        payees::get_payees, payees::create_payee, payees::match_transactions, payees::update_payee,
+        payees::delete_payee, payees::add_alias, payees::delete_alias,
+        reports::cashflow, reports::by_category, reports::net_worth, reports::spending_by_payee,
+    ),
+    components(schemas(ErrorResponse)),
+    modifiers(&NoLicense, &BearerAuth, &ErrorResponses),
+    security(("bearer_auth" = [])),
+)]
+pub struct ApiDoc;
+
+/// utoipa copies the license from Cargo.toml, and the crate doesn't declare one.
+struct NoLicense;
+
+impl Modify for NoLicense {
+    fn modify(&self, openapi: &mut OpenApiDoc) {
+        openapi.info.license = None;
+    }
+}
+
+/// Session tokens and `fin_` API tokens both go in `Authorization: Bearer <token>`.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut OpenApiDoc) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some("A session token from login, or a personal API token"))
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Every operation can fail with an `ErrorResponse`; listing it once per operation as
+/// the `default` response keeps the handler attributes down to their success case.
+struct ErrorResponses;
+
+impl Modify for ErrorResponses {
+    fn modify(&self, openapi: &mut OpenApiDoc) {
+        let response = ResponseBuilder::new()
+            .description("Error; branch on `code`")
+            .content("application/json", ContentBuilder::new().schema(Some(RefOr::Ref(
+                utoipa::openapi::Ref::from_schema_name("ErrorResponse"),
+            ))).build())
+            .build();
+        for path in openapi.paths.paths.values_mut() {
+            for operation in [
+                &mut path.get, &mut path.post, &mut path.put, &mut path.delete,
+            ].into_iter().flatten() {
+                operation.responses.responses.insert("default".to_string(), RefOr::T(response.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+    /// Fails when the handlers or models change the served spec without the committed
+    /// copy being updated. Run with `UPDATE_OPENAPI=1` to rewrite it.
+    #[test]
+    fn committed_spec_is_up_to_date() {
+        let generated = ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+        if std::env::var_os("UPDATE_OPENAPI").is_some() {
+            std::fs::write(SPEC_PATH, &generated).unwrap();
+            return;
+        }
+        let committed = std::fs::read_to_string(SPEC_PATH).unwrap_or_default();
+        assert!(
+            committed == generated,
+            "backend/openapi.json is out of date; run `UPDATE_OPENAPI=1 cargo test` and commit the result"
+        );
+    }
+
+    /// Every `.route(...)` in main.rs must have a `#[utoipa::path]` listed in `ApiDoc`,
+    /// and the other way round. API routes are written relative to their `/api/v1` nest.
+    #[test]
+    fn every_route_is_documented() {
+        let mut routed = Vec::new();
+        for line in include_str!("main.rs").lines() {
+            let Some(rest) = line.trim().strip_prefix(".route(\"") else { continue };
+            let (path, handler) = rest.split_once('"').unwrap();
+            let method = handler.trim_start_matches(", ").split('(').next().unwrap();
+            let prefix = if path == "/health" { "" } else { "/api/v1" };
+            routed.push(format!("{} {}{}", method, prefix, path));
+        }
+
+        let spec = ApiDoc::openapi();
+        let mut documented = Vec::new();
+        for (path, item) in &spec.paths.paths {
+            for (method, operation) in [("get", &item.get), ("post", &item.post), ("put", &item.put), ("delete", &item.delete)] {
+                if operation.is_some() {
+                    documented.push(format!("{} {}", method, path));
+                }
+            }
+        }
+
+        routed.sort();
+        documented.sort();
+        assert_eq!(routed, documented);
+    }
+}
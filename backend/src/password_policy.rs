@@ -0,0 +1,178 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use crate::config::PasswordConfig;
+
+/// Rules applied to every new password (registration and password change), from the
+/// `[password]` settings.
+pub struct PasswordPolicy {
+    min_length: usize,
+    max_length: usize,
+    min_strength: u8,
+    breached: Option<HashSet<[u8; 20]>>,
+}
+
+impl PasswordPolicy {
+    /// Fails only if the breached-password file can't be read; the limits themselves are
+    /// checked when the configuration is loaded.
+    pub fn new(config: &PasswordConfig) -> Result<Self, String> {
+        let breached = match &config.breached_passwords_file {
+            Some(path) => Some(load_breached_hashes(path)?),
+            None => None,
+        };
+
+        Ok(Self {
+            min_length: config.min_length,
+            max_length: config.max_length,
+            min_strength: config.min_strength,
+            breached,
+        })
+    }
+
+    pub fn max_length(&self) -> usize {
+        self.max_length
+    }
+
+    /// Checks a new password. `user_inputs` (e.g. the username) count against its strength.
+    /// The error is a readable reason; callers attach it to their own field name.
+    pub fn validate(&self, password: &str, user_inputs: &[&str]) -> Result<(), String> {
+        let length = password.chars().count();
+        if length < self.min_length {
+            return Err(format!("Password must be at least {} characters", self.min_length));
+        }
+        if length > self.max_length {
+            return Err(format!("Password must be at most {} characters", self.max_length));
+        }
+
+        if self.is_breached(password) {
+            return Err("This password has appeared in a known data breach; please choose a different one".to_string());
+        }
+
+        let estimate = zxcvbn::zxcvbn(password, user_inputs);
+        if u8::from(estimate.score()) < self.min_strength {
+            let mut message = "Password is too weak.".to_string();
+            if let Some(feedback) = estimate.feedback() {
+                if let Some(warning) = feedback.warning() {
+                    message = format!("Password is too weak: {}", warning);
+                }
+                for suggestion in feedback.suggestions() {
+                    message.push_str(&format!(" {}", suggestion));
+                }
+            }
+            return Err(message);
+        }
+
+        Ok(())
+    }
+
+    fn is_breached(&self, password: &str) -> bool {
+        use sha1::{Digest, Sha1};
+
+        match &self.breached {
+            Some(hashes) => hashes.contains(&<[u8; 20]>::from(Sha1::digest(password.as_bytes()))),
+            None => false,
+        }
+    }
+}
+
+fn load_breached_hashes(path: &Path) -> Result<HashSet<[u8; 20]>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read breached passwords file {}: {}", path.display(), e))?;
+
+    let mut hashes = HashSet::new();
+    for (line_no, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let hex = line.split(':').next().unwrap_or_default();
+        let digest = parse_sha1_hex(hex)
+            .ok_or_else(|| format!("{}:{}: expected a 40-character SHA-1 hex digest", path.display(), line_no + 1))?;
+        hashes.insert(digest);
+    }
+
+    tracing::info!("Loaded {} breached password hashes from {}", hashes.len(), path.display());
+    Ok(hashes)
+}
+
+fn parse_sha1_hex(hex: &str) -> Option<[u8; 20]> {
+    if hex.len() != 40 || !hex.is_ascii() {
+        return None;
+    }
+    let mut digest = [0u8; 20];
+    for (i, byte) in digest.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sha1::{Digest, Sha1};
+
+    const STRONG: &str = "violet-canoe-harbor-71";
+
+    fn config() -> PasswordConfig {
+        PasswordConfig { min_length: 8, max_length: 30, min_strength: 2, breached_passwords_file: None }
+    }
+
+    fn sha1_hex(password: &str) -> String {
+        Sha1::digest(password.as_bytes()).iter().map(|b| format!("{:02X}", b)).collect()
+    }
+
+    /// Builds a policy whose breached-password file has the given contents.
+    fn with_breached_file(contents: &str) -> Result<PasswordPolicy, String> {
+        let path = std::env::temp_dir().join(format!("backend-breached-{}.txt", uuid::Uuid::new_v4()));
+        fs::write(&path, contents).unwrap();
+        let policy = PasswordPolicy::new(&PasswordConfig { breached_passwords_file: Some(path.clone()), ..config() });
+        fs::remove_file(path).unwrap();
+        policy
+    }
+
+    #[test]
+    fn length_is_counted_in_characters() {
+        let policy = PasswordPolicy::new(&PasswordConfig { min_strength: 0, ..config() }).unwrap();
+        // 7 characters but 14 bytes
+        assert!(policy.validate("ééééééé", &[]).is_err());
+        assert!(policy.validate("éééééééé", &[]).is_ok());
+        assert!(policy.validate(&"ü".repeat(30), &[]).is_ok());
+        assert!(policy.validate(&"ü".repeat(31), &[]).unwrap_err().contains("at most 30"));
+    }
+
+    #[test]
+    fn weak_passwords_are_rejected_below_the_strength_threshold() {
+        let policy = PasswordPolicy::new(&config()).unwrap();
+        assert!(policy.validate("password", &[]).unwrap_err().starts_with("Password is too weak"));
+        assert!(policy.validate(STRONG, &[]).is_ok());
+        // The username counts against the password's strength
+        assert!(policy.validate("jsmith2024", &[]).is_ok());
+        assert!(policy.validate("jsmith2024", &["jsmith2024"]).is_err());
+
+        let lenient = PasswordPolicy::new(&PasswordConfig { min_strength: 0, ..config() }).unwrap();
+        assert!(lenient.validate("password", &[]).is_ok());
+    }
+
+    #[test]
+    fn breached_file_accepts_plain_and_counted_hashes_in_any_case() {
+        let others = ["another-long-passphrase-1", "yet-another-passphrase-2"];
+        let contents = format!(
+            "# comment\n{}\n\n{}:42\n{}:7\n",
+            sha1_hex(STRONG),
+            sha1_hex(others[0]).to_lowercase(),
+            sha1_hex(others[1]),
+        );
+        let policy = with_breached_file(&contents).unwrap();
+
+        for password in [STRONG, others[0], others[1]] {
+            assert!(policy.validate(password, &[]).unwrap_err().contains("data breach"), "{}", password);
+        }
+        assert!(policy.validate("a-fresh-unbreached-phrase-9", &[]).is_ok());
+    }
+
+    #[test]
+    fn malformed_breached_file_lines_are_reported_with_their_line_number() {
+        let error = with_breached_file(&format!("{}\nnot-a-hash\n", sha1_hex(STRONG))).err().unwrap();
+        assert!(error.contains(":2:"), "{}", error);
+        assert!(with_breached_file(&sha1_hex(STRONG)[..39]).is_err());
+    }
+}
@@ -0,0 +1,552 @@
+use axum::{
+    extract::{State, Path},
+    Json,
+};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+
+use crate::models::{
+    CreatePayeeRequest, CreatePayeeResponse, PayeeResponse, PayeeAliasResponse,
+    UpdatePayeeRequest, UpdatePayeeResponse, DeletePayeeResponse,
+    AddPayeeAliasRequest, AddPayeeAliasResponse, DeletePayeeAliasResponse,
+    MatchPayeesResponse,
+};
+use crate::auth::AuthUser;
+use crate::error::AppError;
+use crate::validation::ValidatedJson;
+use crate::api_tokens::Scope;
+
+/// A payee together with the normalised forms of its name and aliases.
+pub struct PayeeMatcher {
+    pub payee_id: i32,
+    pub default_category_id: Option<i32>,
+    patterns: Vec<String>,
+}
+
+/// Creates a payee with optional aliases used for matching transaction descriptions.
+#[utoipa::path(
+    post,
+    path = "/api/v1/payees",
+    tag = "payees",
+    request_body = CreatePayeeRequest,
+    responses((status = 200, body = CreatePayeeResponse)),
+)]
+pub async fn create_payee(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<CreatePayeeRequest>,
+) -> Result<Json<CreatePayeeResponse>, AppError> {
+    auth.require_scope(Scope::PayeesWrite)?;
+
+    let name = req.name.as_str();
+    let aliases = req.aliases.unwrap_or_default();
+    for alias in &aliases {
+        validate_alias(alias, "aliases")?;
+    }
+    verify_category(&pool, auth.user_id, req.default_category_id).await?;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let payee_id = sqlx::query_scalar::<_, i32>(
+        "INSERT INTO payees (user_id, name, default_category_id) VALUES ($1, $2, $3) RETURNING id"
+    )
+    .bind(auth.user_id)
+    .bind(name)
+    .bind(req.default_category_id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(map_unique_violation)?;
+
+    for alias in &aliases {
+        sqlx::query(
+            "INSERT INTO payee_aliases (payee_id, alias) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+        )
+        .bind(payee_id)
+        .bind(alias.trim())
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    }
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let payee = fetch_payee(&pool, auth.user_id, payee_id).await?;
+
+    Ok(Json(CreatePayeeResponse {
+        message: "Payee created successfully".to_string(),
+        payee,
+    }))
+}
+
+/// Lists the user's payees with their aliases.
+#[utoipa::path(
+    get,
+    path = "/api/v1/payees",
+    tag = "payees",
+    responses((status = 200, body = Vec<PayeeResponse>)),
+)]
+pub async fn get_payees(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+) -> Result<Json<Vec<PayeeResponse>>, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let rows = sqlx::query(
+        "SELECT id, name, default_category_id, created_at FROM payees WHERE user_id = $1 ORDER BY name"
+    )
+    .bind(auth.user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let mut aliases = fetch_aliases(&pool, auth.user_id).await?;
+
+    let payees_response: Vec<PayeeResponse> = rows
+        .into_iter()
+        .map(|row| {
+            let id: i32 = row.get(0);
+            PayeeResponse {
+                id,
+                name: row.get(1),
+                default_category_id: row.get(2),
+                aliases: aliases.remove(&id).unwrap_or_default(),
+                created_at: row.get(3),
+            }
+        })
+        .collect();
+
+    Ok(Json(payees_response))
+}
+
+/// Renames a payee or changes its default category.
+#[utoipa::path(
+    put,
+    path = "/api/v1/payees/{id}",
+    tag = "payees",
+    params(("id" = i32, Path, description = "Payee id")),
+    request_body = UpdatePayeeRequest,
+    responses((status = 200, body = UpdatePayeeResponse)),
+)]
+pub async fn update_payee(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Path(payee_id): Path<i32>,
+    ValidatedJson(req): ValidatedJson<UpdatePayeeRequest>,
+) -> Result<Json<UpdatePayeeResponse>, AppError> {
+    auth.require_scope(Scope::PayeesWrite)?;
+
+    let name = req.name.as_str();
+    verify_category(&pool, auth.user_id, req.default_category_id).await?;
+
+    let result = sqlx::query(
+        "UPDATE payees SET name = $1, default_category_id = $2 WHERE id = $3 AND user_id = $4"
+    )
+    .bind(name)
+    .bind(req.default_category_id)
+    .bind(payee_id)
+    .bind(auth.user_id)
+    .execute(&pool)
+    .await
+    .map_err(map_unique_violation)?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Payee not found or you don't have permission to update it".to_string()));
+    }
+
+    let payee = fetch_payee(&pool, auth.user_id, payee_id).await?;
+
+    Ok(Json(UpdatePayeeResponse {
+        message: "Payee updated successfully".to_string(),
+        payee,
+    }))
+}
+
+/// Deletes a payee; its transactions keep their descriptions but lose the link.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/payees/{id}",
+    tag = "payees",
+    params(("id" = i32, Path, description = "Payee id")),
+    responses((status = 200, body = DeletePayeeResponse)),
+)]
+pub async fn delete_payee(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Path(payee_id): Path<i32>,
+) -> Result<Json<DeletePayeeResponse>, AppError> {
+    auth.require_scope(Scope::PayeesWrite)?;
+
+    let result = sqlx::query(
+        "DELETE FROM payees WHERE id = $1 AND user_id = $2"
+    )
+    .bind(payee_id)
+    .bind(auth.user_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Payee not found or you don't have permission to delete it".to_string()));
+    }
+
+    Ok(Json(DeletePayeeResponse {
+        message: "Payee deleted successfully".to_string(),
+    }))
+}
+
+/// Adds an alias to a payee.
+#[utoipa::path(
+    post,
+    path = "/api/v1/payees/{id}/aliases",
+    tag = "payees",
+    params(("id" = i32, Path, description = "Payee id")),
+    request_body = AddPayeeAliasRequest,
+    responses((status = 200, body = AddPayeeAliasResponse)),
+)]
+pub async fn add_alias(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Path(payee_id): Path<i32>,
+    ValidatedJson(req): ValidatedJson<AddPayeeAliasRequest>,
+) -> Result<Json<AddPayeeAliasResponse>, AppError> {
+    auth.require_scope(Scope::PayeesWrite)?;
+
+    validate_alias(&req.alias, "alias")?;
+
+    let row = sqlx::query(
+        "INSERT INTO payee_aliases (payee_id, alias)
+         SELECT id, $1 FROM payees WHERE id = $2 AND user_id = $3
+         RETURNING id, alias"
+    )
+    .bind(req.alias.trim())
+    .bind(payee_id)
+    .bind(auth.user_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| {
+        if let sqlx::Error::Database(db_err) = &e
+            && db_err.constraint() == Some("payee_aliases_payee_id_alias_key")
+        {
+            return AppError::Conflict("Alias already exists for this payee".to_string());
+        }
+        AppError::InternalServerError(format!("Database error: {}", e))
+    })?;
+
+    let row = match row {
+        Some(r) => r,
+        None => return Err(AppError::NotFound("Payee not found or you don't have permission to update it".to_string())),
+    };
+
+    Ok(Json(AddPayeeAliasResponse {
+        message: "Alias added successfully".to_string(),
+        alias: PayeeAliasResponse {
+            id: row.get(0),
+            alias: row.get(1),
+        },
+    }))
+}
+
+/// Removes an alias from a payee.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/payees/{id}/aliases/{alias_id}",
+    tag = "payees",
+    params(
+        ("id" = i32, Path, description = "Payee id"),
+        ("alias_id" = i32, Path, description = "Alias id"),
+    ),
+    responses((status = 200, body = DeletePayeeAliasResponse)),
+)]
+pub async fn delete_alias(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Path((payee_id, alias_id)): Path<(i32, i32)>,
+) -> Result<Json<DeletePayeeAliasResponse>, AppError> {
+    auth.require_scope(Scope::PayeesWrite)?;
+
+    let result = sqlx::query(
+        "DELETE FROM payee_aliases pa USING payees p
+         WHERE pa.id = $1 AND pa.payee_id = $2 AND p.id = pa.payee_id AND p.user_id = $3"
+    )
+    .bind(alias_id)
+    .bind(payee_id)
+    .bind(auth.user_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Alias not found or you don't have permission to delete it".to_string()));
+    }
+
+    Ok(Json(DeletePayeeAliasResponse {
+        message: "Alias deleted successfully".to_string(),
+    }))
+}
+
+/// Links every transaction that has no payee yet to the best matching payee.
+#[utoipa::path(
+    post,
+    path = "/api/v1/payees/match",
+    tag = "payees",
+    responses((status = 200, body = MatchPayeesResponse)),
+)]
+pub async fn match_transactions(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+) -> Result<Json<MatchPayeesResponse>, AppError> {
+    auth.require_scope(Scope::PayeesWrite)?;
+    auth.require_scope(Scope::TransactionsWrite)?;
+
+    let matchers = load_matchers(&pool, auth.user_id).await?;
+
+    let rows = sqlx::query(
+        "SELECT t.id, t.description FROM transactions t
+         JOIN accounts a ON t.account_id = a.id
+         WHERE user_can_edit($1, a.user_id, a.household_id)
+         AND t.payee_id IS NULL AND t.type <> 'transfer' AND t.description IS NOT NULL"
+    )
+    .bind(auth.user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    let mut matched = 0;
+
+    for row in rows {
+        let description: String = row.get(1);
+        if let Some(payee) = match_payee(&matchers, &description) {
+            // Fill in the payee's default category only where none was chosen, and only
+            // if it belongs to the same household (or none) as the transaction's account
+            let result = sqlx::query(
+                "UPDATE transactions t SET payee_id = $1, category_id = COALESCE(t.category_id, (
+                     SELECT c.id FROM categories c WHERE c.id = $2 AND c.household_id IS NOT DISTINCT FROM a.household_id
+                 ))
+                 FROM accounts a
+                 WHERE t.id = $3 AND a.id = t.account_id AND user_can_edit($4, a.user_id, a.household_id)"
+            )
+            .bind(payee.payee_id)
+            .bind(payee.default_category_id)
+            .bind(row.get::<i32, _>(0))
+            .bind(auth.user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+            matched += result.rows_affected();
+        }
+    }
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(Json(MatchPayeesResponse {
+        message: format!("{} transaction(s) linked to payees", matched),
+        matched,
+    }))
+}
+
+/// Loads every payee of a user with its normalised name and aliases.
+pub async fn load_matchers(pool: &PgPool, user_id: i32) -> Result<Vec<PayeeMatcher>, AppError> {
+    let rows = sqlx::query(
+        "SELECT p.id, p.default_category_id, p.name, pa.alias
+         FROM payees p
+         LEFT JOIN payee_aliases pa ON pa.payee_id = p.id
+         WHERE p.user_id = $1
+         ORDER BY p.id"
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let mut matchers: Vec<PayeeMatcher> = Vec::new();
+    for row in rows {
+        let payee_id: i32 = row.get(0);
+        if matchers.last().map(|m| m.payee_id) != Some(payee_id) {
+            let name: String = row.get(2);
+            matchers.push(PayeeMatcher {
+                payee_id,
+                default_category_id: row.get(1),
+                patterns: vec![normalize_description(&name)],
+            });
+        }
+        if let Some(alias) = row.get::<Option<String>, _>(3)
+            && let Some(matcher) = matchers.last_mut()
+        {
+            matcher.patterns.push(normalize_description(&alias));
+        }
+    }
+
+    Ok(matchers)
+}
+
+/// Finds the payee whose name or alias appears (as whole words) in the
+/// description. The longest matching pattern wins, so "AMZN MKTP" beats "AMZN".
+pub fn match_payee<'a>(matchers: &'a [PayeeMatcher], description: &str) -> Option<&'a PayeeMatcher> {
+    let haystack = format!(" {} ", normalize_description(description));
+
+    matchers
+        .iter()
+        .filter_map(|m| {
+            m.patterns
+                .iter()
+                .filter(|p| !p.is_empty() && haystack.contains(&format!(" {} ", p)))
+                .map(|p| p.chars().count())
+                .max()
+                .map(|len| (len, m))
+        })
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, m)| m)
+}
+
+/// Normalises a bank description for matching: uppercases it, splits on
+/// punctuation and drops tokens containing digits (reference numbers, store ids),
+/// so "AMZN MKTP US*2K3" becomes "AMZN MKTP US".
+pub fn normalize_description(description: &str) -> String {
+    description
+        .to_uppercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty() && !token.chars().any(|c| c.is_ascii_digit()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn validate_alias(alias: &str, field: &str) -> Result<(), AppError> {
+    let alias = alias.trim();
+    // Counted in characters, like the VARCHAR(100) column
+    if alias.is_empty() || alias.chars().count() > 100 {
+        return Err(AppError::field(field, "Alias must be between 1 and 100 characters"));
+    }
+    if normalize_description(alias).is_empty() {
+        return Err(AppError::field(field, format!("Alias '{}' has no matchable words", alias)));
+    }
+    Ok(())
+}
+
+async fn verify_category(pool: &PgPool, user_id: i32, category_id: Option<i32>) -> Result<(), AppError> {
+    if let Some(category_id) = category_id {
+        let category_exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1 AND user_can_view($2, user_id, household_id))"
+        )
+        .bind(category_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+        if !category_exists {
+            return Err(AppError::field("default_category_id", "Category not found or you don't have permission to use it"));
+        }
+    }
+    Ok(())
+}
+
+async fn fetch_aliases(pool: &PgPool, user_id: i32) -> Result<HashMap<i32, Vec<PayeeAliasResponse>>, AppError> {
+    let rows = sqlx::query(
+        "SELECT pa.payee_id, pa.id, pa.alias
+         FROM payee_aliases pa
+         JOIN payees p ON pa.payee_id = p.id
+         WHERE p.user_id = $1
+         ORDER BY pa.id"
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let mut aliases: HashMap<i32, Vec<PayeeAliasResponse>> = HashMap::new();
+    for row in rows {
+        aliases.entry(row.get(0)).or_default().push(PayeeAliasResponse {
+            id: row.get(1),
+            alias: row.get(2),
+        });
+    }
+    Ok(aliases)
+}
+
+async fn fetch_payee(pool: &PgPool, user_id: i32, payee_id: i32) -> Result<PayeeResponse, AppError> {
+    let row = sqlx::query(
+        "SELECT id, name, default_category_id, created_at FROM payees WHERE id = $1 AND user_id = $2"
+    )
+    .bind(payee_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let mut aliases = fetch_aliases(pool, user_id).await?;
+
+    Ok(PayeeResponse {
+        id: row.get(0),
+        name: row.get(1),
+        default_category_id: row.get(2),
+        aliases: aliases.remove(&payee_id).unwrap_or_default(),
+        created_at: row.get(3),
+    })
+}
+
+fn map_unique_violation(e: sqlx::Error) -> AppError {
+    if let sqlx::Error::Database(db_err) = &e
+        && db_err.constraint() == Some("payees_user_id_name_key")
+    {
+        return AppError::Conflict("Payee already exists".to_string());
+    }
+    AppError::InternalServerError(format!("Database error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(payee_id: i32, patterns: &[&str]) -> PayeeMatcher {
+        PayeeMatcher {
+            payee_id,
+            default_category_id: None,
+            patterns: patterns.iter().map(|p| normalize_description(p)).collect(),
+        }
+    }
+
+    fn matched(matchers: &[PayeeMatcher], description: &str) -> Option<i32> {
+        match_payee(matchers, description).map(|m| m.payee_id)
+    }
+
+    #[test]
+    fn normalizing_drops_punctuation_and_tokens_with_digits() {
+        assert_eq!(normalize_description("AMZN Mktp US*2K3"), "AMZN MKTP US");
+        assert_eq!(normalize_description("  starbucks #1234 -- seattle, wa "), "STARBUCKS SEATTLE WA");
+        assert_eq!(normalize_description("POS 0423 7-ELEVEN 32019"), "POS ELEVEN");
+        assert_eq!(normalize_description("Café Müller"), "CAFÉ MÜLLER");
+        assert_eq!(normalize_description("12345 / 678"), "");
+    }
+
+    #[test]
+    fn patterns_match_whole_words_only() {
+        let matchers = [matcher(1, &["Shell"])];
+        assert_eq!(matched(&matchers, "SHELL OIL 57442"), Some(1));
+        assert_eq!(matched(&matchers, "shell"), Some(1));
+        assert_eq!(matched(&matchers, "SHELLFISH SHACK"), None);
+        assert_eq!(matched(&matchers, "SEASHELL GIFTS"), None);
+    }
+
+    #[test]
+    fn the_longest_matching_alias_wins() {
+        let matchers = [matcher(1, &["Amazon", "AMZN"]), matcher(2, &["Amazon Prime", "AMZN Mktp"])];
+        assert_eq!(matched(&matchers, "AMZN Mktp US*2K3"), Some(2));
+        assert_eq!(matched(&matchers, "AMZN Digital 123"), Some(1));
+        assert_eq!(matched(&matchers, "Amazon Prime*MK1"), Some(2));
+        assert_eq!(matched(&matchers, "Amazon.com"), Some(1));
+        assert_eq!(matched(&matchers, "Walmart"), None);
+    }
+
+    #[test]
+    fn aliases_are_limited_by_characters_not_bytes() {
+        assert!(validate_alias(&"é".repeat(100), "alias").is_ok());
+        assert!(validate_alias(&"é".repeat(101), "alias").is_err());
+        assert!(validate_alias("   ", "alias").is_err());
+        assert!(validate_alias("#1234", "alias").is_err());
+    }
+}
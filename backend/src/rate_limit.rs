@@ -0,0 +1,199 @@
+use axum::http::HeaderMap;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::config::LoginConfig;
+
+/// Buckets are pruned once the map grows beyond this many addresses.
+const MAX_TRACKED_IPS: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// In-memory token bucket per client IP for the login endpoint. State is per process,
+/// which is enough to slow down a single attacker; the per-username lockout in the
+/// database covers distributed attempts.
+pub struct LoginRateLimiter {
+    burst: f64,
+    per_minute: f64,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl LoginRateLimiter {
+    pub fn new(config: &LoginConfig) -> Self {
+        Self {
+            burst: config.ip_burst as f64,
+            per_minute: config.ip_per_minute as f64,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes one token for `ip`, or returns how long to wait until one is available.
+    pub fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        self.check_at(ip, Instant::now())
+    }
+
+    fn check_at(&self, ip: IpAddr, now: Instant) -> Result<(), Duration> {
+        let (burst, refill_per_sec) = (self.burst, self.per_minute / 60.0);
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() > MAX_TRACKED_IPS {
+            buckets.retain(|_, b| b.tokens + now.duration_since(b.updated).as_secs_f64() * refill_per_sec < burst);
+        }
+
+        let bucket = buckets.entry(ip).or_insert(Bucket { tokens: burst, updated: now });
+        bucket.tokens = (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * refill_per_sec).min(burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / refill_per_sec))
+        }
+    }
+}
+
+/// Lockout length after `failures` consecutive failed logins, if any. The first lockout
+/// lasts one minute and doubles with every further failure, up to `max_lockout_minutes`.
+pub fn lockout_minutes(config: &LoginConfig, failures: i32) -> Option<i64> {
+    let over = failures - config.lockout_threshold as i32;
+    if over < 0 {
+        return None;
+    }
+    Some((1i64 << over.min(40)).min(config.max_lockout_minutes as i64))
+}
+
+/// The address a login attempt is charged to. `X-Forwarded-For` is only believed when the
+/// connection comes from one of `trusted_proxies`, and is read from the right: the nearest
+/// hop that isn't a trusted proxy is the client, anything further left is whatever the
+/// client chose to send.
+pub fn client_key(headers: &HeaderMap, addr: &SocketAddr, trusted_proxies: &[IpAddr]) -> IpAddr {
+    let peer = addr.ip().to_canonical();
+    if !trusted_proxies.contains(&peer) {
+        return peer;
+    }
+
+    let hops = headers.get_all("X-Forwarded-For").iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .collect::<Vec<_>>();
+    for hop in hops.into_iter().rev() {
+        match hop.trim().parse::<IpAddr>().map(|ip| ip.to_canonical()) {
+            Ok(ip) if trusted_proxies.contains(&ip) => continue,
+            Ok(ip) => return ip,
+            Err(_) => break,
+        }
+    }
+    peer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forwarded(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Forwarded-For", value.parse().unwrap());
+        headers
+    }
+
+    fn addr(ip: &str) -> SocketAddr {
+        SocketAddr::new(ip.parse().unwrap(), 40000)
+    }
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    fn limiter() -> LoginRateLimiter {
+        LoginRateLimiter::new(&LoginConfig::default())
+    }
+
+    #[test]
+    fn bucket_allows_a_burst_then_refills_over_time() {
+        let limiter = limiter();
+        let start = Instant::now();
+        for _ in 0..10 {
+            assert!(limiter.check_at(ip("198.51.100.1"), start).is_ok());
+        }
+        // One token refills every 6 seconds at 10 per minute
+        assert_eq!(limiter.check_at(ip("198.51.100.1"), start), Err(Duration::from_secs(6)));
+        // Other addresses have their own bucket
+        assert!(limiter.check_at(ip("198.51.100.2"), start).is_ok());
+
+        assert!(limiter.check_at(ip("198.51.100.1"), start + Duration::from_secs(3)).is_err());
+        assert!(limiter.check_at(ip("198.51.100.1"), start + Duration::from_secs(6)).is_ok());
+        assert!(limiter.check_at(ip("198.51.100.1"), start + Duration::from_secs(6)).is_err());
+    }
+
+    #[test]
+    fn refill_is_capped_at_the_burst() {
+        let limiter = limiter();
+        let start = Instant::now();
+        assert!(limiter.check_at(ip("198.51.100.1"), start).is_ok());
+
+        let later = start + Duration::from_secs(3600);
+        for _ in 0..10 {
+            assert!(limiter.check_at(ip("198.51.100.1"), later).is_ok());
+        }
+        assert!(limiter.check_at(ip("198.51.100.1"), later).is_err());
+    }
+
+    #[test]
+    fn limits_come_from_the_config() {
+        let limiter = LoginRateLimiter::new(&LoginConfig { ip_burst: 2, ip_per_minute: 60, ..LoginConfig::default() });
+        let start = Instant::now();
+        assert!(limiter.check_at(ip("198.51.100.1"), start).is_ok());
+        assert!(limiter.check_at(ip("198.51.100.1"), start).is_ok());
+        assert_eq!(limiter.check_at(ip("198.51.100.1"), start), Err(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn lockout_starts_at_the_threshold_and_doubles_up_to_the_cap() {
+        let config = LoginConfig::default();
+        assert_eq!(lockout_minutes(&config, 0), None);
+        assert_eq!(lockout_minutes(&config, 4), None);
+        assert_eq!(lockout_minutes(&config, 5), Some(1));
+        assert_eq!(lockout_minutes(&config, 6), Some(2));
+        assert_eq!(lockout_minutes(&config, 10), Some(32));
+        assert_eq!(lockout_minutes(&config, 11), Some(60));
+        assert_eq!(lockout_minutes(&config, 1000), Some(60));
+
+        let config = LoginConfig { lockout_threshold: 3, max_lockout_minutes: 24 * 60, ..LoginConfig::default() };
+        assert_eq!(lockout_minutes(&config, 2), None);
+        assert_eq!(lockout_minutes(&config, 3), Some(1));
+        assert_eq!(lockout_minutes(&config, 13), Some(1024));
+        assert_eq!(lockout_minutes(&config, 14), Some(24 * 60));
+        assert_eq!(lockout_minutes(&config, i32::MAX), Some(24 * 60));
+    }
+
+    #[test]
+    fn forwarded_for_is_ignored_from_untrusted_peers() {
+        let headers = forwarded("203.0.113.7");
+        assert_eq!(client_key(&headers, &addr("198.51.100.1"), &[]), ip("198.51.100.1"));
+        assert_eq!(client_key(&headers, &addr("198.51.100.1"), &[ip("10.0.0.1")]), ip("198.51.100.1"));
+    }
+
+    #[test]
+    fn forwarded_for_is_read_from_the_right_behind_trusted_proxies() {
+        let trusted = [ip("10.0.0.1"), ip("10.0.0.2")];
+        // The client prepended a spoofed hop; the proxies appended the real one
+        let headers = forwarded("1.2.3.4, 203.0.113.7, 10.0.0.2");
+        assert_eq!(client_key(&headers, &addr("10.0.0.1"), &trusted), ip("203.0.113.7"));
+        assert_eq!(client_key(&HeaderMap::new(), &addr("10.0.0.1"), &trusted), ip("10.0.0.1"));
+        assert_eq!(client_key(&forwarded("garbage"), &addr("10.0.0.1"), &trusted), ip("10.0.0.1"));
+    }
+
+    #[test]
+    fn mapped_ipv4_peers_match_trusted_ipv4_proxies() {
+        let headers = forwarded("203.0.113.7");
+        assert_eq!(client_key(&headers, &addr("::ffff:10.0.0.1"), &[ip("10.0.0.1")]), ip("203.0.113.7"));
+    }
+}
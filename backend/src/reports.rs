@@ -0,0 +1,413 @@
+use axum::{
+    extract::{State, Query},
+    Json,
+};
+use sqlx::{PgPool, Row};
+use chrono::{Datelike, Duration, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+
+use crate::models::{
+    PayeeSpendingResponse, CashflowReportResponse, CashflowPeriodResponse,
+    CategoryTotalResponse, NetWorthReportResponse, NetWorthPointResponse,
+    ReportRangeQuery, TimeSeriesQuery,
+};
+use crate::auth::AuthUser;
+use crate::error::AppError;
+use crate::api_tokens::Scope;
+
+// Longest range a time-series report will generate periods for
+const MAX_REPORT_DAYS: i64 = 366 * 10;
+
+/// Expense totals per payee; transactions without a payee are grouped under `null`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/by-payee",
+    tag = "reports",
+    params(ReportRangeQuery),
+    responses((status = 200, body = Vec<PayeeSpendingResponse>)),
+)]
+pub async fn spending_by_payee(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Query(params): Query<ReportRangeQuery>,
+) -> Result<Json<Vec<PayeeSpendingResponse>>, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let from = parse_optional_date(&params.from, "from")?;
+    let to = parse_optional_date(&params.to, "to")?;
+
+    let rows = sqlx::query(
+        "SELECT t.payee_id, p.name, SUM(ABS(t.amount)), COUNT(*)
+         FROM transactions t
+         JOIN accounts a ON t.account_id = a.id
+         LEFT JOIN payees p ON t.payee_id = p.id
+         WHERE user_can_view($1, a.user_id, a.household_id) AND t.type = 'expense'
+         AND ($2::date IS NULL OR t.date >= $2)
+         AND ($3::date IS NULL OR t.date <= $3)
+         GROUP BY t.payee_id, p.name
+         ORDER BY SUM(ABS(t.amount)) DESC"
+    )
+    .bind(auth.user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let report: Vec<PayeeSpendingResponse> = rows
+        .into_iter()
+        .map(|row| PayeeSpendingResponse {
+            payee_id: row.get(0),
+            payee_name: row.get(1),
+            total_spent: row.get(2),
+            transaction_count: row.get(3),
+        })
+        .collect();
+
+    Ok(Json(report))
+}
+
+/// Income, expense, net and savings rate per month or week. Transfers move money
+/// between the user's own accounts, so they are excluded. Intervals without
+/// transactions are reported as zero so the series has no gaps.
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/cashflow",
+    tag = "reports",
+    params(TimeSeriesQuery),
+    responses((status = 200, body = CashflowReportResponse)),
+)]
+pub async fn cashflow(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Query(params): Query<TimeSeriesQuery>,
+) -> Result<Json<CashflowReportResponse>, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let interval = parse_interval(params.interval.as_deref())?;
+    let (from, to) = resolve_range(&params.from, &params.to, interval)?;
+
+    let rows = sqlx::query(
+        "SELECT s.period::date,
+                COALESCE(SUM(t.amount) FILTER (WHERE t.type = 'income'), 0.00),
+                COALESCE(SUM(-t.amount) FILTER (WHERE t.type = 'expense'), 0.00)
+         FROM generate_series(DATE_TRUNC($4, $2::timestamp), $3::timestamp, ('1 ' || $4)::interval) AS s(period)
+         LEFT JOIN (
+             SELECT t.amount, t.type, t.date FROM transactions t
+             JOIN accounts a ON t.account_id = a.id
+             WHERE user_can_view($1, a.user_id, a.household_id)
+             AND t.type <> 'transfer'
+             AND t.date >= $2 AND t.date <= $3
+         ) t ON DATE_TRUNC($4, t.date::timestamp) = s.period
+         GROUP BY s.period
+         ORDER BY s.period"
+    )
+    .bind(auth.user_id)
+    .bind(from)
+    .bind(to)
+    .bind(interval)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let mut total_income = Decimal::ZERO;
+    let mut total_expense = Decimal::ZERO;
+    let mut periods = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        let income = row.get::<Decimal, _>(1);
+        let expense = row.get::<Decimal, _>(2);
+        total_income += income;
+        total_expense += expense;
+
+        periods.push(CashflowPeriodResponse {
+            period_start: row.get(0),
+            income,
+            expense,
+            net: income - expense,
+            savings_rate: savings_rate(income, expense),
+        });
+    }
+
+    Ok(Json(CashflowReportResponse {
+        from,
+        to,
+        interval: interval.to_string(),
+        total_income,
+        total_expense,
+        net: total_income - total_expense,
+        savings_rate: savings_rate(total_income, total_expense),
+        periods,
+    }))
+}
+
+/// Income and expense per category, where each category's totals include all
+/// of its descendants (rolled up through `parent_id`). Uncategorised
+/// transactions are reported with a `null` category. Transfers are excluded.
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/by-category",
+    tag = "reports",
+    params(ReportRangeQuery),
+    responses((status = 200, body = Vec<CategoryTotalResponse>)),
+)]
+pub async fn by_category(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Query(params): Query<ReportRangeQuery>,
+) -> Result<Json<Vec<CategoryTotalResponse>>, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let from = parse_optional_date(&params.from, "from")?;
+    let to = parse_optional_date(&params.to, "to")?;
+
+    // tree maps every category to itself and to each of its ancestors
+    let rows = sqlx::query(
+        "WITH RECURSIVE tree AS (
+             SELECT id AS category_id, id AS ancestor_id FROM categories WHERE user_can_view($1, user_id, household_id)
+             UNION ALL
+             SELECT c.id, tree.ancestor_id FROM categories c JOIN tree ON c.parent_id = tree.category_id
+         ),
+         filtered AS (
+             SELECT t.category_id, t.amount, t.type FROM transactions t
+             JOIN accounts acc ON t.account_id = acc.id
+             WHERE user_can_view($1, acc.user_id, acc.household_id) AND t.type <> 'transfer'
+             AND ($2::date IS NULL OR t.date >= $2)
+             AND ($3::date IS NULL OR t.date <= $3)
+         )
+         SELECT a.id, a.name, a.parent_id,
+                COALESCE(SUM(f.amount) FILTER (WHERE f.type = 'income'), 0.00),
+                COALESCE(SUM(-f.amount) FILTER (WHERE f.type = 'expense'), 0.00),
+                COUNT(f.amount)
+         FROM categories a
+         JOIN tree ON tree.ancestor_id = a.id
+         LEFT JOIN filtered f ON f.category_id = tree.category_id
+         WHERE user_can_view($1, a.user_id, a.household_id)
+         GROUP BY a.id, a.name, a.parent_id
+         UNION ALL
+         SELECT NULL, NULL, NULL,
+                COALESCE(SUM(amount) FILTER (WHERE type = 'income'), 0.00),
+                COALESCE(SUM(-amount) FILTER (WHERE type = 'expense'), 0.00),
+                COUNT(*)
+         FROM filtered WHERE category_id IS NULL"
+    )
+    .bind(auth.user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let mut totals = Vec::with_capacity(rows.len());
+    for row in rows {
+        let category_id: Option<i32> = row.get(0);
+        let transaction_count: i64 = row.get(5);
+        // Skip the uncategorised bucket when there is nothing in it
+        if category_id.is_none() && transaction_count == 0 {
+            continue;
+        }
+        let income = row.get::<Decimal, _>(3);
+        let expense = row.get::<Decimal, _>(4);
+        totals.push((expense, income, CategoryTotalResponse {
+            category_id,
+            category_name: row.get(1),
+            parent_id: row.get(2),
+            income,
+            expense,
+            transaction_count,
+        }));
+    }
+
+    // Biggest spending first, then biggest income
+    totals.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)));
+
+    Ok(Json(totals.into_iter().map(|(_, _, total)| total).collect()))
+}
+
+/// Net worth at the end of each interval, reconstructed from the transaction
+/// ledger rather than the current balances. Balances keep their sign, so money owed
+/// on a liability account counts against net worth and an overpaid one counts for it.
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/net-worth",
+    tag = "reports",
+    params(TimeSeriesQuery),
+    responses((status = 200, body = NetWorthReportResponse)),
+)]
+pub async fn net_worth(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Query(params): Query<TimeSeriesQuery>,
+) -> Result<Json<NetWorthReportResponse>, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let interval = parse_interval(params.interval.as_deref())?;
+    let (from, to) = resolve_range(&params.from, &params.to, interval)?;
+
+    // The last interval ends at `to`, not at the end of its month/week
+    let rows = sqlx::query(
+        "WITH periods AS (
+             SELECT LEAST((s.period + ('1 ' || $4)::interval - INTERVAL '1 day')::date, $3) AS period_end
+             FROM generate_series(DATE_TRUNC($4, $2::timestamp), $3::timestamp, ('1 ' || $4)::interval) AS s(period)
+         ),
+         balances AS (
+             SELECT p.period_end, a.is_liability, COALESCE(SUM(t.amount), 0) AS balance
+             FROM periods p
+             CROSS JOIN accounts a
+             LEFT JOIN transactions t ON t.account_id = a.id AND t.date <= p.period_end
+             WHERE user_can_view($1, a.user_id, a.household_id)
+             GROUP BY p.period_end, a.id, a.is_liability
+         )
+         SELECT p.period_end, b.is_liability, SUM(b.balance)
+         FROM periods p
+         LEFT JOIN balances b ON b.period_end = p.period_end
+         GROUP BY p.period_end, b.is_liability
+         ORDER BY p.period_end"
+    )
+    .bind(auth.user_id)
+    .bind(from)
+    .bind(to)
+    .bind(interval)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    // Up to two rows per period, one for asset and one for liability accounts
+    let mut points: Vec<NetWorthPointResponse> = Vec::new();
+    for row in rows {
+        let date: NaiveDate = row.get(0);
+        if points.last().map(|p| p.date) != Some(date) {
+            points.push(NetWorthPointResponse {
+                date,
+                assets: Decimal::ZERO,
+                liabilities: Decimal::ZERO,
+                net_worth: Decimal::ZERO,
+            });
+        }
+        if let (Some(is_liability), Some(balance), Some(point)) =
+            (row.get::<Option<bool>, _>(1), row.get::<Option<Decimal>, _>(2), points.last_mut())
+        {
+            add_balance(point, balance, is_liability);
+        }
+    }
+
+    Ok(Json(NetWorthReportResponse {
+        from,
+        to,
+        interval: interval.to_string(),
+        points,
+    }))
+}
+
+/// Adds an account balance to a net worth point. Balances are signed as recorded, with
+/// money owed on a card or loan negative, so a liability's debt is its negated balance.
+/// An overpaid liability therefore lowers `liabilities` instead of adding to it.
+fn add_balance(point: &mut NetWorthPointResponse, balance: Decimal, is_liability: bool) {
+    if is_liability {
+        point.liabilities -= balance;
+    } else {
+        point.assets += balance;
+    }
+    point.net_worth = point.assets - point.liabilities;
+}
+
+fn parse_interval(value: Option<&str>) -> Result<&'static str, AppError> {
+    match value.unwrap_or("month") {
+        "month" => Ok("month"),
+        "week" => Ok("week"),
+        _ => Err(AppError::field("interval", "Invalid interval. Use 'month' or 'week'")),
+    }
+}
+
+/// Defaults to the last twelve intervals ending today.
+fn resolve_range(
+    from: &Option<String>,
+    to: &Option<String>,
+    interval: &str,
+) -> Result<(NaiveDate, NaiveDate), AppError> {
+    let to = parse_optional_date(to, "to")?.unwrap_or_else(|| Utc::now().date_naive());
+    let from = match parse_optional_date(from, "from")? {
+        Some(date) => date,
+        None if interval == "week" => {
+            to - Duration::days(to.weekday().num_days_from_monday() as i64) - Duration::weeks(11)
+        }
+        None => {
+            let month_start = to.with_day(1).unwrap_or(to);
+            month_start.checked_sub_months(Months::new(11)).unwrap_or(month_start)
+        }
+    };
+
+    if from > to {
+        return Err(AppError::BadRequest("'from' must not be after 'to'".to_string()));
+    }
+    if (to - from).num_days() > MAX_REPORT_DAYS {
+        return Err(AppError::BadRequest("Report range cannot exceed 10 years".to_string()));
+    }
+
+    Ok((from, to))
+}
+
+/// Share of income left after expenses, as a percentage with two decimals.
+fn savings_rate(income: Decimal, expense: Decimal) -> Option<Decimal> {
+    if income <= Decimal::ZERO {
+        return None;
+    }
+    let rate = (income - expense) / income * Decimal::ONE_HUNDRED;
+    Some(rate.round_dp(2))
+}
+
+fn parse_optional_date(value: &Option<String>, field: &str) -> Result<Option<NaiveDate>, AppError> {
+    match value {
+        Some(s) => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| AppError::field(field, format!("Invalid {} format. Use YYYY-MM-DD", field))),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn point() -> NetWorthPointResponse {
+        NetWorthPointResponse {
+            date: NaiveDate::from_ymd_opt(2026, 1, 31).unwrap(),
+            assets: Decimal::ZERO,
+            liabilities: Decimal::ZERO,
+            net_worth: Decimal::ZERO,
+        }
+    }
+
+    #[test]
+    fn liabilities_count_against_net_worth() {
+        let mut p = point();
+        add_balance(&mut p, amount("1000.00"), false);
+        add_balance(&mut p, amount("-250.00"), true);
+        assert_eq!(p.assets, amount("1000.00"));
+        assert_eq!(p.liabilities, amount("250.00"));
+        assert_eq!(p.net_worth, amount("750.00"));
+    }
+
+    #[test]
+    fn overpaid_liability_adds_to_net_worth() {
+        let mut p = point();
+        add_balance(&mut p, amount("1000.00"), false);
+        add_balance(&mut p, amount("-250.00"), true);
+        // A second card paid off by more than was spent on it
+        add_balance(&mut p, amount("40.00"), true);
+        assert_eq!(p.liabilities, amount("210.00"));
+        assert_eq!(p.net_worth, amount("790.00"));
+    }
+
+    #[test]
+    fn overdrawn_asset_lowers_net_worth() {
+        let mut p = point();
+        add_balance(&mut p, amount("-30.00"), false);
+        assert_eq!(p.assets, amount("-30.00"));
+        assert_eq!(p.net_worth, amount("-30.00"));
+    }
+}
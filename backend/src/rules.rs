@@ -0,0 +1,721 @@
+use axum::{
+    extract::{State, Path},
+    Json,
+};
+use sqlx::{PgPool, Row};
+use regex::{Regex, RegexBuilder};
+use rust_decimal::Decimal;
+
+use crate::models::{
+    CreateRuleRequest, CreateRuleResponse, RuleResponse,
+    UpdateRuleRequest, UpdateRuleResponse, DeleteRuleResponse,
+    ApplyRulesRequest, ApplyRulesResponse, RuleChange, Rule,
+};
+use crate::auth::AuthUser;
+use crate::error::AppError;
+use crate::validation::ValidatedJson;
+use crate::api_tokens::Scope;
+
+const RULE_COLUMNS: &str = "id, user_id, name, priority, stop_processing, description_contains, description_regex, \
+     min_amount, max_amount, account_id, set_category_id, add_tags, set_description, created_at, updated_at";
+
+/// A rule with its regex compiled, ready to be matched against transactions.
+pub struct CompiledRule {
+    rule: Rule,
+    regex: Option<Regex>,
+}
+
+/// The fields of a transaction that rules can inspect and rewrite.
+#[derive(Debug, Clone)]
+pub struct RuleTarget {
+    pub account_id: i32,
+    pub amount: Decimal,
+    pub description: Option<String>,
+    pub category_id: Option<i32>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct RuleOutcome {
+    pub matched_rule_ids: Vec<i32>,
+    pub description: Option<String>,
+    pub category_id: Option<i32>,
+    pub tags: Vec<String>,
+}
+
+impl RuleOutcome {
+    /// The category to store: one already on the transaction wins over the rules unless
+    /// `overwrite` is set.
+    pub fn category_for(&self, existing: Option<i32>, overwrite: bool) -> Option<i32> {
+        if overwrite || existing.is_none() {
+            self.category_id.or(existing)
+        } else {
+            existing
+        }
+    }
+}
+
+/// Creates a rule that categorises, tags or renames matching new transactions.
+#[utoipa::path(
+    post,
+    path = "/api/v1/rules",
+    tag = "rules",
+    request_body = CreateRuleRequest,
+    responses((status = 200, body = CreateRuleResponse)),
+)]
+pub async fn create_rule(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<CreateRuleRequest>,
+) -> Result<Json<CreateRuleResponse>, AppError> {
+    auth.require_scope(Scope::RulesWrite)?;
+
+    validate_rule(&pool, auth.user_id, &req).await?;
+
+    let row = sqlx::query(&format!(
+        "INSERT INTO rules (user_id, name, priority, stop_processing, description_contains, description_regex,
+                            min_amount, max_amount, account_id, set_category_id, add_tags, set_description)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+         RETURNING {}",
+        RULE_COLUMNS
+    ))
+    .bind(auth.user_id)
+    .bind(req.name.as_str())
+    .bind(req.priority.unwrap_or(0))
+    .bind(req.stop_processing.unwrap_or(false))
+    .bind(non_empty(&req.description_contains))
+    .bind(non_empty(&req.description_regex))
+    .bind(req.min_amount.map(|m| m.value()))
+    .bind(req.max_amount.map(|m| m.value()))
+    .bind(req.account_id)
+    .bind(req.set_category_id)
+    .bind(clean_tags(req.add_tags.as_deref().unwrap_or_default()))
+    .bind(non_empty(&req.set_description))
+    .fetch_one(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(Json(CreateRuleResponse {
+        message: "Rule created successfully".to_string(),
+        rule: rule_to_response(row_to_rule(&row)),
+    }))
+}
+
+/// Lists the user's rules in execution order.
+#[utoipa::path(
+    get,
+    path = "/api/v1/rules",
+    tag = "rules",
+    responses((status = 200, body = Vec<RuleResponse>)),
+)]
+pub async fn get_rules(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+) -> Result<Json<Vec<RuleResponse>>, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM rules WHERE user_id = $1 ORDER BY priority, id",
+        RULE_COLUMNS
+    ))
+    .bind(auth.user_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let rules_response: Vec<RuleResponse> = rows
+        .iter()
+        .map(|row| rule_to_response(row_to_rule(row)))
+        .collect();
+
+    Ok(Json(rules_response))
+}
+
+/// Replaces a rule's conditions and actions.
+#[utoipa::path(
+    put,
+    path = "/api/v1/rules/{id}",
+    tag = "rules",
+    params(("id" = i32, Path, description = "Rule id")),
+    request_body = UpdateRuleRequest,
+    responses((status = 200, body = UpdateRuleResponse)),
+)]
+pub async fn update_rule(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Path(rule_id): Path<i32>,
+    ValidatedJson(req): ValidatedJson<UpdateRuleRequest>,
+) -> Result<Json<UpdateRuleResponse>, AppError> {
+    auth.require_scope(Scope::RulesWrite)?;
+
+    validate_rule(&pool, auth.user_id, &req).await?;
+
+    let row = sqlx::query(&format!(
+        "UPDATE rules SET name = $1, priority = $2, stop_processing = $3, description_contains = $4,
+                          description_regex = $5, min_amount = $6, max_amount = $7,
+                          account_id = $8, set_category_id = $9, add_tags = $10, set_description = $11,
+                          updated_at = NOW()
+         WHERE id = $12 AND user_id = $13
+         RETURNING {}",
+        RULE_COLUMNS
+    ))
+    .bind(req.name.as_str())
+    .bind(req.priority.unwrap_or(0))
+    .bind(req.stop_processing.unwrap_or(false))
+    .bind(non_empty(&req.description_contains))
+    .bind(non_empty(&req.description_regex))
+    .bind(req.min_amount.map(|m| m.value()))
+    .bind(req.max_amount.map(|m| m.value()))
+    .bind(req.account_id)
+    .bind(req.set_category_id)
+    .bind(clean_tags(req.add_tags.as_deref().unwrap_or_default()))
+    .bind(non_empty(&req.set_description))
+    .bind(rule_id)
+    .bind(auth.user_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let row = match row {
+        Some(r) => r,
+        None => return Err(AppError::NotFound("Rule not found or you don't have permission to update it".to_string())),
+    };
+
+    Ok(Json(UpdateRuleResponse {
+        message: "Rule updated successfully".to_string(),
+        rule: rule_to_response(row_to_rule(&row)),
+    }))
+}
+
+/// Deletes a rule.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/rules/{id}",
+    tag = "rules",
+    params(("id" = i32, Path, description = "Rule id")),
+    responses((status = 200, body = DeleteRuleResponse)),
+)]
+pub async fn delete_rule(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    Path(rule_id): Path<i32>,
+) -> Result<Json<DeleteRuleResponse>, AppError> {
+    auth.require_scope(Scope::RulesWrite)?;
+
+    let result = sqlx::query(
+        "DELETE FROM rules WHERE id = $1 AND user_id = $2"
+    )
+    .bind(rule_id)
+    .bind(auth.user_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound("Rule not found or you don't have permission to delete it".to_string()));
+    }
+
+    Ok(Json(DeleteRuleResponse {
+        message: "Rule deleted successfully".to_string(),
+    }))
+}
+
+/// Re-runs the rules over existing transactions. With `dry_run` the changes are
+/// only reported; otherwise they are written in a single database transaction.
+#[utoipa::path(
+    post,
+    path = "/api/v1/rules/apply",
+    tag = "rules",
+    request_body = ApplyRulesRequest,
+    responses((status = 200, body = ApplyRulesResponse)),
+)]
+pub async fn apply_rules_to_existing(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<ApplyRulesRequest>,
+) -> Result<Json<ApplyRulesResponse>, AppError> {
+    auth.require_scope(Scope::RulesWrite)?;
+    auth.require_scope(Scope::TransactionsWrite)?;
+
+    let dry_run = req.dry_run.unwrap_or(false);
+    let overwrite_categories = req.overwrite_categories.unwrap_or(false);
+
+    let start_date = req.start_date.map(|d| d.value());
+    let end_date = req.end_date.map(|d| d.value());
+
+    let rules = load_rules(&pool, auth.user_id).await?;
+
+    // Transfers are never categorised, so they are left out
+    let rows = sqlx::query(
+        "SELECT t.id, t.account_id, t.amount, t.description, t.category_id, t.tags
+         FROM transactions t
+         JOIN accounts a ON t.account_id = a.id
+         WHERE user_can_edit($1, a.user_id, a.household_id) AND t.type <> 'transfer'
+         AND ($2::int IS NULL OR t.account_id = $2)
+         AND ($3::date IS NULL OR t.date >= $3)
+         AND ($4::date IS NULL OR t.date <= $4)
+         ORDER BY t.date, t.id"
+    )
+    .bind(auth.user_id)
+    .bind(req.account_id)
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let mut changes = Vec::new();
+
+    for row in rows {
+        let target = RuleTarget {
+            account_id: row.get(1),
+            amount: row.get(2),
+            description: row.get(3),
+            category_id: row.get(4),
+            tags: row.get(5),
+        };
+
+        let outcome = apply_rules(&rules, &target);
+        let category_id_after = outcome.category_for(target.category_id, overwrite_categories);
+
+        if outcome.description == target.description
+            && category_id_after == target.category_id
+            && outcome.tags == target.tags
+        {
+            continue;
+        }
+
+        changes.push(RuleChange {
+            transaction_id: row.get(0),
+            matched_rule_ids: outcome.matched_rule_ids,
+            description_before: target.description,
+            description_after: outcome.description,
+            category_id_before: target.category_id,
+            category_id_after,
+            tags_before: target.tags,
+            tags_after: outcome.tags,
+        });
+    }
+
+    if !dry_run && !changes.is_empty() {
+        let mut tx = pool.begin().await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+        for change in &changes {
+            sqlx::query(
+                "UPDATE transactions t SET description = $1, category_id = $2, tags = $3
+                 FROM accounts a
+                 WHERE t.id = $4 AND a.id = t.account_id AND user_can_edit($5, a.user_id, a.household_id)"
+            )
+            .bind(&change.description_after)
+            .bind(change.category_id_after)
+            .bind(&change.tags_after)
+            .bind(change.transaction_id)
+            .bind(auth.user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+        }
+
+        tx.commit().await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    }
+
+    let message = if dry_run {
+        format!("{} transaction(s) would be updated", changes.len())
+    } else {
+        format!("{} transaction(s) updated", changes.len())
+    };
+
+    Ok(Json(ApplyRulesResponse {
+        message,
+        dry_run,
+        changed: changes.len(),
+        changes,
+    }))
+}
+
+/// Loads a user's rules in execution order.
+pub async fn load_rules(pool: &PgPool, user_id: i32) -> Result<Vec<CompiledRule>, AppError> {
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM rules WHERE user_id = $1 ORDER BY priority, id",
+        RULE_COLUMNS
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let mut rules = Vec::with_capacity(rows.len());
+    for row in rows {
+        let rule = row_to_rule(&row);
+        // Patterns are validated on save, so a failure here means the row was edited by hand
+        let regex = match rule.description_regex.as_deref().map(compile_regex) {
+            Some(Ok(re)) => Some(re),
+            Some(Err(e)) => {
+                tracing::warn!("Skipping rule {} with invalid regex: {}", rule.id, e);
+                continue;
+            }
+            None => None,
+        };
+        rules.push(CompiledRule {
+            regex,
+            rule,
+        });
+    }
+
+    Ok(rules)
+}
+
+/// Runs the rules in order against a transaction. Each matching rule sets the
+/// category and description (later rules win) and adds its tags; a matching rule
+/// with `stop_processing` ends the run.
+pub fn apply_rules(rules: &[CompiledRule], target: &RuleTarget) -> RuleOutcome {
+    let mut outcome = RuleOutcome {
+        matched_rule_ids: Vec::new(),
+        description: target.description.clone(),
+        category_id: None,
+        tags: target.tags.clone(),
+    };
+
+    for compiled in rules {
+        if !rule_matches(compiled, target, outcome.description.as_deref()) {
+            continue;
+        }
+        let rule = &compiled.rule;
+        outcome.matched_rule_ids.push(rule.id);
+
+        if let Some(category_id) = rule.set_category_id {
+            outcome.category_id = Some(category_id);
+        }
+
+        if let Some(template) = &rule.set_description {
+            let current = outcome.description.clone().unwrap_or_default();
+            let new_description = match compiled.regex.as_ref().and_then(|re| re.captures(&current)) {
+                Some(caps) => {
+                    let mut expanded = String::new();
+                    caps.expand(template, &mut expanded);
+                    expanded
+                }
+                None => template.clone(),
+            };
+            outcome.description = Some(new_description);
+        }
+
+        for tag in &rule.add_tags {
+            if !outcome.tags.contains(tag) {
+                outcome.tags.push(tag.clone());
+            }
+        }
+
+        if rule.stop_processing {
+            break;
+        }
+    }
+
+    outcome
+}
+
+fn rule_matches(compiled: &CompiledRule, target: &RuleTarget, description: Option<&str>) -> bool {
+    let rule = &compiled.rule;
+    let description = description.unwrap_or("");
+
+    if let Some(account_id) = rule.account_id
+        && account_id != target.account_id
+    {
+        return false;
+    }
+    if let Some(min) = compiled.rule.min_amount
+        && target.amount < min
+    {
+        return false;
+    }
+    if let Some(max) = compiled.rule.max_amount
+        && target.amount > max
+    {
+        return false;
+    }
+    if let Some(needle) = &rule.description_contains
+        && !description.to_lowercase().contains(&needle.to_lowercase())
+    {
+        return false;
+    }
+    if let Some(re) = &compiled.regex
+        && !re.is_match(description)
+    {
+        return false;
+    }
+
+    true
+}
+
+async fn validate_rule(pool: &PgPool, user_id: i32, req: &CreateRuleRequest) -> Result<(), AppError> {
+    let has_condition = non_empty(&req.description_contains).is_some()
+        || non_empty(&req.description_regex).is_some()
+        || req.min_amount.is_some()
+        || req.max_amount.is_some()
+        || req.account_id.is_some();
+    if !has_condition {
+        return Err(AppError::BadRequest("Rule must have at least one condition".to_string()));
+    }
+
+    let has_action = req.set_category_id.is_some()
+        || !clean_tags(req.add_tags.as_deref().unwrap_or_default()).is_empty()
+        || non_empty(&req.set_description).is_some();
+    if !has_action {
+        return Err(AppError::BadRequest("Rule must set a category, tags or description".to_string()));
+    }
+
+    if let Some(pattern) = non_empty(&req.description_regex) {
+        compile_regex(pattern)
+            .map_err(|e| AppError::field("description_regex", format!("Invalid description_regex: {}", e)))?;
+    }
+
+    if let (Some(min), Some(max)) = (req.min_amount, req.max_amount)
+        && min > max
+    {
+        return Err(AppError::field("min_amount", "min_amount cannot be greater than max_amount"));
+    }
+
+    // Referenced account and category must be usable by the user, personally or through a household
+    if let Some(account_id) = req.account_id {
+        let account_exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM accounts WHERE id = $1 AND user_can_edit($2, user_id, household_id))"
+        )
+        .bind(account_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+        if !account_exists {
+            return Err(AppError::field("account_id", "Account not found or you don't have permission to use it"));
+        }
+    }
+
+    if let Some(category_id) = req.set_category_id {
+        let category_exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1 AND user_can_view($2, user_id, household_id))"
+        )
+        .bind(category_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+        if !category_exists {
+            return Err(AppError::field("set_category_id", "Category not found or you don't have permission to use it"));
+        }
+    }
+
+    Ok(())
+}
+
+fn compile_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .size_limit(1 << 20)
+        .build()
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
+
+fn clean_tags(tags: &[String]) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !cleaned.iter().any(|t| t == tag) {
+            cleaned.push(tag.to_string());
+        }
+    }
+    cleaned
+}
+
+fn row_to_rule(row: &sqlx::postgres::PgRow) -> Rule {
+    Rule {
+        id: row.get(0),
+        user_id: row.get(1),
+        name: row.get(2),
+        priority: row.get(3),
+        stop_processing: row.get(4),
+        description_contains: row.get(5),
+        description_regex: row.get(6),
+        min_amount: row.get(7),
+        max_amount: row.get(8),
+        account_id: row.get(9),
+        set_category_id: row.get(10),
+        add_tags: row.get(11),
+        set_description: row.get(12),
+        created_at: row.get(13),
+        updated_at: row.get(14),
+    }
+}
+
+fn rule_to_response(rule: Rule) -> RuleResponse {
+    RuleResponse {
+        id: rule.id,
+        name: rule.name,
+        priority: rule.priority,
+        stop_processing: rule.stop_processing,
+        description_contains: rule.description_contains,
+        description_regex: rule.description_regex,
+        min_amount: rule.min_amount,
+        max_amount: rule.max_amount,
+        account_id: rule.account_id,
+        set_category_id: rule.set_category_id,
+        add_tags: rule.add_tags,
+        set_description: rule.set_description,
+        created_at: rule.created_at,
+        updated_at: rule.updated_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: i32) -> Rule {
+        Rule {
+            id,
+            user_id: 1,
+            name: format!("Rule {}", id),
+            priority: 0,
+            stop_processing: false,
+            description_contains: None,
+            description_regex: None,
+            min_amount: None,
+            max_amount: None,
+            account_id: None,
+            set_category_id: None,
+            add_tags: Vec::new(),
+            set_description: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    fn compile(rules: Vec<Rule>) -> Vec<CompiledRule> {
+        rules
+            .into_iter()
+            .map(|rule| CompiledRule { regex: rule.description_regex.as_deref().map(|p| compile_regex(p).unwrap()), rule })
+            .collect()
+    }
+
+    fn target(amount: &str, description: &str) -> RuleTarget {
+        RuleTarget {
+            account_id: 1,
+            amount: amount.parse().unwrap(),
+            description: Some(description.to_string()),
+            category_id: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn rules_run_in_order_and_stop_processing_ends_the_run() {
+        let rules = |stop: bool| {
+            let mut groceries = rule(1);
+            groceries.description_contains = Some("market".to_string());
+            groceries.set_category_id = Some(10);
+            let mut farmers = rule(2);
+            farmers.description_contains = Some("farmers".to_string());
+            farmers.set_category_id = Some(20);
+            farmers.stop_processing = stop;
+            let mut fallback = rule(3);
+            fallback.min_amount = Some("-1000".parse().unwrap());
+            fallback.set_category_id = Some(30);
+            compile(vec![groceries, farmers, fallback])
+        };
+
+        // Later matches overwrite earlier ones
+        let outcome = apply_rules(&rules(false), &target("-12", "Farmers Market"));
+        assert_eq!(outcome.matched_rule_ids, vec![1, 2, 3]);
+        assert_eq!(outcome.category_id, Some(30));
+
+        // A matching stop_processing rule keeps its result
+        let outcome = apply_rules(&rules(true), &target("-12", "Farmers Market"));
+        assert_eq!(outcome.matched_rule_ids, vec![1, 2]);
+        assert_eq!(outcome.category_id, Some(20));
+
+        // A stop_processing rule that doesn't match stops nothing
+        let outcome = apply_rules(&rules(true), &target("-12", "Night Market"));
+        assert_eq!(outcome.matched_rule_ids, vec![1, 3]);
+        assert_eq!(outcome.category_id, Some(30));
+    }
+
+    #[test]
+    fn an_existing_category_beats_the_rules() {
+        let mut r = rule(1);
+        r.description_contains = Some("coffee".to_string());
+        r.set_category_id = Some(10);
+        let outcome = apply_rules(&compile(vec![r]), &target("-4", "Coffee"));
+
+        assert_eq!(outcome.category_for(None, false), Some(10));
+        assert_eq!(outcome.category_for(Some(5), false), Some(5));
+        assert_eq!(outcome.category_for(Some(5), true), Some(10));
+
+        let unmatched = apply_rules(&[], &target("-4", "Coffee"));
+        assert_eq!(unmatched.category_for(Some(5), true), Some(5));
+    }
+
+    #[test]
+    fn amount_bounds_are_inclusive() {
+        let mut r = rule(1);
+        r.min_amount = Some("-50".parse().unwrap());
+        r.max_amount = Some("-10".parse().unwrap());
+        r.set_category_id = Some(10);
+        let rules = compile(vec![r]);
+
+        for (amount, matches) in [("-50", true), ("-10", true), ("-25.5", true), ("-50.01", false), ("-9.99", false), ("30", false)] {
+            assert_eq!(apply_rules(&rules, &target(amount, "")).category_id.is_some(), matches, "{}", amount);
+        }
+    }
+
+    #[test]
+    fn description_matching_ignores_case() {
+        let mut contains = rule(1);
+        contains.description_contains = Some("NETFLIX".to_string());
+        contains.add_tags = vec!["streaming".to_string()];
+        let mut regex = rule(2);
+        regex.description_regex = Some(r"^amzn mktp (\w+)".to_string());
+        regex.set_description = Some("Amazon $1".to_string());
+        let rules = compile(vec![contains, regex]);
+
+        assert_eq!(apply_rules(&rules, &target("-15", "netflix.com")).tags, vec!["streaming"]);
+        assert!(apply_rules(&rules, &target("-15", "Hulu")).matched_rule_ids.is_empty());
+
+        let outcome = apply_rules(&rules, &target("-30", "AMZN Mktp US*1234"));
+        assert_eq!(outcome.matched_rule_ids, vec![2]);
+        assert_eq!(outcome.description.as_deref(), Some("Amazon US"));
+    }
+
+    #[test]
+    fn tags_are_merged_without_duplicates() {
+        let mut first = rule(1);
+        first.min_amount = Some("-100".parse().unwrap());
+        first.add_tags = vec!["food".to_string(), "weekly".to_string()];
+        let mut second = rule(2);
+        second.max_amount = Some("0".parse().unwrap());
+        second.add_tags = vec!["weekly".to_string(), "budget".to_string()];
+
+        let mut t = target("-20", "Groceries");
+        t.tags = vec!["food".to_string()];
+        let outcome = apply_rules(&compile(vec![first, second]), &t);
+        assert_eq!(outcome.tags, vec!["food", "weekly", "budget"]);
+    }
+
+    #[test]
+    fn account_condition_limits_the_rule_to_one_account() {
+        let mut r = rule(1);
+        r.account_id = Some(2);
+        r.set_category_id = Some(10);
+        let rules = compile(vec![r]);
+
+        assert_eq!(apply_rules(&rules, &target("-5", "")).category_id, None);
+        let mut t = target("-5", "");
+        t.account_id = 2;
+        assert_eq!(apply_rules(&rules, &t).category_id, Some(10));
+    }
+}
@@ -0,0 +1,73 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::auth::AuthUser;
+
+/// Entries beyond this trigger a sweep of stale ones, so the map can't grow without bound.
+const SWEEP_THRESHOLD: usize = 10_000;
+
+struct CachedAuth {
+    user: AuthUser,
+    cached_at: Instant,
+    expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Short-lived in-memory cache of authenticated tokens, keyed by token hash, that saves the
+/// database lookup on bursts of requests. Disabled unless `auth.session_cache_ttl_secs` is set.
+///
+/// Handlers that revoke credentials call [`SessionCache::invalidate_user`]; with several
+/// backend instances a revoked token can stay usable on the others for up to the TTL.
+pub struct SessionCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, CachedAuth>>,
+}
+
+impl SessionCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, entries: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    pub fn get(&self, token_hash: &str) -> Option<AuthUser> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(token_hash) {
+            Some(entry) if entry.cached_at.elapsed() < self.ttl && entry.expires_at > chrono::Utc::now() => {
+                Some(entry.user.clone())
+            }
+            Some(_) => {
+                entries.remove(token_hash);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, token_hash: String, user: AuthUser, expires_at: chrono::DateTime<chrono::Utc>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= SWEEP_THRESHOLD {
+            let ttl = self.ttl;
+            entries.retain(|_, entry| entry.cached_at.elapsed() < ttl);
+        }
+        entries.insert(token_hash, CachedAuth { user, cached_at: Instant::now(), expires_at });
+    }
+
+    /// Forgets every cached token of the user, so the next request re-checks the database.
+    pub fn invalidate_user(&self, user_id: i32) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.entries.lock().unwrap().retain(|_, entry| entry.user.user_id != user_id);
+    }
+}
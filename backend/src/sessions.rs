@@ -0,0 +1,144 @@
+use std::sync::Arc;
+use axum::{
+    extract::{State, Path},
+    Json,
+};
+use sqlx::{PgPool, Row};
+
+use crate::models::{SessionResponse, RevokeSessionResponse, LogoutAllResponse};
+use crate::auth::{self, AuthUser};
+use crate::error::AppError;
+use crate::session_cache::SessionCache;
+
+/// Lists the devices the user is signed in on. A login stays listed while its access token
+/// is valid or it still holds an unused refresh token, so idle devices don't disappear.
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/sessions",
+    tag = "sessions",
+    responses((status = 200, body = Vec<SessionResponse>)),
+)]
+pub async fn get_sessions(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+) -> Result<Json<Vec<SessionResponse>>, AppError> {
+    let session_id = auth.require_session()?;
+
+    let rows = sqlx::query(
+        "SELECT s.id, s.created_at, s.expires_at, s.last_used_at, s.user_agent, s.ip_address, s.id = $2
+         FROM sessions s
+         WHERE s.user_id = $1
+           AND s.is_valid = TRUE
+           AND (s.expires_at > NOW() OR EXISTS (
+               SELECT 1 FROM refresh_tokens r
+               WHERE r.family_id = s.family_id AND r.revoked = FALSE AND r.used_at IS NULL AND r.expires_at > NOW()
+           ))
+         ORDER BY COALESCE(s.last_used_at, s.created_at) DESC"
+    )
+    .bind(auth.user_id)
+    .bind(session_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let sessions = rows
+        .into_iter()
+        .map(|row| SessionResponse {
+            id: row.get(0),
+            created_at: row.get(1),
+            expires_at: row.get(2),
+            last_used_at: row.get(3),
+            user_agent: row.get(4),
+            ip_address: row.get(5),
+            current: row.get(6),
+        })
+        .collect();
+
+    Ok(Json(sessions))
+}
+
+/// Signs out one device, including any refresh tokens issued to it.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/auth/sessions/{id}",
+    tag = "sessions",
+    params(("id" = i32, Path, description = "Session id")),
+    responses((status = 200, body = RevokeSessionResponse)),
+)]
+pub async fn revoke_session(
+    State(pool): State<PgPool>,
+    State(cache): State<Arc<SessionCache>>,
+    auth: AuthUser,
+    Path(session_id): Path<i32>,
+) -> Result<Json<RevokeSessionResponse>, AppError> {
+    auth.require_session()?;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let family_id = sqlx::query_scalar::<_, Option<String>>(
+        "UPDATE sessions SET is_valid = FALSE WHERE id = $1 AND user_id = $2 RETURNING family_id"
+    )
+    .bind(session_id)
+    .bind(auth.user_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?
+    .ok_or_else(|| AppError::NotFound("Session not found or you don't have permission to revoke it".to_string()))?;
+
+    if let Some(family_id) = family_id {
+        auth::revoke_family(&mut tx, &family_id).await?;
+    }
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    cache.invalidate_user(auth.user_id);
+
+    Ok(Json(RevokeSessionResponse {
+        message: "Session revoked successfully".to_string(),
+    }))
+}
+
+/// Signs out every device, including the one making the request.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/logout-all",
+    tag = "sessions",
+    responses((status = 200, body = LogoutAllResponse)),
+)]
+pub async fn logout_all(
+    State(pool): State<PgPool>,
+    State(cache): State<Arc<SessionCache>>,
+    auth: AuthUser,
+) -> Result<Json<LogoutAllResponse>, AppError> {
+    auth.require_session()?;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let revoked = sqlx::query(
+        "UPDATE sessions SET is_valid = FALSE WHERE user_id = $1 AND is_valid = TRUE"
+    )
+    .bind(auth.user_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?
+    .rows_affected();
+
+    sqlx::query("UPDATE refresh_tokens SET revoked = TRUE WHERE user_id = $1 AND revoked = FALSE")
+        .bind(auth.user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    cache.invalidate_user(auth.user_id);
+
+    Ok(Json(LogoutAllResponse {
+        message: "Logged out of all sessions".to_string(),
+        revoked_sessions: revoked as i64,
+    }))
+}
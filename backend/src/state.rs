@@ -0,0 +1,49 @@
+use std::sync::Arc;
+use axum::extract::FromRef;
+use sqlx::PgPool;
+
+use crate::config::Config;
+use crate::password_policy::PasswordPolicy;
+use crate::rate_limit::LoginRateLimiter;
+use crate::session_cache::SessionCache;
+
+/// Shared router state. Handlers keep extracting `State<PgPool>`; the other parts are
+/// available the same way through `FromRef`.
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: PgPool,
+    pub config: Arc<Config>,
+    pub login_limiter: Arc<LoginRateLimiter>,
+    pub password_policy: Arc<PasswordPolicy>,
+    pub session_cache: Arc<SessionCache>,
+}
+
+impl FromRef<AppState> for PgPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Config> {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<LoginRateLimiter> {
+    fn from_ref(state: &AppState) -> Self {
+        state.login_limiter.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<PasswordPolicy> {
+    fn from_ref(state: &AppState) -> Self {
+        state.password_policy.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<SessionCache> {
+    fn from_ref(state: &AppState) -> Self {
+        state.session_cache.clone()
+    }
+}
@@ -0,0 +1,257 @@
+//! HTTPS termination: loading the certificate, picking up renewed files without a restart,
+//! and the optional plain-HTTP listener that sends clients over to HTTPS.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::{
+    extract::State,
+    http::{header, uri::Authority, HeaderMap, StatusCode, Uri},
+    response::{IntoResponse, Redirect, Response},
+    Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use rustls::{
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    ServerConfig,
+};
+use sha2::{Digest, Sha256};
+
+/// Reads a PEM certificate chain and private key into a rustls config that offers HTTP/2
+/// and HTTP/1.1. Errors name the file at fault.
+pub fn load_server_config(cert_file: &Path, key_file: &Path) -> Result<ServerConfig, String> {
+    let certs = CertificateDer::pem_file_iter(cert_file)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Failed to read certificate {}: {}", cert_file.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("No certificates found in {}", cert_file.display()));
+    }
+    let key = PrivateKeyDer::from_pem_file(key_file)
+        .map_err(|e| format!("Failed to read private key {}: {}", key_file.display(), e))?;
+
+    let mut config = ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("Invalid certificate {} or key {}: {}", cert_file.display(), key_file.display(), e))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(config)
+}
+
+/// A hash of the file's contents. Modification times aren't enough: on filesystems with
+/// one-second resolution a renewal written soon after the previous one looks unchanged.
+fn fingerprint(path: &Path) -> Option<[u8; 32]> {
+    fs::read(path).ok().map(|contents| Sha256::digest(contents).into())
+}
+
+/// Checks the certificate and key for changes every `interval` and swaps them into `rustls`,
+/// so renewals (e.g. from certbot) apply to new connections without a restart. A pair that
+/// fails to load is logged and the current certificate stays in use.
+pub fn spawn_reload(rustls: RustlsConfig, cert_file: PathBuf, key_file: PathBuf, interval: Duration) {
+    tokio::spawn(async move {
+        let mut seen = (fingerprint(&cert_file), fingerprint(&key_file));
+        let mut interval = tokio::time::interval(interval);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let current = (fingerprint(&cert_file), fingerprint(&key_file));
+            if current == seen {
+                continue;
+            }
+            // Remembered even on failure, so a half-written renewal is retried once the
+            // other file changes rather than logged every tick
+            seen = current;
+            match load_server_config(&cert_file, &key_file) {
+                Ok(config) => {
+                    rustls.reload_from_config(Arc::new(config));
+                    tracing::info!("Reloaded TLS certificate from {}", cert_file.display());
+                }
+                Err(e) => tracing::error!("TLS certificate reload failed, keeping the current one: {}", e),
+            }
+        }
+    });
+}
+
+/// Answers every request with a permanent redirect to the same host and path over HTTPS
+/// on `https_port`. 308 rather than 301 so clients repeat a POST as a POST.
+pub fn redirect_router(https_port: u16) -> Router {
+    Router::new().fallback(redirect_to_https).with_state(https_port)
+}
+
+async fn redirect_to_https(State(https_port): State<u16>, headers: HeaderMap, uri: Uri) -> Response {
+    let Some(authority) = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.parse::<Authority>().ok())
+    else {
+        return (StatusCode::BAD_REQUEST, "Missing or invalid Host header").into_response();
+    };
+
+    let path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+    let location = if https_port == 443 {
+        format!("https://{}{}", authority.host(), path)
+    } else {
+        format!("https://{}:{}{}", authority.host(), https_port, path)
+    };
+    Redirect::permanent(&location).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{body::Body, http::Request, routing::get};
+    use rcgen::CertifiedKey;
+    use rustls::{pki_types::ServerName, ClientConfig, RootCertStore};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpStream,
+    };
+    use tokio_rustls::{client::TlsStream, TlsConnector};
+    use tower::ServiceExt;
+
+    /// A fresh self-signed certificate for `localhost`, written to `dir` as cert.pem and key.pem.
+    fn write_cert(dir: &Path) -> CertificateDer<'static> {
+        let CertifiedKey { cert, key_pair } = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        fs::write(dir.join("cert.pem"), cert.pem()).unwrap();
+        fs::write(dir.join("key.pem"), key_pair.serialize_pem()).unwrap();
+        cert.der().clone()
+    }
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("backend-tls-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Serves a one-route app over HTTPS on a free local port.
+    fn serve(rustls: RustlsConfig) -> u16 {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let app = Router::new().route("/", get(|| async { "ok" }));
+        tokio::spawn(axum_server::from_tcp_rustls(listener, rustls).serve(app.into_make_service()));
+        port
+    }
+
+    async fn connect(port: u16, trusted: &[CertificateDer<'static>]) -> TlsStream<TcpStream> {
+        let mut roots = RootCertStore::empty();
+        for cert in trusted {
+            roots.add(cert.clone()).unwrap();
+        }
+        let config = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap()
+    }
+
+    fn peer_cert(stream: &TlsStream<TcpStream>) -> CertificateDer<'static> {
+        stream.get_ref().1.peer_certificates().unwrap()[0].clone().into_owned()
+    }
+
+    #[tokio::test]
+    async fn serves_https_with_the_configured_certificate() {
+        let dir = temp_dir();
+        let cert = write_cert(&dir);
+        let config = load_server_config(&dir.join("cert.pem"), &dir.join("key.pem")).unwrap();
+        let port = serve(RustlsConfig::from_config(Arc::new(config)));
+
+        let mut stream = connect(port, std::slice::from_ref(&cert)).await;
+        assert_eq!(peer_cert(&stream), cert);
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+        assert!(response.ends_with("ok"), "{}", response);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn picks_up_a_renewed_certificate() {
+        let dir = temp_dir();
+        let (cert_file, key_file) = (dir.join("cert.pem"), dir.join("key.pem"));
+        let old = write_cert(&dir);
+        let rustls = RustlsConfig::from_config(Arc::new(load_server_config(&cert_file, &key_file).unwrap()));
+        spawn_reload(rustls.clone(), cert_file.clone(), key_file.clone(), Duration::from_millis(20));
+        let port = serve(rustls);
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        // Renewed within the same mtime tick, as on filesystems with one-second resolution
+        let mtimes = [&cert_file, &key_file].map(|path| fs::metadata(path).unwrap().modified().unwrap());
+        let new = write_cert(&dir);
+        for (path, mtime) in [&cert_file, &key_file].into_iter().zip(mtimes) {
+            fs::File::options().write(true).open(path).unwrap().set_modified(mtime).unwrap();
+        }
+        let trusted = [old.clone(), new.clone()];
+        let mut presented = old;
+        for _ in 0..100 {
+            presented = peer_cert(&connect(port, &trusted).await);
+            if presented == new {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(presented, new);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_files_are_reported_by_path() {
+        let dir = temp_dir();
+        write_cert(&dir);
+        let (cert_file, key_file) = (dir.join("cert.pem"), dir.join("key.pem"));
+
+        fs::write(&key_file, "not a key").unwrap();
+        let error = load_server_config(&cert_file, &key_file).unwrap_err();
+        assert!(error.contains(&*key_file.to_string_lossy()), "{}", error);
+
+        fs::write(&cert_file, "").unwrap();
+        let error = load_server_config(&cert_file, &key_file).unwrap_err();
+        assert!(error.contains(&*cert_file.to_string_lossy()), "{}", error);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    async fn redirect(https_port: u16, host: Option<&str>, uri: &str) -> Response {
+        let mut request = Request::builder().uri(uri);
+        if let Some(host) = host {
+            request = request.header(header::HOST, host);
+        }
+        redirect_router(https_port).oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    fn location(response: &Response) -> &str {
+        response.headers()[header::LOCATION].to_str().unwrap()
+    }
+
+    #[tokio::test]
+    async fn redirects_to_the_https_port() {
+        let response = redirect(8443, Some("finance.example.com:8080"), "/api/v1/accounts?limit=5").await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(location(&response), "https://finance.example.com:8443/api/v1/accounts?limit=5");
+
+        let response = redirect(443, Some("finance.example.com"), "/health").await;
+        assert_eq!(location(&response), "https://finance.example.com/health");
+
+        let response = redirect(443, Some("[::1]:80"), "/").await;
+        assert_eq!(location(&response), "https://[::1]/");
+    }
+
+    #[tokio::test]
+    async fn redirect_needs_a_host() {
+        assert_eq!(redirect(443, None, "/").await.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(redirect(443, Some("bad host"), "/").await.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,123 +1,110 @@
 use axum::{
     extract::{State, Query},
-    http::HeaderMap,
     Json,
 };
 use sqlx::{PgPool, Row};
-use serde::Deserialize;
 use chrono::NaiveDate;
+use rust_decimal::Decimal;
 
 use crate::models::{
-    CreateTransactionRequest, CreateTransactionResponse, TransactionResponse,
+    CreateTransactionRequest, CreateTransactionResponse, GetTransactionsQuery, TransactionResponse,
     TransferRequest, TransferResponse,
 };
-use crate::auth::AppError;
-use crate::middleware;
-
-#[derive(Debug, Deserialize)]
-pub struct GetTransactionsQuery {
-    pub account_id: Option<i32>,
-    pub start_date: Option<String>,
-    pub end_date: Option<String>,
-}
-
+use crate::auth::AuthUser;
+use crate::error::AppError;
+use crate::validation::ValidatedJson;
+use crate::api_tokens::Scope;
+use crate::rules;
+use crate::payees;
+
+/// Records a transaction and updates the account balance. Positive amounts are income, negative ones expenses; rules and payee matching run on the new transaction.
+/// Naming an account or category instead of passing its id creates it, which API tokens need `accounts:write` or `categories:write` for.
+#[utoipa::path(
+    post,
+    path = "/api/v1/transactions",
+    tag = "transactions",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Replays the stored response when the same request is retried")),
+    request_body = CreateTransactionRequest,
+    responses((status = 200, body = CreateTransactionResponse)),
+)]
 pub async fn create_transaction(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
-    Json(req): Json<CreateTransactionRequest>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<CreateTransactionRequest>,
 ) -> Result<Json<CreateTransactionResponse>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::TransactionsWrite)?;
+    // Naming an account or category creates it, which needs the matching write scope too
+    if req.account_name.is_some() {
+        auth.require_scope(Scope::AccountsWrite)?;
+    }
+    if req.category_name.is_some() {
+        auth.require_scope(Scope::CategoriesWrite)?;
+    }
 
-    // Determine account_id
-    let account_id = match (req.account_id, req.account_name) {
+    // Determine account_id, and the household it belongs to (None for personal accounts)
+    let (account_id, household_id) = match (req.account_id, req.account_name) {
         (Some(id), None) => {
-            // Verify account belongs to user
-            let account_exists = sqlx::query_scalar::<_, bool>(
-                "SELECT EXISTS(SELECT 1 FROM accounts WHERE id = $1 AND user_id = $2)"
+            // Verify the user may add transactions to the account
+            let household_id = sqlx::query_scalar::<_, Option<i32>>(
+                "SELECT household_id FROM accounts WHERE id = $1 AND user_can_edit($2, user_id, household_id)"
             )
             .bind(id)
             .bind(auth.user_id)
-            .fetch_one(&pool)
+            .fetch_optional(&pool)
             .await
             .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
             
-            if !account_exists {
-                return Err(AppError::BadRequest("Account not found or you don't have permission to use it".to_string()));
+            match household_id {
+                Some(household_id) => (id, household_id),
+                None => return Err(AppError::field("account_id", "Account not found or you don't have permission to use it")),
             }
-            id
         }
         (None, Some(name)) => {
             // Create new account
-            if name.is_empty() || name.len() > 50 {
-                return Err(AppError::BadRequest("Account name must be between 1 and 50 characters".to_string()));
-            }
-            
-            let account_id = sqlx::query_scalar::<_, i32>(
+            let id = sqlx::query_scalar::<_, i32>(
                 "INSERT INTO accounts (user_id, name, type, currency) VALUES ($1, $2, $2, 'USD') RETURNING id"
             )
             .bind(auth.user_id)
-            .bind(&name)
+            .bind(name.as_str())
             .fetch_one(&pool)
             .await
             .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
-            
-            account_id
+            (id, None)
         }
         _ => return Err(AppError::BadRequest("Either account_id or account_name must be provided".to_string())),
     };
 
-    // Parse amount
-    let amount_str = req.amount.trim();
-    let amount_decimal: rust_decimal::Decimal = amount_str.parse()
-        .map_err(|_| AppError::BadRequest("Invalid amount format".to_string()))?;
+    let amount_decimal = req.amount.value();
 
     // Determine transaction type based on amount sign
-    let transaction_type = if amount_decimal > rust_decimal::Decimal::ZERO {
+    let transaction_type = if amount_decimal > Decimal::ZERO {
         "income"
-    } else if amount_decimal < rust_decimal::Decimal::ZERO {
+    } else if amount_decimal < Decimal::ZERO {
         "expense"
     } else {
-        return Err(AppError::BadRequest("Amount cannot be zero".to_string()));
+        return Err(AppError::field("amount", "Amount cannot be zero"));
     };
 
-    // Parse date
-    let date = if let Some(date_str) = req.date {
-        NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-            .map_err(|_| AppError::BadRequest("Invalid date format. Use YYYY-MM-DD".to_string()))?
-    } else {
-        chrono::Utc::now().date_naive()
-    };
+    let date = req.date.map_or_else(|| chrono::Utc::now().date_naive(), |d| d.value());
 
     // Determine category_id
     let category_id = match (req.category_id, req.category_name) {
         (Some(id), None) => {
-            // Verify category belongs to user
-            let category_exists = sqlx::query_scalar::<_, bool>(
-                "SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1 AND user_id = $2)"
-            )
-            .bind(id)
-            .bind(auth.user_id)
-            .fetch_one(&pool)
-            .await
-            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
-            
-            if !category_exists {
-                return Err(AppError::BadRequest("Category not found or you don't have permission to use it".to_string()));
+            // Verify the category is usable on this account
+            if !category_in_scope(&pool, auth.user_id, household_id, id).await? {
+                return Err(AppError::field("category_id", "Category not found or you don't have permission to use it"));
             }
             Some(id)
         }
         (None, Some(name)) => {
             // Create new category if it doesn't exist
-            if name.is_empty() || name.len() > 50 {
-                return Err(AppError::BadRequest("Category name must be between 1 and 50 characters".to_string()));
-            }
-            
-            // Check if category already exists
+            // Check if category already exists alongside the account (personal or same household)
             let existing_id = sqlx::query_scalar::<_, i32>(
-                "SELECT id FROM categories WHERE name = $1 AND user_id = $2"
+                "SELECT id FROM categories WHERE name = $1 AND household_id IS NOT DISTINCT FROM $3 AND user_can_view($2, user_id, household_id)"
             )
-            .bind(&name)
+            .bind(name.as_str())
             .bind(auth.user_id)
+            .bind(household_id)
             .fetch_optional(&pool)
             .await
             .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
@@ -127,10 +114,11 @@ pub async fn create_transaction(
             } else {
                 // Create new category
                 let new_id = sqlx::query_scalar::<_, i32>(
-                    "INSERT INTO categories (user_id, name) VALUES ($1, $2) RETURNING id"
+                    "INSERT INTO categories (user_id, name, household_id) VALUES ($1, $2, $3) RETURNING id"
                 )
                 .bind(auth.user_id)
-                .bind(&name)
+                .bind(name.as_str())
+                .bind(household_id)
                 .fetch_one(&pool)
                 .await
                 .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
@@ -142,31 +130,79 @@ pub async fn create_transaction(
         (Some(_), Some(_)) => return Err(AppError::BadRequest("Provide either category_id or category_name, not both".to_string())),
     };
 
+    // Apply auto-categorisation rules (an explicitly chosen category always wins)
+    let rules = rules::load_rules(&pool, auth.user_id).await?;
+    let outcome = rules::apply_rules(&rules, &rules::RuleTarget {
+        account_id,
+        amount: amount_decimal,
+        description: req.description,
+        category_id,
+        tags: Vec::new(),
+    });
+    // Rules and payees are personal, so their category may not exist in a household
+    let rule_category_id = match outcome.category_id {
+        Some(id) if category_in_scope(&pool, auth.user_id, household_id, id).await? => Some(id),
+        _ => None,
+    };
+    let category_id = category_id.or(rule_category_id);
+
+    // Link the payee: an explicit payee_id, otherwise the best match on the description
+    let (payee_id, payee_category_id) = if let Some(id) = req.payee_id {
+        let payee_category = sqlx::query_scalar::<_, Option<i32>>(
+            "SELECT default_category_id FROM payees WHERE id = $1 AND user_id = $2"
+        )
+        .bind(id)
+        .bind(auth.user_id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+        match payee_category {
+            Some(default_category_id) => (Some(id), default_category_id),
+            None => return Err(AppError::field("payee_id", "Payee not found or you don't have permission to use it")),
+        }
+    } else if let Some(description) = &outcome.description {
+        let matchers = payees::load_matchers(&pool, auth.user_id).await?;
+        match payees::match_payee(&matchers, description) {
+            Some(payee) => (Some(payee.payee_id), payee.default_category_id),
+            None => (None, None),
+        }
+    } else {
+        (None, None)
+    };
+    let payee_category_id = match payee_category_id {
+        Some(id) if category_in_scope(&pool, auth.user_id, household_id, id).await? => Some(id),
+        _ => None,
+    };
+    let category_id = category_id.or(payee_category_id);
+
     // Start transaction to ensure atomicity
     let mut tx = pool.begin().await
         .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
-    // Insert transaction (using amount as string and casting in SQL)
+    // Insert transaction
     let transaction_id = sqlx::query_scalar::<_, i32>(
-        "INSERT INTO transactions (user_id, account_id, category_id, amount, type, date, description) 
-         VALUES ($1, $2, $3, $4::numeric, $5, $6, $7) RETURNING id"
+        "INSERT INTO transactions (user_id, account_id, category_id, amount, type, date, description, tags, payee_id) 
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING id"
     )
     .bind(auth.user_id)
     .bind(account_id)
-    .bind(&category_id)
-    .bind(&amount_str)
+    .bind(category_id)
+    .bind(amount_decimal)
     .bind(transaction_type)
     .bind(date)
-    .bind(&req.description)
+    .bind(&outcome.description)
+    .bind(&outcome.tags)
+    .bind(payee_id)
     .fetch_one(&mut *tx)
     .await
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
-    // Update account balance (using amount as string and casting in SQL)
+    // Update account balance
     sqlx::query(
-        "UPDATE accounts SET balance = balance + $1::numeric WHERE id = $2"
+        "UPDATE accounts SET balance = balance + $1 WHERE id = $2"
     )
-    .bind(&amount_str)
+    .bind(amount_decimal)
     .bind(account_id)
     .execute(&mut *tx)
     .await
@@ -179,11 +215,12 @@ pub async fn create_transaction(
     // Fetch complete transaction data for response
     let row = sqlx::query(
         "SELECT t.id, t.user_id, t.account_id, a.name as account_name, t.category_id, c.name as category_name, 
-                t.amount::text, t.type, t.date, t.description, t.created_at
+                t.amount, t.type, t.date, t.description, t.created_at, t.tags, t.payee_id, p.name as payee_name
          FROM transactions t
          JOIN accounts a ON t.account_id = a.id
          LEFT JOIN categories c ON t.category_id = c.id
-         WHERE t.id = $1 AND t.user_id = $2"
+         LEFT JOIN payees p ON t.payee_id = p.id
+         WHERE t.id = $1 AND user_can_view($2, a.user_id, a.household_id)"
     )
     .bind(transaction_id)
     .bind(auth.user_id)
@@ -201,6 +238,9 @@ pub async fn create_transaction(
         r#type: row.get(7),
         date: row.get(8),
         description: row.get(9),
+        tags: row.get(11),
+        payee_id: row.get(12),
+        payee_name: row.get(13),
         created_at: row.get(10),
     };
 
@@ -210,24 +250,32 @@ pub async fn create_transaction(
     }))
 }
 
+/// Lists transactions, newest first, optionally for one account and date range.
+#[utoipa::path(
+    get,
+    path = "/api/v1/transactions",
+    tag = "transactions",
+    params(GetTransactionsQuery),
+    responses((status = 200, body = Vec<TransactionResponse>)),
+)]
 pub async fn get_transactions(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
+    auth: AuthUser,
     Query(params): Query<GetTransactionsQuery>,
 ) -> Result<Json<Vec<TransactionResponse>>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::Read)?;
 
     // Parse dates if provided
     let start_date = if let Some(start_str) = &params.start_date {
         Some(NaiveDate::parse_from_str(start_str, "%Y-%m-%d")
-            .map_err(|_| AppError::BadRequest("Invalid start_date format. Use YYYY-MM-DD".to_string()))?)
+            .map_err(|_| AppError::field("start_date", "Invalid start_date format. Use YYYY-MM-DD"))?)
     } else {
         None
     };
 
     let end_date = if let Some(end_str) = &params.end_date {
         Some(NaiveDate::parse_from_str(end_str, "%Y-%m-%d")
-            .map_err(|_| AppError::BadRequest("Invalid end_date format. Use YYYY-MM-DD".to_string()))?)
+            .map_err(|_| AppError::field("end_date", "Invalid end_date format. Use YYYY-MM-DD"))?)
     } else {
         None
     };
@@ -237,11 +285,12 @@ pub async fn get_transactions(
         (Some(acc_id), Some(start), Some(end)) => {
             sqlx::query(
                 "SELECT t.id, t.user_id, t.account_id, a.name as account_name, t.category_id, c.name as category_name, 
-                        t.amount::text, t.type, t.date, t.description, t.created_at
+                        t.amount, t.type, t.date, t.description, t.created_at, t.tags, t.payee_id, p.name as payee_name
                  FROM transactions t
                  JOIN accounts a ON t.account_id = a.id
                  LEFT JOIN categories c ON t.category_id = c.id
-                 WHERE t.user_id = $1 AND t.account_id = $2 AND t.date >= $3 AND t.date <= $4
+                 LEFT JOIN payees p ON t.payee_id = p.id
+                 WHERE user_can_view($1, a.user_id, a.household_id) AND t.account_id = $2 AND t.date >= $3 AND t.date <= $4
                  ORDER BY t.date DESC, t.created_at DESC"
             )
             .bind(auth.user_id)
@@ -254,11 +303,12 @@ pub async fn get_transactions(
         (Some(acc_id), Some(start), None) => {
             sqlx::query(
                 "SELECT t.id, t.user_id, t.account_id, a.name as account_name, t.category_id, c.name as category_name, 
-                        t.amount::text, t.type, t.date, t.description, t.created_at
+                        t.amount, t.type, t.date, t.description, t.created_at, t.tags, t.payee_id, p.name as payee_name
                  FROM transactions t
                  JOIN accounts a ON t.account_id = a.id
                  LEFT JOIN categories c ON t.category_id = c.id
-                 WHERE t.user_id = $1 AND t.account_id = $2 AND t.date >= $3
+                 LEFT JOIN payees p ON t.payee_id = p.id
+                 WHERE user_can_view($1, a.user_id, a.household_id) AND t.account_id = $2 AND t.date >= $3
                  ORDER BY t.date DESC, t.created_at DESC"
             )
             .bind(auth.user_id)
@@ -270,11 +320,12 @@ pub async fn get_transactions(
         (Some(acc_id), None, Some(end)) => {
             sqlx::query(
                 "SELECT t.id, t.user_id, t.account_id, a.name as account_name, t.category_id, c.name as category_name, 
-                        t.amount::text, t.type, t.date, t.description, t.created_at
+                        t.amount, t.type, t.date, t.description, t.created_at, t.tags, t.payee_id, p.name as payee_name
                  FROM transactions t
                  JOIN accounts a ON t.account_id = a.id
                  LEFT JOIN categories c ON t.category_id = c.id
-                 WHERE t.user_id = $1 AND t.account_id = $2 AND t.date <= $3
+                 LEFT JOIN payees p ON t.payee_id = p.id
+                 WHERE user_can_view($1, a.user_id, a.household_id) AND t.account_id = $2 AND t.date <= $3
                  ORDER BY t.date DESC, t.created_at DESC"
             )
             .bind(auth.user_id)
@@ -286,11 +337,12 @@ pub async fn get_transactions(
         (Some(acc_id), None, None) => {
             sqlx::query(
                 "SELECT t.id, t.user_id, t.account_id, a.name as account_name, t.category_id, c.name as category_name, 
-                        t.amount::text, t.type, t.date, t.description, t.created_at
+                        t.amount, t.type, t.date, t.description, t.created_at, t.tags, t.payee_id, p.name as payee_name
                  FROM transactions t
                  JOIN accounts a ON t.account_id = a.id
                  LEFT JOIN categories c ON t.category_id = c.id
-                 WHERE t.user_id = $1 AND t.account_id = $2
+                 LEFT JOIN payees p ON t.payee_id = p.id
+                 WHERE user_can_view($1, a.user_id, a.household_id) AND t.account_id = $2
                  ORDER BY t.date DESC, t.created_at DESC"
             )
             .bind(auth.user_id)
@@ -301,11 +353,12 @@ pub async fn get_transactions(
         (None, Some(start), Some(end)) => {
             sqlx::query(
                 "SELECT t.id, t.user_id, t.account_id, a.name as account_name, t.category_id, c.name as category_name, 
-                        t.amount::text, t.type, t.date, t.description, t.created_at
+                        t.amount, t.type, t.date, t.description, t.created_at, t.tags, t.payee_id, p.name as payee_name
                  FROM transactions t
                  JOIN accounts a ON t.account_id = a.id
                  LEFT JOIN categories c ON t.category_id = c.id
-                 WHERE t.user_id = $1 AND t.date >= $2 AND t.date <= $3
+                 LEFT JOIN payees p ON t.payee_id = p.id
+                 WHERE user_can_view($1, a.user_id, a.household_id) AND t.date >= $2 AND t.date <= $3
                  ORDER BY t.date DESC, t.created_at DESC"
             )
             .bind(auth.user_id)
@@ -317,11 +370,12 @@ pub async fn get_transactions(
         (None, Some(start), None) => {
             sqlx::query(
                 "SELECT t.id, t.user_id, t.account_id, a.name as account_name, t.category_id, c.name as category_name, 
-                        t.amount::text, t.type, t.date, t.description, t.created_at
+                        t.amount, t.type, t.date, t.description, t.created_at, t.tags, t.payee_id, p.name as payee_name
                  FROM transactions t
                  JOIN accounts a ON t.account_id = a.id
                  LEFT JOIN categories c ON t.category_id = c.id
-                 WHERE t.user_id = $1 AND t.date >= $2
+                 LEFT JOIN payees p ON t.payee_id = p.id
+                 WHERE user_can_view($1, a.user_id, a.household_id) AND t.date >= $2
                  ORDER BY t.date DESC, t.created_at DESC"
             )
             .bind(auth.user_id)
@@ -332,11 +386,12 @@ pub async fn get_transactions(
         (None, None, Some(end)) => {
             sqlx::query(
                 "SELECT t.id, t.user_id, t.account_id, a.name as account_name, t.category_id, c.name as category_name, 
-                        t.amount::text, t.type, t.date, t.description, t.created_at
+                        t.amount, t.type, t.date, t.description, t.created_at, t.tags, t.payee_id, p.name as payee_name
                  FROM transactions t
                  JOIN accounts a ON t.account_id = a.id
                  LEFT JOIN categories c ON t.category_id = c.id
-                 WHERE t.user_id = $1 AND t.date <= $2
+                 LEFT JOIN payees p ON t.payee_id = p.id
+                 WHERE user_can_view($1, a.user_id, a.household_id) AND t.date <= $2
                  ORDER BY t.date DESC, t.created_at DESC"
             )
             .bind(auth.user_id)
@@ -347,11 +402,12 @@ pub async fn get_transactions(
         (None, None, None) => {
             sqlx::query(
                 "SELECT t.id, t.user_id, t.account_id, a.name as account_name, t.category_id, c.name as category_name, 
-                        t.amount::text, t.type, t.date, t.description, t.created_at
+                        t.amount, t.type, t.date, t.description, t.created_at, t.tags, t.payee_id, p.name as payee_name
                  FROM transactions t
                  JOIN accounts a ON t.account_id = a.id
                  LEFT JOIN categories c ON t.category_id = c.id
-                 WHERE t.user_id = $1
+                 LEFT JOIN payees p ON t.payee_id = p.id
+                 WHERE user_can_view($1, a.user_id, a.household_id)
                  ORDER BY t.date DESC, t.created_at DESC"
             )
             .bind(auth.user_id)
@@ -373,6 +429,9 @@ pub async fn get_transactions(
             r#type: row.get(7),
             date: row.get(8),
             description: row.get(9),
+            tags: row.get(11),
+            payee_id: row.get(12),
+            payee_name: row.get(13),
             created_at: row.get(10),
         })
         .collect();
@@ -380,16 +439,25 @@ pub async fn get_transactions(
     Ok(Json(transactions))
 }
 
+/// Moves money between two accounts as a pair of linked transactions.
+#[utoipa::path(
+    post,
+    path = "/api/v1/transactions/transfer",
+    tag = "transactions",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Replays the stored response when the same request is retried")),
+    request_body = TransferRequest,
+    responses((status = 200, body = TransferResponse)),
+)]
 pub async fn transfer(
     State(pool): State<PgPool>,
-    headers: HeaderMap,
-    Json(req): Json<TransferRequest>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<TransferRequest>,
 ) -> Result<Json<TransferResponse>, AppError> {
-    let auth = middleware::verify_auth(&pool, &headers).await?;
+    auth.require_scope(Scope::TransactionsWrite)?;
 
-    // Validate accounts belong to user
+    // Validate the user may move money out of and into both accounts
     let from_account_exists = sqlx::query_scalar::<_, bool>(
-        "SELECT EXISTS(SELECT 1 FROM accounts WHERE id = $1 AND user_id = $2)"
+        "SELECT EXISTS(SELECT 1 FROM accounts WHERE id = $1 AND user_can_edit($2, user_id, household_id))"
     )
     .bind(req.from_account_id)
     .bind(auth.user_id)
@@ -398,7 +466,7 @@ pub async fn transfer(
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
     let to_account_exists = sqlx::query_scalar::<_, bool>(
-        "SELECT EXISTS(SELECT 1 FROM accounts WHERE id = $1 AND user_id = $2)"
+        "SELECT EXISTS(SELECT 1 FROM accounts WHERE id = $1 AND user_can_edit($2, user_id, household_id))"
     )
     .bind(req.to_account_id)
     .bind(auth.user_id)
@@ -407,48 +475,37 @@ pub async fn transfer(
     .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
     if !from_account_exists {
-        return Err(AppError::BadRequest("From account not found or you don't have permission to use it".to_string()));
+        return Err(AppError::field("from_account_id", "From account not found or you don't have permission to use it"));
     }
 
     if !to_account_exists {
-        return Err(AppError::BadRequest("To account not found or you don't have permission to use it".to_string()));
+        return Err(AppError::field("to_account_id", "To account not found or you don't have permission to use it"));
     }
 
     if req.from_account_id == req.to_account_id {
-        return Err(AppError::BadRequest("Cannot transfer to the same account".to_string()));
+        return Err(AppError::field("to_account_id", "Cannot transfer to the same account"));
     }
 
-    // Parse amount
-    let amount_str = req.amount.trim();
-    let amount_decimal: rust_decimal::Decimal = amount_str.parse()
-        .map_err(|_| AppError::BadRequest("Invalid amount format".to_string()))?;
+    let amount_decimal = req.amount.value();
 
-    if amount_decimal <= rust_decimal::Decimal::ZERO {
-        return Err(AppError::BadRequest("Transfer amount must be positive".to_string()));
+    if amount_decimal <= Decimal::ZERO {
+        return Err(AppError::field("amount", "Transfer amount must be positive"));
     }
 
     // Check if from_account has sufficient balance
-    let from_balance: rust_decimal::Decimal = sqlx::query_scalar::<_, String>(
-        "SELECT balance::text FROM accounts WHERE id = $1"
+    let from_balance = sqlx::query_scalar::<_, Decimal>(
+        "SELECT balance FROM accounts WHERE id = $1"
     )
     .bind(req.from_account_id)
     .fetch_one(&pool)
     .await
-    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?
-    .parse()
-    .map_err(|_| AppError::InternalServerError("Failed to parse balance".to_string()))?;
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
     if from_balance < amount_decimal {
-        return Err(AppError::BadRequest(format!("Insufficient balance. Account has {}, but trying to transfer {}", from_balance, amount_decimal)));
+        return Err(AppError::field("amount", format!("Insufficient balance. Account has {}, but trying to transfer {}", from_balance, amount_decimal)));
     }
 
-    // Parse date
-    let date = if let Some(date_str) = req.date {
-        NaiveDate::parse_from_str(&date_str, "%Y-%m-%d")
-            .map_err(|_| AppError::BadRequest("Invalid date format. Use YYYY-MM-DD".to_string()))?
-    } else {
-        chrono::Utc::now().date_naive()
-    };
+    let date = req.date.map_or_else(|| chrono::Utc::now().date_naive(), |d| d.value());
 
     // Build description
     let description = req.description.or_else(|| {
@@ -460,14 +517,13 @@ pub async fn transfer(
         .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
 
     // Create transaction for from_account (negative amount)
-    let from_amount = format!("-{}", amount_str);
     let from_transaction_id = sqlx::query_scalar::<_, i32>(
         "INSERT INTO transactions (user_id, account_id, category_id, amount, type, date, description) 
-         VALUES ($1, $2, NULL, $3::numeric, 'transfer', $4, $5) RETURNING id"
+         VALUES ($1, $2, NULL, $3, 'transfer', $4, $5) RETURNING id"
     )
     .bind(auth.user_id)
     .bind(req.from_account_id)
-    .bind(&from_amount)
+    .bind(-amount_decimal)
     .bind(date)
     .bind(&description)
     .fetch_one(&mut *tx)
@@ -477,11 +533,11 @@ pub async fn transfer(
     // Create transaction for to_account (positive amount)
     let to_transaction_id = sqlx::query_scalar::<_, i32>(
         "INSERT INTO transactions (user_id, account_id, category_id, amount, type, date, description) 
-         VALUES ($1, $2, NULL, $3::numeric, 'transfer', $4, $5) RETURNING id"
+         VALUES ($1, $2, NULL, $3, 'transfer', $4, $5) RETURNING id"
     )
     .bind(auth.user_id)
     .bind(req.to_account_id)
-    .bind(&amount_str)
+    .bind(amount_decimal)
     .bind(date)
     .bind(&description)
     .fetch_one(&mut *tx)
@@ -490,9 +546,9 @@ pub async fn transfer(
 
     // Update from_account balance (subtract)
     sqlx::query(
-        "UPDATE accounts SET balance = balance - $1::numeric WHERE id = $2"
+        "UPDATE accounts SET balance = balance - $1 WHERE id = $2"
     )
-    .bind(&amount_str)
+    .bind(amount_decimal)
     .bind(req.from_account_id)
     .execute(&mut *tx)
     .await
@@ -500,9 +556,9 @@ pub async fn transfer(
 
     // Update to_account balance (add)
     sqlx::query(
-        "UPDATE accounts SET balance = balance + $1::numeric WHERE id = $2"
+        "UPDATE accounts SET balance = balance + $1 WHERE id = $2"
     )
-    .bind(&amount_str)
+    .bind(amount_decimal)
     .bind(req.to_account_id)
     .execute(&mut *tx)
     .await
@@ -515,11 +571,12 @@ pub async fn transfer(
     // Fetch complete transaction data for response
     let from_row = sqlx::query(
         "SELECT t.id, t.user_id, t.account_id, a.name as account_name, t.category_id, c.name as category_name, 
-                t.amount::text, t.type, t.date, t.description, t.created_at
+                t.amount, t.type, t.date, t.description, t.created_at, t.tags, t.payee_id, p.name as payee_name
          FROM transactions t
          JOIN accounts a ON t.account_id = a.id
          LEFT JOIN categories c ON t.category_id = c.id
-         WHERE t.id = $1 AND t.user_id = $2"
+         LEFT JOIN payees p ON t.payee_id = p.id
+         WHERE t.id = $1 AND user_can_view($2, a.user_id, a.household_id)"
     )
     .bind(from_transaction_id)
     .bind(auth.user_id)
@@ -529,11 +586,12 @@ pub async fn transfer(
 
     let to_row = sqlx::query(
         "SELECT t.id, t.user_id, t.account_id, a.name as account_name, t.category_id, c.name as category_name, 
-                t.amount::text, t.type, t.date, t.description, t.created_at
+                t.amount, t.type, t.date, t.description, t.created_at, t.tags, t.payee_id, p.name as payee_name
          FROM transactions t
          JOIN accounts a ON t.account_id = a.id
          LEFT JOIN categories c ON t.category_id = c.id
-         WHERE t.id = $1 AND t.user_id = $2"
+         LEFT JOIN payees p ON t.payee_id = p.id
+         WHERE t.id = $1 AND user_can_view($2, a.user_id, a.household_id)"
     )
     .bind(to_transaction_id)
     .bind(auth.user_id)
@@ -551,6 +609,9 @@ pub async fn transfer(
         r#type: from_row.get(7),
         date: from_row.get(8),
         description: from_row.get(9),
+        tags: from_row.get(11),
+        payee_id: from_row.get(12),
+        payee_name: from_row.get(13),
         created_at: from_row.get(10),
     };
 
@@ -564,6 +625,9 @@ pub async fn transfer(
         r#type: to_row.get(7),
         date: to_row.get(8),
         description: to_row.get(9),
+        tags: to_row.get(11),
+        payee_id: to_row.get(12),
+        payee_name: to_row.get(13),
         created_at: to_row.get(10),
     };
 
@@ -574,3 +638,21 @@ pub async fn transfer(
     }))
 }
 
+/// Whether `category_id` can be used on an account in `household_id`: a personal category
+/// of the user for personal accounts, or a category of the same household.
+async fn category_in_scope(
+    pool: &PgPool,
+    user_id: i32,
+    household_id: Option<i32>,
+    category_id: i32,
+) -> Result<bool, AppError> {
+    sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS(SELECT 1 FROM categories WHERE id = $1 AND household_id IS NOT DISTINCT FROM $3 AND user_can_view($2, user_id, household_id))"
+    )
+    .bind(category_id)
+    .bind(user_id)
+    .bind(household_id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))
+}
@@ -0,0 +1,504 @@
+use axum::{
+    extract::{ConnectInfo, State},
+    http::HeaderMap,
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Postgres, Row, Transaction};
+use std::{net::SocketAddr, sync::Arc};
+use subtle::ConstantTimeEq;
+use totp_rs::{Algorithm, Secret, TOTP};
+use uuid::Uuid;
+
+use crate::models::{
+    LoginResponse, TwoFactorChallengeResponse, TwoFactorVerifyRequest,
+    TwoFactorSetupResponse, TwoFactorConfirmRequest, TwoFactorConfirmResponse,
+    TwoFactorDisableRequest, TwoFactorDisableResponse,
+};
+use crate::auth::{self, AuthUser, ClientInfo};
+use crate::config::Config;
+use crate::error::AppError;
+use crate::validation::ValidatedJson;
+use crate::rate_limit::{self, LoginRateLimiter};
+
+const ISSUER: &str = "Personal Finance Tracker";
+const TOTP_STEP_SECS: u64 = 30;
+/// Codes from one step either side of "now" are accepted to allow for clock drift.
+const TOTP_SKEW_STEPS: i64 = 1;
+const CHALLENGE_TTL_MINUTES: i64 = 5;
+const CHALLENGE_MAX_ATTEMPTS: i32 = 5;
+const RECOVERY_CODE_COUNT: usize = 10;
+
+fn build_totp(secret_base32: &str, username: &str) -> Result<TOTP, AppError> {
+    let secret = Secret::Encoded(secret_base32.to_string())
+        .to_bytes()
+        .map_err(|e| AppError::InternalServerError(format!("Invalid TOTP secret: {}", e)))?;
+    // ':' separates issuer and account in the otpauth label
+    TOTP::new(Algorithm::SHA1, 6, 1, TOTP_STEP_SECS, secret, Some(ISSUER.to_string()), username.replace(':', "_"))
+        .map_err(|e| AppError::InternalServerError(format!("TOTP error: {}", e)))
+}
+
+/// Returns the time step `code` belongs to, if it is valid now and newer than `last_step`.
+fn check_totp(totp: &TOTP, code: &str, last_step: Option<i64>) -> Option<i64> {
+    check_totp_at(totp, code, last_step, Utc::now().timestamp())
+}
+
+fn check_totp_at(totp: &TOTP, code: &str, last_step: Option<i64>, now: i64) -> Option<i64> {
+    let current = now / TOTP_STEP_SECS as i64;
+    (current - TOTP_SKEW_STEPS..=current + TOTP_SKEW_STEPS)
+        .filter(|step| last_step.is_none_or(|last| *step > last))
+        .find(|step| bool::from(totp.generate(*step as u64 * TOTP_STEP_SECS).as_bytes().ct_eq(code.as_bytes())))
+}
+
+/// Strips the spaces and dashes people type when copying codes.
+fn normalize_code(code: &str) -> String {
+    code.chars().filter(|c| !c.is_whitespace() && *c != '-').collect::<String>().to_lowercase()
+}
+
+fn is_totp_code(code: &str) -> bool {
+    code.len() == 6 && code.chars().all(|c| c.is_ascii_digit())
+}
+
+/// What the database keeps for a recovery code, so it matches however the user types it.
+fn recovery_code_hash(code: &str) -> String {
+    auth::hash_token(&normalize_code(code))
+}
+
+/// A challenge is spent once it expires or has taken `CHALLENGE_MAX_ATTEMPTS` wrong codes.
+fn challenge_spent(expires_at: DateTime<Utc>, attempts: i32, now: DateTime<Utc>) -> bool {
+    expires_at < now || attempts >= CHALLENGE_MAX_ATTEMPTS
+}
+
+fn generate_recovery_codes() -> Vec<String> {
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let hex = Uuid::new_v4().simple().to_string();
+            format!("{}-{}-{}-{}", &hex[0..4], &hex[4..8], &hex[8..12], &hex[12..16])
+        })
+        .collect()
+}
+
+/// Accepts either a TOTP code (recording its step) or an unused recovery code (marking it used).
+async fn verify_second_factor(
+    tx: &mut Transaction<'_, Postgres>,
+    user_id: i32,
+    code: &str,
+) -> Result<bool, AppError> {
+    let code = normalize_code(code);
+
+    if is_totp_code(&code) {
+        let row = sqlx::query("SELECT username, totp_secret, totp_last_step FROM users WHERE id = $1 FOR UPDATE")
+            .bind(user_id)
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+        let username: String = row.get(0);
+        let Some(secret) = row.get::<Option<String>, _>(1) else { return Ok(false) };
+        let last_step: Option<i64> = row.get(2);
+
+        let Some(step) = check_totp(&build_totp(&secret, &username)?, &code, last_step) else {
+            return Ok(false);
+        };
+        sqlx::query("UPDATE users SET totp_last_step = $1 WHERE id = $2")
+            .bind(step)
+            .bind(user_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+        return Ok(true);
+    }
+
+    let used = sqlx::query(
+        "UPDATE recovery_codes SET used_at = NOW()
+         WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL"
+    )
+    .bind(user_id)
+    .bind(recovery_code_hash(&code))
+    .execute(&mut **tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?
+    .rows_affected();
+
+    Ok(used > 0)
+}
+
+/// Called by login after the password checked out for a user with 2FA enabled.
+pub async fn create_challenge(pool: &PgPool, user_id: i32) -> Result<TwoFactorChallengeResponse, AppError> {
+    let challenge_token = Uuid::new_v4().to_string();
+    let expires_at = Utc::now() + Duration::minutes(CHALLENGE_TTL_MINUTES);
+
+    sqlx::query("INSERT INTO login_challenges (user_id, token_hash, expires_at) VALUES ($1, $2, $3)")
+        .bind(user_id)
+        .bind(auth::hash_token(&challenge_token))
+        .bind(expires_at)
+        .execute(pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(TwoFactorChallengeResponse {
+        message: "Two-factor authentication required".to_string(),
+        two_factor_required: true,
+        challenge_token,
+        expires_at,
+    })
+}
+
+/// Second login step: exchanges a challenge plus a TOTP or recovery code for a session.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/2fa/verify",
+    tag = "auth",
+    request_body = TwoFactorVerifyRequest,
+    responses((status = 200, body = LoginResponse)),
+    security(()),
+)]
+pub async fn verify_login(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<Config>>,
+    State(limiter): State<Arc<LoginRateLimiter>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    ValidatedJson(req): ValidatedJson<TwoFactorVerifyRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    let client = ClientInfo::from_request(&headers, &addr);
+    let ip = rate_limit::client_key(&headers, &addr, &config.server.trusted_proxies);
+    if let Err(wait) = limiter.check(ip) {
+        return Err(AppError::RateLimited {
+            message: "Too many login attempts, please slow down".to_string(),
+            retry_after_secs: wait.as_secs().max(1),
+        });
+    }
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let challenge = sqlx::query(
+        "SELECT id, user_id, attempts, expires_at FROM login_challenges WHERE token_hash = $1 FOR UPDATE"
+    )
+    .bind(auth::hash_token(&req.challenge_token))
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?
+    .ok_or_else(|| AppError::Unauthorized("Login challenge is invalid or has expired, please log in again".to_string()))?;
+
+    let challenge_id: i32 = challenge.get(0);
+    let user_id: i32 = challenge.get(1);
+    let attempts: i32 = challenge.get(2);
+    let expires_at: chrono::DateTime<Utc> = challenge.get(3);
+
+    if challenge_spent(expires_at, attempts, Utc::now()) {
+        delete_challenge(&mut tx, challenge_id).await?;
+        tx.commit().await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+        return Err(AppError::Unauthorized("Login challenge is invalid or has expired, please log in again".to_string()));
+    }
+
+    let user = sqlx::query("SELECT username, locked_until FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    let username: String = user.get(0);
+    let locked_until: Option<chrono::DateTime<Utc>> = user.get(1);
+
+    if let Some(locked_until) = locked_until
+        && locked_until > Utc::now()
+    {
+        return Err(auth::account_locked(locked_until));
+    }
+
+    if !verify_second_factor(&mut tx, user_id, &req.code).await? {
+        sqlx::query("UPDATE login_challenges SET attempts = attempts + 1 WHERE id = $1")
+            .bind(challenge_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+        tx.commit().await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+        if attempts + 1 >= CHALLENGE_MAX_ATTEMPTS {
+            tracing::warn!("Two-factor challenge for user {} exhausted from {}", user_id, client.ip_address);
+        }
+        // Counts toward the per-user lockout, so fresh challenges don't mean fresh guesses
+        auth::record_failed_login(&pool, &config.login, user_id, &username, &client.ip_address).await?;
+        return Err(AppError::Unauthorized("Invalid authentication code".to_string()));
+    }
+
+    delete_challenge(&mut tx, challenge_id).await?;
+    sqlx::query("UPDATE users SET failed_login_attempts = 0, locked_until = NULL WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let response = auth::start_session(&pool, &config.auth, user_id, &client, "Login successful").await?;
+    Ok(Json(response))
+}
+
+async fn delete_challenge(tx: &mut Transaction<'_, Postgres>, challenge_id: i32) -> Result<(), AppError> {
+    sqlx::query("DELETE FROM login_challenges WHERE id = $1")
+        .bind(challenge_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    Ok(())
+}
+
+/// Starts enrolment: generates a secret and returns it with an `otpauth://` URI for
+/// authenticator apps. 2FA is only switched on once a code is confirmed.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/2fa/setup",
+    tag = "auth",
+    responses((status = 200, body = TwoFactorSetupResponse)),
+)]
+pub async fn setup(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+) -> Result<Json<TwoFactorSetupResponse>, AppError> {
+    auth.require_session()?;
+
+    let row = sqlx::query("SELECT username, totp_enabled FROM users WHERE id = $1")
+        .bind(auth.user_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    let username: String = row.get(0);
+    if row.get::<bool, _>(1) {
+        return Err(AppError::Conflict("Two-factor authentication is already enabled".to_string()));
+    }
+
+    let secret = Secret::generate_secret().to_encoded().to_string();
+    let totp = build_totp(&secret, &username)?;
+
+    sqlx::query("UPDATE users SET totp_pending_secret = $1 WHERE id = $2")
+        .bind(&secret)
+        .bind(auth.user_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(Json(TwoFactorSetupResponse {
+        message: "Scan the URI with an authenticator app, then confirm with a code".to_string(),
+        secret,
+        otpauth_uri: totp.get_url(),
+    }))
+}
+
+/// Finishes enrolment with a code from the authenticator and returns fresh recovery codes.
+/// The codes are only ever shown here; the database keeps their hashes.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/2fa/confirm",
+    tag = "auth",
+    request_body = TwoFactorConfirmRequest,
+    responses((status = 200, body = TwoFactorConfirmResponse)),
+)]
+pub async fn confirm(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<TwoFactorConfirmRequest>,
+) -> Result<Json<TwoFactorConfirmResponse>, AppError> {
+    auth.require_session()?;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let row = sqlx::query("SELECT username, totp_pending_secret FROM users WHERE id = $1 FOR UPDATE")
+        .bind(auth.user_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    let username: String = row.get(0);
+    let secret: String = row.get::<Option<String>, _>(1)
+        .ok_or_else(|| AppError::Conflict("Start two-factor setup first".to_string()))?;
+
+    let step = check_totp(&build_totp(&secret, &username)?, &normalize_code(&req.code), None)
+        .ok_or_else(|| AppError::field("code", "Invalid authentication code"))?;
+
+    sqlx::query(
+        "UPDATE users SET totp_secret = totp_pending_secret, totp_pending_secret = NULL,
+             totp_enabled = TRUE, totp_last_step = $1
+         WHERE id = $2"
+    )
+    .bind(step)
+    .bind(auth.user_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let recovery_codes = replace_recovery_codes(&mut tx, auth.user_id).await?;
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(Json(TwoFactorConfirmResponse {
+        message: "Two-factor authentication enabled. Store these recovery codes somewhere safe".to_string(),
+        recovery_codes,
+    }))
+}
+
+async fn replace_recovery_codes(tx: &mut Transaction<'_, Postgres>, user_id: i32) -> Result<Vec<String>, AppError> {
+    sqlx::query("DELETE FROM recovery_codes WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let codes = generate_recovery_codes();
+    for code in &codes {
+        sqlx::query("INSERT INTO recovery_codes (user_id, code_hash) VALUES ($1, $2)")
+            .bind(user_id)
+            .bind(recovery_code_hash(code))
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    }
+    Ok(codes)
+}
+
+/// Turns 2FA off; needs both the password and a current TOTP or recovery code.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/2fa/disable",
+    tag = "auth",
+    request_body = TwoFactorDisableRequest,
+    responses((status = 200, body = TwoFactorDisableResponse)),
+)]
+pub async fn disable(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<TwoFactorDisableRequest>,
+) -> Result<Json<TwoFactorDisableResponse>, AppError> {
+    auth.require_session()?;
+
+    let row = sqlx::query("SELECT password_hash, totp_enabled FROM users WHERE id = $1")
+        .bind(auth.user_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    if !row.get::<bool, _>(1) {
+        return Err(AppError::Conflict("Two-factor authentication is not enabled".to_string()));
+    }
+    if !auth::verify_password(&req.password, &row.get::<String, _>(0))? {
+        return Err(AppError::field("password", "Password is incorrect"));
+    }
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    if !verify_second_factor(&mut tx, auth.user_id, &req.code).await? {
+        return Err(AppError::field("code", "Invalid authentication code"));
+    }
+
+    sqlx::query(
+        "UPDATE users SET totp_enabled = FALSE, totp_secret = NULL, totp_pending_secret = NULL, totp_last_step = NULL
+         WHERE id = $1"
+    )
+    .bind(auth.user_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    sqlx::query("DELETE FROM recovery_codes WHERE user_id = $1")
+        .bind(auth.user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    Ok(Json(TwoFactorDisableResponse {
+        message: "Two-factor authentication disabled".to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP";
+    /// An arbitrary instant in the middle of a 30-second step.
+    const NOW: i64 = 1_760_000_015;
+
+    fn totp() -> TOTP {
+        build_totp(SECRET, "alice").unwrap()
+    }
+
+    fn code_at(step: i64) -> String {
+        totp().generate(step as u64 * TOTP_STEP_SECS)
+    }
+
+    fn step(now: i64) -> i64 {
+        now / TOTP_STEP_SECS as i64
+    }
+
+    #[test]
+    fn totp_accepts_one_step_of_skew_either_way() {
+        let current = step(NOW);
+        for s in [current - 1, current, current + 1] {
+            assert_eq!(check_totp_at(&totp(), &code_at(s), None, NOW), Some(s));
+        }
+        assert_eq!(check_totp_at(&totp(), &code_at(current - 2), None, NOW), None);
+        assert_eq!(check_totp_at(&totp(), &code_at(current + 2), None, NOW), None);
+    }
+
+    #[test]
+    fn totp_rejects_the_last_used_step_and_older() {
+        let current = step(NOW);
+        let code = code_at(current);
+        assert_eq!(check_totp_at(&totp(), &code, Some(current), NOW), None);
+        assert_eq!(check_totp_at(&totp(), &code_at(current - 1), Some(current - 1), NOW), None);
+        // A later code is still fine after an earlier one was used
+        assert_eq!(check_totp_at(&totp(), &code_at(current + 1), Some(current), NOW), Some(current + 1));
+        assert_eq!(check_totp_at(&totp(), &code, Some(current - 1), NOW), Some(current));
+    }
+
+    #[test]
+    fn totp_secret_must_be_base32() {
+        assert!(build_totp("not base32!", "alice").is_err());
+        // ':' would end the label's issuer part early
+        assert_eq!(build_totp(SECRET, "a:b").unwrap().account_name, "a_b");
+    }
+
+    #[test]
+    fn codes_are_normalized_before_checking() {
+        assert_eq!(normalize_code(" 123 456 "), "123456");
+        assert_eq!(normalize_code("ABCD-ef01-2345-6789"), "abcdef0123456789");
+        assert!(is_totp_code(&normalize_code("123 456")));
+        assert!(!is_totp_code("12345"));
+        assert!(!is_totp_code("1234567"));
+        assert!(!is_totp_code("12a456"));
+    }
+
+    #[test]
+    fn recovery_codes_are_unique_and_match_however_typed() {
+        let codes = generate_recovery_codes();
+        assert_eq!(codes.len(), RECOVERY_CODE_COUNT);
+        assert_eq!(codes.iter().collect::<std::collections::HashSet<_>>().len(), RECOVERY_CODE_COUNT);
+        for code in &codes {
+            assert_eq!(code.len(), 19);
+            assert_eq!(code.matches('-').count(), 3);
+            // Never mistaken for a TOTP code, so they reach the recovery check
+            assert!(!is_totp_code(&normalize_code(code)));
+        }
+
+        let code = &codes[0];
+        let stored = recovery_code_hash(code);
+        assert_eq!(recovery_code_hash(&code.to_uppercase()), stored);
+        assert_eq!(recovery_code_hash(&code.replace('-', " ")), stored);
+        assert_ne!(recovery_code_hash(&codes[1]), stored);
+    }
+
+    #[test]
+    fn challenge_is_spent_after_expiry_or_the_attempt_cap() {
+        let now = Utc::now();
+        let expires_at = now + Duration::minutes(CHALLENGE_TTL_MINUTES);
+        assert!(!challenge_spent(expires_at, 0, now));
+        assert!(!challenge_spent(expires_at, CHALLENGE_MAX_ATTEMPTS - 1, now));
+        assert!(challenge_spent(expires_at, CHALLENGE_MAX_ATTEMPTS, now));
+        assert!(challenge_spent(expires_at, 0, expires_at + Duration::seconds(1)));
+    }
+}
@@ -0,0 +1,192 @@
+use axum::{
+    extract::State,
+    Json,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::models::{
+    ChangePasswordRequest, ChangePasswordResponse, ChangeUsernameRequest,
+    ChangeUsernameResponse, DeleteMeRequest, DeleteMeResponse,
+};
+use crate::auth::{self, AuthUser};
+use crate::error::AppError;
+use crate::validation::ValidatedJson;
+use crate::session_cache::SessionCache;
+use crate::households;
+use crate::password_policy::PasswordPolicy;
+
+async fn current_password_hash(pool: &PgPool, user_id: i32) -> Result<String, AppError> {
+    sqlx::query_scalar::<_, String>("SELECT password_hash FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?
+        .ok_or_else(|| AppError::Unauthorized("User no longer exists".to_string()))
+}
+
+/// Changes the password and signs out every other device. The calling session stays valid.
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/change-password",
+    tag = "users",
+    request_body = ChangePasswordRequest,
+    responses((status = 200, body = ChangePasswordResponse)),
+)]
+pub async fn change_password(
+    State(pool): State<PgPool>,
+    State(cache): State<Arc<SessionCache>>,
+    State(policy): State<Arc<PasswordPolicy>>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<ChangePasswordRequest>,
+) -> Result<Json<ChangePasswordResponse>, AppError> {
+    let session_id = auth.require_session()?;
+
+    let password_hash = current_password_hash(&pool, auth.user_id).await?;
+    if !auth::verify_password(&req.current_password, &password_hash)? {
+        return Err(AppError::field("current_password", "Current password is incorrect"));
+    }
+    if req.new_password == req.current_password {
+        return Err(AppError::field("new_password", "New password must be different from the current password"));
+    }
+    let username = sqlx::query_scalar::<_, String>("SELECT username FROM users WHERE id = $1")
+        .bind(auth.user_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+    policy.validate(&req.new_password, &[&username])
+        .map_err(|msg| AppError::field("new_password", msg))?;
+
+    let new_hash = auth::hash_password(&req.new_password)?;
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    sqlx::query("UPDATE users SET password_hash = $1 WHERE id = $2")
+        .bind(&new_hash)
+        .bind(auth.user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let current_family = sqlx::query_scalar::<_, Option<String>>("SELECT family_id FROM sessions WHERE id = $1")
+        .bind(session_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    let revoked = sqlx::query(
+        "UPDATE sessions SET is_valid = FALSE
+         WHERE user_id = $1 AND is_valid = TRUE AND id <> $2
+           AND family_id IS DISTINCT FROM $3"
+    )
+    .bind(auth.user_id)
+    .bind(session_id)
+    .bind(&current_family)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?
+    .rows_affected();
+
+    sqlx::query(
+        "UPDATE refresh_tokens SET revoked = TRUE
+         WHERE user_id = $1 AND revoked = FALSE AND family_id IS DISTINCT FROM $2"
+    )
+    .bind(auth.user_id)
+    .bind(&current_family)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    cache.invalidate_user(auth.user_id);
+
+    Ok(Json(ChangePasswordResponse {
+        message: "Password changed successfully".to_string(),
+        revoked_sessions: revoked as i64,
+    }))
+}
+
+/// Renames the user; usernames are unique.
+#[utoipa::path(
+    put,
+    path = "/api/v1/me/username",
+    tag = "users",
+    request_body = ChangeUsernameRequest,
+    responses((status = 200, body = ChangeUsernameResponse)),
+)]
+pub async fn change_username(
+    State(pool): State<PgPool>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<ChangeUsernameRequest>,
+) -> Result<Json<ChangeUsernameResponse>, AppError> {
+    auth.require_session()?;
+
+    let username = req.username.trim().to_string();
+    auth::validate_username(&username)?;
+
+    sqlx::query("UPDATE users SET username = $1 WHERE id = $2")
+        .bind(&username)
+        .bind(auth.user_id)
+        .execute(&pool)
+        .await
+        .map_err(|e| {
+            if let sqlx::Error::Database(db_err) = &e
+                && db_err.constraint() == Some("users_username_key")
+            {
+                return AppError::Conflict("Username already exists".to_string());
+            }
+            AppError::InternalServerError(format!("Database error: {}", e))
+        })?;
+
+    Ok(Json(ChangeUsernameResponse {
+        message: "Username changed successfully".to_string(),
+        username,
+    }))
+}
+
+/// Permanently deletes the user. Every table references `users` with `ON DELETE CASCADE`,
+/// so accounts, transactions, budgets, rules, payees and sessions go with it. Shared
+/// household data stays with the remaining members.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/me",
+    tag = "users",
+    request_body = DeleteMeRequest,
+    responses((status = 200, body = DeleteMeResponse)),
+)]
+pub async fn delete_me(
+    State(pool): State<PgPool>,
+    State(cache): State<Arc<SessionCache>>,
+    auth: AuthUser,
+    ValidatedJson(req): ValidatedJson<DeleteMeRequest>,
+) -> Result<Json<DeleteMeResponse>, AppError> {
+    auth.require_session()?;
+
+    let password_hash = current_password_hash(&pool, auth.user_id).await?;
+    if !auth::verify_password(&req.password, &password_hash)? {
+        return Err(AppError::field("password", "Password is incorrect"));
+    }
+
+    let mut tx = pool.begin().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    households::leave_all_households(&mut tx, auth.user_id).await?;
+
+    sqlx::query("DELETE FROM users WHERE id = $1")
+        .bind(auth.user_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    tx.commit().await
+        .map_err(|e| AppError::InternalServerError(format!("Database error: {}", e)))?;
+
+    cache.invalidate_user(auth.user_id);
+
+    Ok(Json(DeleteMeResponse {
+        message: "Account and all data deleted".to_string(),
+    }))
+}
@@ -0,0 +1,44 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::header,
+};
+use finance_api_types::validation::{FromJsonFields, JsonFields};
+use serde_json::Value;
+
+use crate::error::AppError;
+
+/// Like `Json`, but rejects with `AppError` and lists every invalid field.
+pub struct ValidatedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: FromJsonFields,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let is_json = req.headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/json"));
+        if !is_json {
+            return Err(AppError::BadRequest("Expected a JSON body with Content-Type: application/json".to_string()));
+        }
+
+        let body = Bytes::from_request(req, state).await
+            .map_err(|e| AppError::BadRequest(format!("Could not read request body: {}", e)))?;
+        let map = match serde_json::from_slice::<Value>(&body) {
+            Ok(Value::Object(map)) => map,
+            Ok(_) => return Err(AppError::BadRequest("Request body must be a JSON object".to_string())),
+            Err(e) => return Err(AppError::BadRequest(format!("Invalid JSON: {}", e))),
+        };
+
+        let mut fields = JsonFields::new(map);
+        match T::from_json_fields(&mut fields) {
+            Some(value) => Ok(ValidatedJson(value)),
+            None => Err(AppError::Validation(fields.into_errors())),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use finance_api_types::ErrorResponse;
+use reqwest::StatusCode;
+
+/// Why a call failed.
+#[derive(Debug)]
+pub enum Error {
+    /// The server could not be reached, or the connection broke before a response arrived.
+    Network { base_url: String, source: reqwest::Error },
+    /// The server rejected the request with its usual error body. Branch on `error.code`.
+    Api {
+        status: StatusCode,
+        error: ErrorResponse,
+        /// Seconds to wait before retrying, for `429 Too Many Requests`.
+        retry_after: Option<u64>,
+    },
+    /// An error status without the usual body, e.g. from a proxy in front of the server.
+    Status { status: StatusCode, body: String },
+    /// A success response whose body didn't match the expected type.
+    Decode(reqwest::Error),
+    /// The call needs a login and the client has no tokens.
+    NotAuthenticated,
+    /// The access token was rejected and could not be refreshed; log in again.
+    SessionExpired,
+}
+
+impl Error {
+    /// The server's stable error code (`not_found`, `validation_failed`, ...), if it sent one.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Error::Api { error, .. } => Some(&error.code),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Network { base_url, source } => write!(f, "Could not reach server at {}: {}", base_url, source),
+            // Validation errors list each rejected field as `field: message`
+            Error::Api { error, retry_after, .. } => {
+                if error.fields.is_empty() {
+                    f.write_str(&error.error)?;
+                } else {
+                    let fields = error.fields.iter()
+                        .map(|e| format!("{}: {}", e.field, e.message))
+                        .collect::<Vec<_>>();
+                    f.write_str(&fields.join("; "))?;
+                }
+                match retry_after {
+                    Some(secs) => write!(f, ". Try again in {}s", secs),
+                    None => Ok(()),
+                }
+            }
+            Error::Status { status, body } if body.trim().is_empty() => write!(f, "{}", status),
+            Error::Status { body, .. } => f.write_str(body),
+            Error::Decode(e) => write!(f, "Unexpected response from server: {}", e),
+            Error::NotAuthenticated => f.write_str("Not authenticated"),
+            Error::SessionExpired => f.write_str("Session expired, please log in again"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Network { source, .. } | Error::Decode(source) => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,590 @@
+//! Async client for the Finance Tracker API (`/api/v1`), used by the TUI and usable from
+//! scripts. Requests and responses are the shared types from `finance-api-types`,
+//! re-exported as [`types`].
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), finance_api_client::Error> {
+//! use finance_api_client::{types::models::LoginRequest, ApiClient, LoginStep};
+//!
+//! let api = ApiClient::new("http://localhost:3000");
+//! let login = LoginRequest { username: "alice".into(), password: "...".into() };
+//! if let LoginStep::LoggedIn(_) = api.login(&login).await? {
+//!     for account in api.get_accounts().await? {
+//!         println!("{}: {}", account.name, account.balance);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod error;
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+use reqwest::{header, Client, RequestBuilder, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+
+pub use error::Error;
+pub use finance_api_types as types;
+
+use types::models::*;
+use types::ErrorResponse;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Prefix of every versioned endpoint.
+const API_PREFIX: &str = "/api/v1";
+
+/// Access token plus the refresh token used to renew it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthTokens {
+    pub access: String,
+    pub refresh: Option<String>,
+}
+
+/// Outcome of a password login: either signed in, or a second factor is needed for the
+/// challenge (see [`ApiClient::verify_two_factor`]).
+#[derive(Debug)]
+pub enum LoginStep {
+    LoggedIn(LoginResponse),
+    TwoFactorRequired(TwoFactorChallengeResponse),
+}
+
+type TokensListener = Arc<dyn Fn(&AuthTokens) + Send + Sync>;
+
+/// Cheap to clone; clones share the tokens.
+#[derive(Clone)]
+pub struct ApiClient {
+    http: Client,
+    base_url: String,
+    tokens: Arc<Mutex<Option<AuthTokens>>>,
+    /// Held while rotating the tokens. Refresh tokens are single-use and the server treats
+    /// a reused one as stolen, so concurrent requests must not each refresh.
+    refresh_lock: Arc<tokio::sync::Mutex<()>>,
+    on_refresh: Option<TokensListener>,
+    session_expired: Arc<AtomicBool>,
+}
+
+impl ApiClient {
+    /// `base_url` is the server root, e.g. `http://localhost:3000`. A trailing `/api` from
+    /// before the API was versioned is ignored.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into();
+        let base_url = base_url.trim_end_matches('/');
+        let base_url = base_url.strip_suffix("/api").unwrap_or(base_url);
+        Self {
+            http: Client::new(),
+            base_url: base_url.to_string(),
+            tokens: Arc::new(Mutex::new(None)),
+            refresh_lock: Arc::new(tokio::sync::Mutex::new(())),
+            on_refresh: None,
+            session_expired: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Called with the new pair whenever the client refreshes its tokens on its own. The
+    /// old refresh token is spent at that point, so callers that persist tokens should
+    /// save the new ones right away.
+    pub fn on_tokens_refreshed(mut self, listener: impl Fn(&AuthTokens) + Send + Sync + 'static) -> Self {
+        self.on_refresh = Some(Arc::new(listener));
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn tokens(&self) -> Option<AuthTokens> {
+        self.tokens.lock().unwrap().clone()
+    }
+
+    /// Uses tokens from an earlier login, e.g. ones saved to disk, or forgets them.
+    pub fn set_tokens(&self, tokens: Option<AuthTokens>) {
+        *self.tokens.lock().unwrap() = tokens;
+    }
+
+    /// True once after a request failed with [`Error::SessionExpired`], for callers that
+    /// ignore errors of background refreshes but still need to send the user to login.
+    pub fn take_session_expired(&self) -> bool {
+        self.session_expired.swap(false, Ordering::Relaxed)
+    }
+
+    fn url(&self, endpoint: &str) -> String {
+        format!("{}{}{}", self.base_url, API_PREFIX, endpoint)
+    }
+
+    async fn send(&self, req: RequestBuilder) -> Result<reqwest::Response> {
+        req.send().await.map_err(|source| Error::Network { base_url: self.base_url.clone(), source })
+    }
+
+    /// Sends an authenticated request. On 401 the refresh token is exchanged once for a new
+    /// pair and the request is retried; if that fails the session is reported as expired.
+    async fn send_auth(&self, build: impl Fn() -> RequestBuilder) -> Result<reqwest::Response> {
+        let tokens = self.tokens().ok_or(Error::NotAuthenticated)?;
+        let resp = self.send(build().bearer_auth(&tokens.access)).await?;
+        if resp.status() != StatusCode::UNAUTHORIZED {
+            return Ok(resp);
+        }
+
+        if let Some(access) = self.renew_access(&tokens.access).await {
+            let resp = self.send(build().bearer_auth(access)).await?;
+            if resp.status() != StatusCode::UNAUTHORIZED {
+                return Ok(resp);
+            }
+        }
+
+        self.session_expired.store(true, Ordering::Relaxed);
+        Err(Error::SessionExpired)
+    }
+
+    /// A fresh access token after `rejected` got a 401. If another request already rotated
+    /// the pair while this one waited for the lock, its new token is used as is.
+    async fn renew_access(&self, rejected: &str) -> Option<String> {
+        let _guard = self.refresh_lock.lock().await;
+        let tokens = self.tokens()?;
+        if tokens.access != rejected {
+            return Some(tokens.access);
+        }
+        self.rotate(tokens.refresh?).await.ok().map(|data| data.token)
+    }
+
+    async fn call<T: DeserializeOwned>(&self, build: impl Fn() -> RequestBuilder) -> Result<T> {
+        decode(self.send_auth(build).await?).await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
+        self.call(|| self.http.get(self.url(endpoint))).await
+    }
+
+    async fn get_query<Q: Serialize, T: DeserializeOwned>(&self, endpoint: &str, query: &Q) -> Result<T> {
+        self.call(|| self.http.get(self.url(endpoint)).query(query)).await
+    }
+
+    async fn post<B: Serialize, T: DeserializeOwned>(&self, endpoint: &str, body: &B) -> Result<T> {
+        self.call(|| self.http.post(self.url(endpoint)).json(body)).await
+    }
+
+    async fn post_empty<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
+        self.call(|| self.http.post(self.url(endpoint))).await
+    }
+
+    /// Like `post`, for writes that must not happen twice. If the connection drops the
+    /// request is sent once more with the same `Idempotency-Key`, so the server replays
+    /// its first response instead of applying the write again.
+    async fn post_once<B: Serialize, T: DeserializeOwned>(&self, endpoint: &str, body: &B) -> Result<T> {
+        let key = uuid::Uuid::new_v4().to_string();
+        let build = || self.http.post(self.url(endpoint)).header("Idempotency-Key", &key).json(body);
+        let resp = match self.send_auth(build).await {
+            Err(Error::Network { .. }) => self.send_auth(build).await?,
+            result => result?,
+        };
+        decode(resp).await
+    }
+
+    async fn put<B: Serialize, T: DeserializeOwned>(&self, endpoint: &str, body: &B) -> Result<T> {
+        self.call(|| self.http.put(self.url(endpoint)).json(body)).await
+    }
+
+    async fn delete<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T> {
+        self.call(|| self.http.delete(self.url(endpoint))).await
+    }
+
+    async fn delete_with_body<B: Serialize, T: DeserializeOwned>(&self, endpoint: &str, body: &B) -> Result<T> {
+        self.call(|| self.http.delete(self.url(endpoint)).json(body)).await
+    }
+
+    fn store_session(&self, data: &LoginResponse) {
+        self.set_tokens(Some(AuthTokens { access: data.token.clone(), refresh: Some(data.refresh_token.clone()) }));
+    }
+
+    // Authentication
+
+    pub async fn register(&self, req: &RegisterRequest) -> Result<RegisterResponse> {
+        decode(self.send(self.http.post(self.url("/auth/register")).json(req)).await?).await
+    }
+
+    /// Signs in; on [`LoginStep::LoggedIn`] the client keeps the new tokens.
+    pub async fn login(&self, req: &LoginRequest) -> Result<LoginStep> {
+        let resp = self.send(self.http.post(self.url("/auth/login")).json(req)).await?;
+        match decode(resp).await? {
+            LoginResult::Session(data) => {
+                self.store_session(&data);
+                Ok(LoginStep::LoggedIn(data))
+            }
+            LoginResult::TwoFactorRequired(challenge) => Ok(LoginStep::TwoFactorRequired(challenge)),
+        }
+    }
+
+    /// Completes a two-factor login with a TOTP or recovery code.
+    pub async fn verify_two_factor(&self, req: &TwoFactorVerifyRequest) -> Result<LoginResponse> {
+        let resp = self.send(self.http.post(self.url("/auth/2fa/verify")).json(req)).await?;
+        let data: LoginResponse = decode(resp).await?;
+        self.store_session(&data);
+        Ok(data)
+    }
+
+    /// Rotates the token pair. Requests do this by themselves on a 401.
+    pub async fn refresh(&self, refresh_token: String) -> Result<LoginResponse> {
+        let _guard = self.refresh_lock.lock().await;
+        self.rotate(refresh_token).await
+    }
+
+    /// `refresh` for callers already holding `refresh_lock`.
+    async fn rotate(&self, refresh_token: String) -> Result<LoginResponse> {
+        let req = RefreshRequest { refresh_token };
+        let resp = self.send(self.http.post(self.url("/auth/refresh")).json(&req)).await?;
+        let data: LoginResponse = decode(resp).await?;
+        self.store_session(&data);
+        if let (Some(listener), Some(tokens)) = (&self.on_refresh, self.tokens()) {
+            listener(&tokens);
+        }
+        Ok(data)
+    }
+
+    /// Invalidates the session server-side; the client drops its tokens either way.
+    pub async fn logout(&self) -> Result<LogoutResponse> {
+        let result = self.post_empty("/auth/logout").await;
+        self.set_tokens(None);
+        result
+    }
+
+    pub async fn logout_all(&self) -> Result<LogoutAllResponse> {
+        let result = self.post_empty("/auth/logout-all").await;
+        self.set_tokens(None);
+        result
+    }
+
+    pub async fn setup_two_factor(&self) -> Result<TwoFactorSetupResponse> {
+        self.post_empty("/auth/2fa/setup").await
+    }
+
+    pub async fn confirm_two_factor(&self, req: &TwoFactorConfirmRequest) -> Result<TwoFactorConfirmResponse> {
+        self.post("/auth/2fa/confirm", req).await
+    }
+
+    pub async fn disable_two_factor(&self, req: &TwoFactorDisableRequest) -> Result<TwoFactorDisableResponse> {
+        self.post("/auth/2fa/disable", req).await
+    }
+
+    pub async fn get_sessions(&self) -> Result<Vec<SessionResponse>> {
+        self.get("/auth/sessions").await
+    }
+
+    pub async fn revoke_session(&self, id: i32) -> Result<RevokeSessionResponse> {
+        self.delete(&format!("/auth/sessions/{}", id)).await
+    }
+
+    // The signed-in user
+
+    pub async fn change_password(&self, req: &ChangePasswordRequest) -> Result<ChangePasswordResponse> {
+        self.post("/auth/change-password", req).await
+    }
+
+    pub async fn change_username(&self, req: &ChangeUsernameRequest) -> Result<ChangeUsernameResponse> {
+        self.put("/me/username", req).await
+    }
+
+    /// Permanently deletes the user and all their data, then drops the tokens.
+    pub async fn delete_me(&self, req: &DeleteMeRequest) -> Result<DeleteMeResponse> {
+        let data = self.delete_with_body("/me", req).await?;
+        self.set_tokens(None);
+        Ok(data)
+    }
+
+    pub async fn get_tokens(&self) -> Result<Vec<ApiTokenResponse>> {
+        self.get("/tokens").await
+    }
+
+    pub async fn create_token(&self, req: &CreateApiTokenRequest) -> Result<CreateApiTokenResponse> {
+        self.post("/tokens", req).await
+    }
+
+    pub async fn delete_token(&self, id: i32) -> Result<DeleteApiTokenResponse> {
+        self.delete(&format!("/tokens/{}", id)).await
+    }
+
+    // Households
+
+    pub async fn get_households(&self) -> Result<Vec<HouseholdResponse>> {
+        self.get("/households").await
+    }
+
+    pub async fn create_household(&self, req: &CreateHouseholdRequest) -> Result<CreateHouseholdResponse> {
+        self.post("/households", req).await
+    }
+
+    pub async fn delete_household(&self, id: i32) -> Result<DeleteHouseholdResponse> {
+        self.delete(&format!("/households/{}", id)).await
+    }
+
+    pub async fn get_household_members(&self, id: i32) -> Result<Vec<HouseholdMemberResponse>> {
+        self.get(&format!("/households/{}/members", id)).await
+    }
+
+    pub async fn add_household_member(&self, id: i32, req: &AddHouseholdMemberRequest) -> Result<HouseholdMemberChangeResponse> {
+        self.post(&format!("/households/{}/members", id), req).await
+    }
+
+    pub async fn update_household_member(&self, id: i32, user_id: i32, req: &UpdateHouseholdMemberRequest) -> Result<HouseholdMemberChangeResponse> {
+        self.put(&format!("/households/{}/members/{}", id, user_id), req).await
+    }
+
+    pub async fn remove_household_member(&self, id: i32, user_id: i32) -> Result<HouseholdMemberChangeResponse> {
+        self.delete(&format!("/households/{}/members/{}", id, user_id)).await
+    }
+
+    // Accounts and categories
+
+    pub async fn get_accounts(&self) -> Result<Vec<AccountResponse>> {
+        self.get("/accounts").await
+    }
+
+    /// Sent with an `Idempotency-Key`, so a retry can't create the account twice.
+    pub async fn create_account(&self, req: &CreateAccountRequest) -> Result<CreateAccountResponse> {
+        self.post_once("/accounts", req).await
+    }
+
+    pub async fn update_account(&self, id: i32, req: &UpdateAccountRequest) -> Result<UpdateAccountResponse> {
+        self.put(&format!("/accounts/{}", id), req).await
+    }
+
+    pub async fn delete_account(&self, id: i32) -> Result<DeleteAccountResponse> {
+        self.delete(&format!("/accounts/{}", id)).await
+    }
+
+    pub async fn get_categories(&self) -> Result<Vec<CategoryResponse>> {
+        self.get("/categories").await
+    }
+
+    pub async fn create_category(&self, req: &CreateCategoryRequest) -> Result<CreateCategoryResponse> {
+        self.post("/categories", req).await
+    }
+
+    pub async fn delete_category(&self, id: i32) -> Result<DeleteCategoryResponse> {
+        self.delete(&format!("/categories/{}", id)).await
+    }
+
+    // Transactions
+
+    pub async fn get_transactions(&self, query: &GetTransactionsQuery) -> Result<Vec<TransactionResponse>> {
+        self.get_query("/transactions", query).await
+    }
+
+    /// Sent with an `Idempotency-Key`, so a retry can't record the transaction twice.
+    pub async fn create_transaction(&self, req: &CreateTransactionRequest) -> Result<CreateTransactionResponse> {
+        self.post_once("/transactions", req).await
+    }
+
+    /// Sent with an `Idempotency-Key`, so a retry can't move the money twice.
+    pub async fn transfer(&self, req: &TransferRequest) -> Result<TransferResponse> {
+        self.post_once("/transactions/transfer", req).await
+    }
+
+    // Budgets
+
+    pub async fn get_budgets(&self) -> Result<Vec<BudgetResponse>> {
+        self.get("/budgets").await
+    }
+
+    pub async fn create_budget(&self, req: &CreateBudgetRequest) -> Result<CreateBudgetResponse> {
+        self.post("/budgets", req).await
+    }
+
+    pub async fn update_budget(&self, id: i32, req: &UpdateBudgetRequest) -> Result<UpdateBudgetResponse> {
+        self.put(&format!("/budgets/{}", id), req).await
+    }
+
+    pub async fn delete_budget(&self, id: i32) -> Result<DeleteBudgetResponse> {
+        self.delete(&format!("/budgets/{}", id)).await
+    }
+
+    // Rules
+
+    pub async fn get_rules(&self) -> Result<Vec<RuleResponse>> {
+        self.get("/rules").await
+    }
+
+    pub async fn create_rule(&self, req: &CreateRuleRequest) -> Result<CreateRuleResponse> {
+        self.post("/rules", req).await
+    }
+
+    pub async fn apply_rules(&self, req: &ApplyRulesRequest) -> Result<ApplyRulesResponse> {
+        self.post("/rules/apply", req).await
+    }
+
+    pub async fn update_rule(&self, id: i32, req: &UpdateRuleRequest) -> Result<UpdateRuleResponse> {
+        self.put(&format!("/rules/{}", id), req).await
+    }
+
+    pub async fn delete_rule(&self, id: i32) -> Result<DeleteRuleResponse> {
+        self.delete(&format!("/rules/{}", id)).await
+    }
+
+    // Payees
+
+    pub async fn get_payees(&self) -> Result<Vec<PayeeResponse>> {
+        self.get("/payees").await
+    }
+
+    pub async fn create_payee(&self, req: &CreatePayeeRequest) -> Result<CreatePayeeResponse> {
+        self.post("/payees", req).await
+    }
+
+    pub async fn match_payees(&self) -> Result<MatchPayeesResponse> {
+        self.post_empty("/payees/match").await
+    }
+
+    pub async fn update_payee(&self, id: i32, req: &UpdatePayeeRequest) -> Result<UpdatePayeeResponse> {
+        self.put(&format!("/payees/{}", id), req).await
+    }
+
+    pub async fn delete_payee(&self, id: i32) -> Result<DeletePayeeResponse> {
+        self.delete(&format!("/payees/{}", id)).await
+    }
+
+    pub async fn add_payee_alias(&self, id: i32, req: &AddPayeeAliasRequest) -> Result<AddPayeeAliasResponse> {
+        self.post(&format!("/payees/{}/aliases", id), req).await
+    }
+
+    pub async fn delete_payee_alias(&self, id: i32, alias_id: i32) -> Result<DeletePayeeAliasResponse> {
+        self.delete(&format!("/payees/{}/aliases/{}", id, alias_id)).await
+    }
+
+    // Reports
+
+    pub async fn get_cashflow(&self, query: &TimeSeriesQuery) -> Result<CashflowReportResponse> {
+        self.get_query("/reports/cashflow", query).await
+    }
+
+    pub async fn get_spending_by_category(&self, query: &ReportRangeQuery) -> Result<Vec<CategoryTotalResponse>> {
+        self.get_query("/reports/by-category", query).await
+    }
+
+    pub async fn get_net_worth(&self, query: &TimeSeriesQuery) -> Result<NetWorthReportResponse> {
+        self.get_query("/reports/net-worth", query).await
+    }
+
+    pub async fn get_spending_by_payee(&self, query: &ReportRangeQuery) -> Result<Vec<PayeeSpendingResponse>> {
+        self.get_query("/reports/by-payee", query).await
+    }
+}
+
+/// The body of a success response, or the server's error.
+async fn decode<T: DeserializeOwned>(resp: reqwest::Response) -> Result<T> {
+    let status = resp.status();
+    if status.is_success() {
+        return resp.json().await.map_err(Error::Decode);
+    }
+
+    let retry_after = resp.headers().get(header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    let body = resp.text().await.unwrap_or_default();
+    match serde_json::from_str::<ErrorResponse>(&body) {
+        Ok(error) => Err(Error::Api { status, error, retry_after }),
+        Err(_) => Err(Error::Status { status, body }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::AtomicUsize;
+
+    use axum::{
+        extract::State,
+        http::{self, HeaderMap},
+        response::{IntoResponse, Response},
+        routing::{get, post},
+        Json, Router,
+    };
+    use serde_json::{json, Value};
+    use tokio::sync::Barrier;
+
+    /// Stands in for the server's token rotation: one live pair at a time, and presenting
+    /// an already-rotated refresh token counts as reuse.
+    struct Server {
+        tokens: Mutex<(String, String)>,
+        refreshes: AtomicUsize,
+        reused: AtomicBool,
+        /// Holds the first requests until all of them have been rejected.
+        rejected: Barrier,
+    }
+
+    async fn accounts(State(server): State<Arc<Server>>, headers: HeaderMap) -> Response {
+        let bearer = headers[http::header::AUTHORIZATION].to_str().unwrap().trim_start_matches("Bearer ").to_string();
+        if bearer == server.tokens.lock().unwrap().0 {
+            return Json(json!([])).into_response();
+        }
+        if bearer == "expired" {
+            server.rejected.wait().await;
+        }
+        (http::StatusCode::UNAUTHORIZED, Json(json!({ "error": "Invalid token", "code": "unauthorized" }))).into_response()
+    }
+
+    async fn refresh(State(server): State<Arc<Server>>, Json(body): Json<Value>) -> Response {
+        let mut tokens = server.tokens.lock().unwrap();
+        if body["refresh_token"] != tokens.1 {
+            server.reused.store(true, Ordering::SeqCst);
+            return (http::StatusCode::UNAUTHORIZED, Json(json!({ "error": "Invalid refresh token", "code": "unauthorized" }))).into_response();
+        }
+        let n = server.refreshes.fetch_add(1, Ordering::SeqCst) + 1;
+        *tokens = (format!("access-{}", n), format!("refresh-{}", n));
+        Json(json!({
+            "message": "Token refreshed",
+            "token": tokens.0,
+            "expires_at": "2030-01-01T00:00:00Z",
+            "refresh_token": tokens.1,
+            "refresh_expires_at": "2030-01-01T00:00:00Z",
+            "user_id": 1,
+        }))
+        .into_response()
+    }
+
+    async fn serve(concurrent: usize) -> (ApiClient, Arc<Server>) {
+        let server = Arc::new(Server {
+            tokens: Mutex::new(("access-0".to_string(), "refresh-0".to_string())),
+            refreshes: AtomicUsize::new(0),
+            reused: AtomicBool::new(false),
+            rejected: Barrier::new(concurrent),
+        });
+        let app = Router::new()
+            .route("/api/v1/accounts", get(accounts))
+            .route("/api/v1/auth/refresh", post(refresh))
+            .with_state(server.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let api = ApiClient::new(format!("http://{}", addr));
+        api.set_tokens(Some(AuthTokens { access: "expired".to_string(), refresh: Some("refresh-0".to_string()) }));
+        (api, server)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn concurrent_401s_refresh_once() {
+        let (api, server) = serve(2).await;
+        let other = api.clone();
+
+        let (a, b) = tokio::join!(api.get_accounts(), other.get_accounts());
+        assert!(a.is_ok(), "{:?}", a);
+        assert!(b.is_ok(), "{:?}", b);
+        assert_eq!(server.refreshes.load(Ordering::SeqCst), 1);
+        assert!(!server.reused.load(Ordering::SeqCst));
+        assert_eq!(api.tokens().unwrap().access, "access-1");
+        assert!(!api.take_session_expired());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn failed_refresh_expires_the_session() {
+        let (api, server) = serve(1).await;
+        *server.tokens.lock().unwrap() = ("access-0".to_string(), "revoked".to_string());
+
+        assert!(matches!(api.get_accounts().await, Err(Error::SessionExpired)));
+        assert!(api.take_session_expired());
+    }
+}
@@ -0,0 +1,250 @@
+use std::{collections::BTreeMap, env, fs, path::PathBuf};
+use anyhow::{Result, anyhow, Context};
+use serde::Deserialize;
+
+const DEFAULT_PROFILE: &str = "local";
+const DEFAULT_URL: &str = "http://localhost:3000";
+
+const USAGE: &str = "Usage: finance_tui [--profile <name>] [--url <base-url>] [--config <path>]
+
+  --profile <name>   Use a named profile from the config file (env: FINANCE_TUI_PROFILE)
+  --url <base-url>   Talk to the server at this URL, e.g. http://localhost:3000 (env: FINANCE_TUI_URL)
+  --config <path>    Read profiles from this file instead of the default location
+  -h, --help         Show this help";
+
+/// Contents of `config.toml`:
+///
+/// ```toml
+/// default_profile = "home"
+///
+/// [profiles.home]
+/// url = "https://finance.example.com"
+///
+/// [profiles.local]
+/// url = "http://localhost:3000"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    default_profile: Option<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Profile {
+    url: String,
+}
+
+/// The server the TUI talks to, resolved from CLI flags, environment and config file.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub profile: String,
+    pub base_url: String,
+}
+
+struct CliArgs {
+    profile: Option<String>,
+    url: Option<String>,
+    config: Option<PathBuf>,
+}
+
+impl Settings {
+    /// Resolves the base URL with precedence: `--url`, `FINANCE_TUI_URL`, the
+    /// selected profile (`--profile`, `FINANCE_TUI_PROFILE`, then `default_profile`),
+    /// and finally the built-in `local` profile. Returns `Ok(None)` if `--help` was requested.
+    pub fn load() -> Result<Option<Self>> {
+        let Some(args) = parse_args(env::args().skip(1))? else {
+            println!("{}", USAGE);
+            return Ok(None);
+        };
+        Self::resolve(args, |name| env::var(name).ok(), default_config_path()).map(Some)
+    }
+
+    /// `load` without the process environment: variables come from `env_var`, and
+    /// `default_config` is read when `--config` isn't given (and skipped if missing).
+    fn resolve(args: CliArgs, env_var: impl Fn(&str) -> Option<String>, default_config: Option<PathBuf>) -> Result<Self> {
+        let config_path = args.config.clone().or(default_config);
+        let config = match &config_path {
+            Some(path) if path.exists() => {
+                let text = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read config file {}", path.display()))?;
+                toml::from_str::<ConfigFile>(&text)
+                    .with_context(|| format!("Invalid config file {}", path.display()))?
+            }
+            Some(path) if args.config.is_some() => {
+                return Err(anyhow!("Config file {} does not exist", path.display()));
+            }
+            _ => ConfigFile::default(),
+        };
+
+        let url_override = args.url.or_else(|| env_var("FINANCE_TUI_URL").filter(|u| !u.is_empty()));
+        let profile = args.profile
+            .or_else(|| env_var("FINANCE_TUI_PROFILE").filter(|p| !p.is_empty()))
+            .or(config.default_profile.clone())
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+
+        let base_url = match (url_override, config.profiles.get(&profile)) {
+            (Some(url), _) => url,
+            (None, Some(p)) => p.url.clone(),
+            (None, None) if profile == DEFAULT_PROFILE => DEFAULT_URL.to_string(),
+            (None, None) => {
+                let known: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+                return Err(anyhow!(
+                    "Unknown profile '{}'. Known profiles: {} (config: {})",
+                    profile,
+                    if known.is_empty() { "none".to_string() } else { known.join(", ") },
+                    config_path.map(|p| p.display().to_string()).unwrap_or_else(|| "none".to_string()),
+                ));
+            }
+        };
+
+        Ok(Self {
+            profile,
+            base_url: validate_url(&base_url)?,
+        })
+    }
+}
+
+/// `$XDG_CONFIG_HOME/finance_tui` (or the platform equivalent).
+pub fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("finance_tui"))
+}
+
+fn default_config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("config.toml"))
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<CliArgs>> {
+    let mut parsed = CliArgs { profile: None, url: None, config: None };
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or_else(|| anyhow!("{} requires a value\n\n{}", flag, USAGE));
+        match arg.as_str() {
+            "--profile" | "-p" => parsed.profile = Some(value(&arg)?),
+            "--url" | "-u" => parsed.url = Some(value(&arg)?),
+            "--config" | "-c" => parsed.config = Some(PathBuf::from(value(&arg)?)),
+            "--help" | "-h" => return Ok(None),
+            other => return Err(anyhow!("Unknown argument '{}'\n\n{}", other, USAGE)),
+        }
+    }
+    Ok(Some(parsed))
+}
+
+fn validate_url(url: &str) -> Result<String> {
+    let url = url.trim().trim_end_matches('/');
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(anyhow!("Invalid server URL '{}': must start with http:// or https://", url));
+    }
+    Ok(url.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    const CONFIG: &str = r#"
+        default_profile = "home"
+
+        [profiles.home]
+        url = "https://finance.example.com/"
+
+        [profiles.work]
+        url = "https://finance.work.example.com"
+    "#;
+
+    /// A config file unique to the calling test, removed again by `resolve_with`.
+    fn config_file(name: &str, contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("finance-tui-{}-{}.toml", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn args(list: &[&str]) -> CliArgs {
+        parse_args(list.iter().map(|a| a.to_string())).unwrap().unwrap()
+    }
+
+    fn resolve_with(cli: &[&str], vars: &[(&str, &str)], config: Option<PathBuf>) -> Result<Settings> {
+        let vars = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>();
+        let settings = Settings::resolve(args(cli), |name| vars.get(name).cloned(), config.clone());
+        if let Some(path) = config {
+            let _ = fs::remove_file(path);
+        }
+        settings
+    }
+
+    #[test]
+    fn without_config_the_local_profile_is_used() {
+        let settings = resolve_with(&[], &[], None).unwrap();
+        assert_eq!(settings.profile, "local");
+        assert_eq!(settings.base_url, "http://localhost:3000");
+
+        // A default config file that doesn't exist is not an error
+        let settings = resolve_with(&[], &[], Some(env::temp_dir().join("finance-tui-missing.toml"))).unwrap();
+        assert_eq!(settings.base_url, "http://localhost:3000");
+    }
+
+    #[test]
+    fn profile_precedence_is_flag_then_env_then_default_profile() {
+        let settings = resolve_with(&[], &[], Some(config_file("default", CONFIG))).unwrap();
+        assert_eq!(settings.profile, "home");
+        assert_eq!(settings.base_url, "https://finance.example.com");
+
+        let settings = resolve_with(&[], &[("FINANCE_TUI_PROFILE", "work")], Some(config_file("env", CONFIG))).unwrap();
+        assert_eq!(settings.base_url, "https://finance.work.example.com");
+
+        let settings = resolve_with(
+            &["--profile", "home"],
+            &[("FINANCE_TUI_PROFILE", "work")],
+            Some(config_file("flag", CONFIG)),
+        )
+        .unwrap();
+        assert_eq!(settings.profile, "home");
+
+        // Blank variables count as unset
+        let settings = resolve_with(&[], &[("FINANCE_TUI_PROFILE", "")], Some(config_file("blank", CONFIG))).unwrap();
+        assert_eq!(settings.profile, "home");
+    }
+
+    #[test]
+    fn url_flag_beats_env_and_both_beat_the_profile() {
+        let vars = [("FINANCE_TUI_URL", "http://env.example.com")];
+        let settings = resolve_with(&[], &vars, Some(config_file("env-url", CONFIG))).unwrap();
+        assert_eq!(settings.base_url, "http://env.example.com");
+
+        let settings = resolve_with(&["-u", "http://flag.example.com/"], &vars, Some(config_file("flag-url", CONFIG))).unwrap();
+        assert_eq!(settings.base_url, "http://flag.example.com");
+        assert_eq!(settings.profile, "home");
+    }
+
+    #[test]
+    fn unknown_profiles_are_errors_listing_the_known_ones() {
+        let error = resolve_with(&["--profile", "typo"], &[], Some(config_file("unknown", CONFIG))).unwrap_err().to_string();
+        assert!(error.starts_with("Unknown profile 'typo'. Known profiles: home, work"), "{}", error);
+
+        let error = resolve_with(&[], &[("FINANCE_TUI_PROFILE", "typo")], None).unwrap_err().to_string();
+        assert!(error.contains("Known profiles: none"), "{}", error);
+
+        // A URL makes the profile's own URL irrelevant
+        assert!(resolve_with(&["-p", "typo", "-u", "http://x.example.com"], &[], None).is_ok());
+    }
+
+    #[test]
+    fn explicit_config_must_exist_and_parse() {
+        let missing = env::temp_dir().join(format!("finance-tui-{}-absent.toml", std::process::id()));
+        let error = resolve_with(&["--config", missing.to_str().unwrap()], &[], None).unwrap_err().to_string();
+        assert!(error.contains("does not exist"), "{}", error);
+
+        let error = resolve_with(&[], &[], Some(config_file("invalid", "profiles = 3"))).unwrap_err().to_string();
+        assert!(error.starts_with("Invalid config file"), "{}", error);
+    }
+
+    #[test]
+    fn arguments_are_checked() {
+        assert!(parse_args(["--help".to_string()].into_iter()).unwrap().is_none());
+        assert!(parse_args(["--profile".to_string()].into_iter()).is_err());
+        assert!(parse_args(["--verbose".to_string()].into_iter()).is_err());
+        assert!(validate_url("localhost:3000").is_err());
+        assert_eq!(validate_url(" https://example.com// ").unwrap(), "https://example.com");
+    }
+}
@@ -1,5 +1,5 @@
-mod models;
-mod api;
+mod config;
+mod session;
 
 use std::{io, time::Duration};
 use crossterm::{
@@ -7,17 +7,37 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetSize},
 };
-use ratatui::{prelude::*, widgets::*};
-use api::ApiClient;
-use models::*;
+use ratatui::{prelude::*, symbols, widgets::*};
+use chrono::{Datelike, Duration as ChronoDuration, Months, NaiveDate};
+use finance_api_client::{ApiClient, Error as ApiError, LoginStep};
+use finance_api_client::types::{models::*, AccountName, CategoryName, CurrencyCode, IsoDate, Money};
+use config::Settings;
+use session::SessionStore;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 
 
 enum AppState {
     Login,
     Dashboard,
+    Reports,
     InputPopup(PopupType),
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum ReportInterval {
+    Month,
+    Week,
+}
+
+impl ReportInterval {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReportInterval::Month => "month",
+            ReportInterval::Week => "week",
+        }
+    }
+}
+
 #[derive(PartialEq)]
 enum Focus {
     Accounts,
@@ -40,6 +60,10 @@ enum PopupType {
         verify_name: String, 
         input_name: String 
     },
+
+    ChangePassword { step: usize, current: String, new: String, confirm: String },
+    ChangeUsername { username: String },
+    DeleteAccount { step: usize, password: String, confirm: String },
 }
 
 #[derive(PartialEq)]
@@ -51,12 +75,16 @@ enum InputMode {
 struct App {
     state: AppState,
     api: ApiClient,
+    store: SessionStore,
+    profile: String,
     
     // Auth
     input_username: String,
     input_password: String,
     input_mode: InputMode,
     is_register_mode: bool,
+    login_challenge: Option<String>,
+    input_code: String,
     message: Option<(String, Color)>, 
 
     // Data
@@ -64,6 +92,15 @@ struct App {
     transactions: Vec<TransactionResponse>,
     budgets: Vec<BudgetResponse>,
     categories: Vec<CategoryResponse>, 
+    net_worth: Vec<NetWorthPointResponse>,
+
+    // Reports
+    report_interval: ReportInterval,
+    report_periods: u32,
+    report_offset: u32,
+    cashflow: Option<CashflowReportResponse>,
+    category_totals: Vec<CategoryTotalResponse>,
+    report_net_worth: Vec<NetWorthPointResponse>,
 
     // UI Navigation State
     focus: Focus, 
@@ -73,23 +110,42 @@ struct App {
 }
 
 impl App {
-    fn new() -> Self {
+    fn new(settings: Settings) -> Self {
         let mut list_state = ListState::default();
         list_state.select(Some(0)); 
 
+        // The old refresh token is spent once the client rotates it, so the new pair is saved
+        // right away; presenting the old one again would make the server revoke the session.
+        let api = ApiClient::new(settings.base_url);
+        let store = SessionStore::new(&settings.profile, api.base_url());
+        let saver = store.clone();
+        let api = api.on_tokens_refreshed(move |tokens| { let _ = saver.save(tokens); });
+
         Self {
             state: AppState::Login,
-            api: ApiClient::new(),
+            api,
+            store,
+            profile: settings.profile,
             input_username: String::new(),
             input_password: String::new(),
             input_mode: InputMode::Username,
             is_register_mode: false,
+            login_challenge: None,
+            input_code: String::new(),
             message: None,
             
             accounts: vec![],
             transactions: vec![],
             budgets: vec![],
             categories: vec![],
+            net_worth: vec![],
+
+            report_interval: ReportInterval::Month,
+            report_periods: 12,
+            report_offset: 0,
+            cashflow: None,
+            category_totals: vec![],
+            report_net_worth: vec![],
             
             // 初始化焦点和列表状态
             focus: Focus::Accounts, 
@@ -166,35 +222,162 @@ impl App {
     }
 
     async fn try_register(&mut self) {
-        let req = LoginRequest { username: self.input_username.clone(), password: self.input_password.clone() };
-        match self.api.register(req).await {
-            Ok(msg) => { self.is_register_mode = false; self.message = Some((format!("Success: {}. Please Login.", msg), Color::Green)); }
+        let req = RegisterRequest { username: self.input_username.clone(), password: self.input_password.clone() };
+        match self.api.register(&req).await {
+            Ok(data) => { self.is_register_mode = false; self.message = Some((format!("Success: {}. Please Login.", data.message), Color::Green)); }
             Err(e) => self.message = Some((format!("Register Error: {}", e), Color::Red)),
         }
     }
 
     async fn try_login(&mut self) {
         let req = LoginRequest { username: self.input_username.clone(), password: self.input_password.clone() };
-        match self.api.login(req).await {
+        match self.api.login(&req).await {
+            Ok(LoginStep::LoggedIn(_)) => self.finish_login().await,
+            Ok(LoginStep::TwoFactorRequired(challenge)) => {
+                self.login_challenge = Some(challenge.challenge_token);
+                self.input_code.clear();
+                self.input_password.clear();
+                self.message = Some(("Enter the code from your authenticator app or a recovery code.".to_string(), Color::Yellow));
+            }
+            Err(e @ ApiError::Api { .. }) if e.code() != Some("rate_limited") => {
+                self.message = Some(("Login failed: Check username/password".to_string(), Color::Red));
+            }
+            Err(e) => self.message = Some((format!("Login failed: {}", e), Color::Red)),
+        }
+    }
+
+    async fn try_verify_two_factor(&mut self) {
+        let Some(challenge) = self.login_challenge.clone() else { return; };
+        let req = TwoFactorVerifyRequest { challenge_token: challenge, code: self.input_code.trim().to_string() };
+        match self.api.verify_two_factor(&req).await {
+            Ok(_) => {
+                self.login_challenge = None;
+                self.finish_login().await;
+            }
+            Err(e) => {
+                self.input_code.clear();
+                self.message = Some((format!("Verification failed: {}", e), Color::Red));
+            }
+        }
+    }
+
+    fn cancel_two_factor(&mut self) {
+        self.login_challenge = None;
+        self.input_code.clear();
+        self.input_mode = InputMode::Password;
+        self.message = None;
+    }
+
+    async fn finish_login(&mut self) {
+        self.state = AppState::Dashboard;
+        self.message = None;
+        self.input_password.clear();
+        self.input_code.clear();
+        if let Some(Err(e)) = self.api.tokens().map(|tokens| self.store.save(&tokens)) {
+            self.message = Some((format!("Logged in, but the session could not be saved: {}", e), Color::Yellow));
+        }
+        self.refresh_all_data().await;
+    }
+
+    /// Picks up the token saved by a previous run, skipping the login screen if it is still valid.
+    async fn resume_session(&mut self) {
+        let Some(tokens) = self.store.load() else { return; };
+        self.api.set_tokens(Some(tokens));
+        match self.api.get_accounts().await {
             Ok(_) => {
                 self.state = AppState::Dashboard;
-                self.message = None;
                 self.refresh_all_data().await;
             }
-            Err(e) => self.message = Some((e.to_string(), Color::Red)),
+            Err(ApiError::SessionExpired) => {}
+            Err(e) => {
+                self.message = Some((format!("Could not resume saved session: {}", e), Color::Red));
+            }
         }
     }
 
+    /// Called after any request was rejected with 401: forget the token and return to login.
+    fn expire_session(&mut self) {
+        self.api.set_tokens(None);
+        self.store.clear();
+        self.input_password.clear();
+        self.is_register_mode = false;
+        self.input_mode = InputMode::Username;
+        self.state = AppState::Login;
+        self.message = Some(("Your session has expired. Please log in again.".to_string(), Color::Yellow));
+    }
+
+    async fn logout(&mut self) {
+        let result = self.api.logout().await;
+        self.store.clear();
+        self.api.take_session_expired();
+        self.input_password.clear();
+        self.input_mode = InputMode::Username;
+        self.state = AppState::Login;
+        self.message = match result {
+            Ok(_) => Some(("Logged out.".to_string(), Color::Green)),
+            Err(e) => Some((format!("Logged out locally ({})", e), Color::Yellow)),
+        };
+    }
+
+    /// Flips whether the selected account is a liability, which the net worth subtracts.
+    async fn toggle_liability(&mut self) {
+        let Some(acc) = self.get_selected_account() else { return };
+        let (id, is_liability) = (acc.id, !acc.is_liability);
+        let name = match acc.name.parse::<AccountName>() {
+            Ok(name) => name,
+            Err(e) => {
+                self.message = Some((format!("Error: {}", e), Color::Red));
+                return;
+            }
+        };
+        let req = UpdateAccountRequest { name, is_liability: Some(is_liability) };
+        self.message = match self.api.update_account(id, &req).await {
+            Ok(res) => Some((
+                format!("'{}' now counts as {}.", res.account.name, if is_liability { "a liability" } else { "an asset" }),
+                Color::Green,
+            )),
+            Err(e) => Some((format!("Error: {}", e), Color::Red)),
+        };
+        self.refresh_all_data().await;
+    }
+
     async fn refresh_all_data(&mut self) {
         if let Ok(data) = self.api.get_accounts().await { self.accounts = data; }
         if let Ok(data) = self.api.get_budgets().await { self.budgets = data; }
         if let Ok(data) = self.api.get_categories().await { self.categories = data; }
+        let (from, to) = report_range(ReportInterval::Month, 12, 0);
+        let query = TimeSeriesQuery { from: Some(from.to_string()), to: Some(to.to_string()), interval: Some("month".to_string()) };
+        if let Ok(data) = self.api.get_net_worth(&query).await { self.net_worth = data.points; }
         self.refresh_transactions().await;
     }
 
+    async fn refresh_reports(&mut self) {
+        let (from, to) = report_range(self.report_interval, self.report_periods, self.report_offset);
+        let range = ReportRangeQuery { from: Some(from.to_string()), to: Some(to.to_string()) };
+        let series = TimeSeriesQuery { from: range.from.clone(), to: range.to.clone(), interval: Some(self.report_interval.as_str().to_string()) };
+
+        let cashflow = self.api.get_cashflow(&series).await;
+        let categories = self.api.get_spending_by_category(&range).await;
+        let net_worth = self.api.get_net_worth(&series).await;
+        if let Ok(data) = self.api.get_budgets().await { self.budgets = data; }
+
+        match (cashflow, categories, net_worth) {
+            (Ok(cashflow), Ok(categories), Ok(net_worth)) => {
+                self.cashflow = Some(cashflow);
+                self.category_totals = categories;
+                self.report_net_worth = net_worth.points;
+                self.message = None;
+            }
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                self.message = Some((format!("Failed to load reports: {}", e), Color::Red));
+            }
+        }
+    }
+
     async fn refresh_transactions(&mut self) {
         let selected_id = self.get_selected_account().map(|a| a.id);
-        match self.api.get_transactions(selected_id).await {
+        let query = GetTransactionsQuery { account_id: selected_id, ..Default::default() };
+        match self.api.get_transactions(&query).await {
             Ok(data) => self.transactions = data,
             Err(_) => self.transactions = vec![],
         }
@@ -246,12 +429,18 @@ impl App {
                     let name_trim = name.trim();
                     if name_trim.is_empty() {
                         Err(anyhow::anyhow!("Account name cannot be empty!"))
-                    } else if name_trim.len() > 50 {
+                    } else if name_trim.chars().count() > 50 {
                         Err(anyhow::anyhow!("Account name must be 50 characters or less!"))
                     } else if self.accounts.iter().any(|a| a.name.eq_ignore_ascii_case(name_trim)) {
                         Err(anyhow::anyhow!("Account '{}' already exists!", name_trim))
                     } else {
-                        self.api.create_account(CreateAccountRequest { name: name_trim.to_string(), currency: Some(currency.clone()) }).await
+                        match (name_trim.parse::<AccountName>(), currency.parse::<CurrencyCode>()) {
+                            (Ok(name), Ok(currency)) => {
+                                let req = CreateAccountRequest { name, currency: Some(currency), is_liability: None, household_id: None };
+                                self.api.create_account(&req).await.map(drop).map_err(Into::into)
+                            }
+                            (Err(e), _) | (_, Err(e)) => Err(anyhow::anyhow!(e)),
+                        }
                     }
                 },
                 PopupType::AddTransaction { amount, desc, category_input, .. } => {
@@ -263,14 +452,14 @@ impl App {
                         if amount_trim.is_empty() {
                             Err(anyhow::anyhow!("Amount cannot be empty!"))
                         } else {
-                            let amount_val = match amount_trim.parse::<f64>() {
-                                Ok(v) => v,
-                                Err(_) => {
-                                    self.message = Some(("Invalid amount format! Use numbers only.".to_string(), Color::Red));
+                            let amount_val = match parse_amount(amount_trim) {
+                                Some(v) => v,
+                                None => {
+                                    self.message = Some(("Invalid amount! Use a number with at most 2 decimals.".to_string(), Color::Red));
                                     return;
                                 }
                             };
-                            if amount_val == 0.0 {
+                            if amount_val.value().is_zero() {
                                 self.message = Some(("Amount cannot be zero!".to_string(), Color::Red));
                                 return;
                             } else {
@@ -284,14 +473,16 @@ impl App {
                                         return;
                                     }
                                 }
-                                self.api.create_transaction(CreateTransactionRequest {
+                                self.api.create_transaction(&CreateTransactionRequest {
                                     account_id: acc_id,
-                                    account_name: None, category_id: final_cat_id,
-                                    amount: amount_trim.to_string(),
-                                    r#type: if amount_val >= 0.0 { "income".to_string() } else { "expense".to_string() },
-                                    date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+                                    account_name: None,
+                                    amount: amount_val,
+                                    date: Some(IsoDate::from(chrono::Local::now().date_naive())),
+                                    category_id: final_cat_id,
+                                    category_name: None,
                                     description: Some(desc.clone()),
-                                }).await
+                                    payee_id: None,
+                                }).await.map(drop).map_err(Into::into)
                             }
                         }
                     }
@@ -330,18 +521,18 @@ impl App {
                         } else if !self.accounts.iter().any(|a| a.id == t) {
                             Err(anyhow::anyhow!("To account #{} not found!", t))
                         } else {
-                            let amount_val = match amount_trim.parse::<f64>() {
-                                Ok(v) => v,
-                                Err(_) => {
-                                    self.message = Some(("Invalid amount format! Use numbers only.".to_string(), Color::Red));
+                            let amount_val = match parse_amount(amount_trim) {
+                                Some(v) => v,
+                                None => {
+                                    self.message = Some(("Invalid amount! Use a number with at most 2 decimals.".to_string(), Color::Red));
                                     return;
                                 }
                             };
-                            if amount_val <= 0.0 {
+                            if amount_val.value() <= Decimal::ZERO {
                                 self.message = Some(("Transfer amount must be positive!".to_string(), Color::Red));
                                 return;
                             } else {
-                                self.api.transfer(TransferRequest { from_account_id: f, to_account_id: t, amount: amount_trim.to_string(), date: None, description: Some("TUI Transfer".to_string()) }).await
+                                self.api.transfer(&TransferRequest { from_account_id: f, to_account_id: t, amount: amount_val, date: None, description: Some("TUI Transfer".to_string()) }).await.map(drop).map_err(Into::into)
                             }
                         }
                     }
@@ -350,12 +541,18 @@ impl App {
                     let name_trim = name.trim();
                     if name_trim.is_empty() {
                         Err(anyhow::anyhow!("Category name cannot be empty!"))
-                    } else if name_trim.len() > 50 {
+                    } else if name_trim.chars().count() > 50 {
                         Err(anyhow::anyhow!("Category name must be 50 characters or less!"))
                     } else if self.categories.iter().any(|c| c.name.eq_ignore_ascii_case(name_trim)) {
                         Err(anyhow::anyhow!("Category '{}' already exists!", name_trim))
                     } else {
-                        self.api.create_category(CreateCategoryRequest { name: name_trim.to_string(), parent_id: None }).await
+                        match name_trim.parse::<CategoryName>() {
+                            Ok(name) => {
+                                let req = CreateCategoryRequest { name, parent_id: None, household_id: None };
+                                self.api.create_category(&req).await.map(drop).map_err(Into::into)
+                            }
+                            Err(e) => Err(anyhow::anyhow!(e)),
+                        }
                     }
                 },
                 PopupType::AddBudget { amount, category_id, .. } => {
@@ -363,14 +560,14 @@ impl App {
                     if amount_trim.is_empty() {
                         Err(anyhow::anyhow!("Budget amount cannot be empty!"))
                     } else {
-                        let amount_val = match amount_trim.parse::<f64>() {
-                            Ok(v) => v,
-                            Err(_) => {
-                                self.message = Some(("Invalid amount format! Use numbers only.".to_string(), Color::Red));
+                        let amount_val = match parse_amount(amount_trim) {
+                            Some(v) => v,
+                            None => {
+                                self.message = Some(("Invalid amount! Use a number with at most 2 decimals.".to_string(), Color::Red));
                                 return;
                             }
                         };
-                        if amount_val <= 0.0 {
+                        if amount_val.value() <= Decimal::ZERO {
                             self.message = Some(("Budget amount must be positive!".to_string(), Color::Red));
                             return;
                         } else {
@@ -391,20 +588,49 @@ impl App {
                                     Some(parsed_id)
                                 }
                             };
-                            self.api.create_budget(CreateBudgetRequest { category_id: cat_id, amount: amount_trim.to_string(), period: Some("monthly".to_string()), start_date: None }).await
+                            let req = CreateBudgetRequest { category_id: cat_id, household_id: None, amount: amount_val, period: Some("monthly".to_string()), start_date: None };
+                            self.api.create_budget(&req).await.map(drop).map_err(Into::into)
                         }
                     }
                 },
                 
+                PopupType::ChangePassword { current, new, confirm, .. } => {
+                    if current.is_empty() || new.is_empty() {
+                        Err(anyhow::anyhow!("Both current and new password are required!"))
+                    } else if new != confirm {
+                        Err(anyhow::anyhow!("New passwords do not match!"))
+                    } else {
+                        self.api.change_password(&ChangePasswordRequest { current_password: current.clone(), new_password: new.clone() }).await.map(drop).map_err(Into::into)
+                    }
+                },
+                PopupType::ChangeUsername { username } => {
+                    let name_trim = username.trim();
+                    if name_trim.is_empty() || name_trim.len() > 50 {
+                        Err(anyhow::anyhow!("Username must be between 1 and 50 characters!"))
+                    } else {
+                        self.api.change_username(&ChangeUsernameRequest { username: name_trim.to_string() }).await.map(drop).map_err(Into::into)
+                    }
+                },
+                PopupType::DeleteAccount { password, confirm, .. } => {
+                    if confirm != "DELETE" {
+                        Err(anyhow::anyhow!("Type DELETE to confirm!"))
+                    } else if password.is_empty() {
+                        Err(anyhow::anyhow!("Password is required!"))
+                    } else {
+                        let result = self.api.delete_me(&DeleteMeRequest { password: password.clone() }).await;
+                        if result.is_ok() { self.store.clear(); }
+                        result.map(drop).map_err(Into::into)
+                    }
+                },
               
                 PopupType::DeleteConfirm { type_label, target_id, verify_name, input_name } => {
                     if input_name != verify_name {
                         Err(anyhow::anyhow!("Name mismatch! Cancelled."))
                     } else {
                         match type_label.as_str() {
-                            "Account" => self.api.delete_account(*target_id).await,
-                            "Category" => self.api.delete_category(*target_id).await,
-                            "Budget" => self.api.delete_budget(*target_id).await,
+                            "Account" => self.api.delete_account(*target_id).await.map(drop).map_err(Into::into),
+                            "Category" => self.api.delete_category(*target_id).await.map(drop).map_err(Into::into),
+                            "Budget" => self.api.delete_budget(*target_id).await.map(drop).map_err(Into::into),
                             _ => Ok(()),
                         }
                     }
@@ -412,8 +638,19 @@ impl App {
             };
 
             match result {
+                Ok(_) if matches!(popup, PopupType::DeleteAccount { .. }) => {
+                    self.input_username.clear();
+                    self.input_password.clear();
+                    self.input_mode = InputMode::Username;
+                    self.state = AppState::Login;
+                    self.message = Some(("Your account and all its data have been deleted.".to_string(), Color::Green));
+                },
                 Ok(_) => {
-                    self.message = Some(("Action Successful!".to_string(), Color::Green));
+                    self.message = Some((match popup {
+                        PopupType::ChangePassword { .. } => "Password changed. Other devices have been signed out.".to_string(),
+                        PopupType::ChangeUsername { username } => format!("Username changed to '{}'.", username.trim()),
+                        _ => "Action Successful!".to_string(),
+                    }, Color::Green));
                     self.refresh_all_data().await;
                     self.state = AppState::Dashboard;
                 },
@@ -424,6 +661,49 @@ impl App {
 }
 
 
+/// Date range covering `periods` intervals, shifted `offset` ranges into the past.
+/// The current range ends today; older ranges end on their last interval's final day.
+fn report_range(interval: ReportInterval, periods: u32, offset: u32) -> (NaiveDate, NaiveDate) {
+    let today = chrono::Local::now().date_naive();
+    let back = offset * periods;
+    match interval {
+        ReportInterval::Month => {
+            let this_month = today.with_day(1).unwrap_or(today);
+            let anchor = this_month.checked_sub_months(Months::new(back)).unwrap_or(this_month);
+            let from = anchor.checked_sub_months(Months::new(periods - 1)).unwrap_or(anchor);
+            let to = if offset == 0 {
+                today
+            } else {
+                anchor.checked_add_months(Months::new(1)).map(|d| d - ChronoDuration::days(1)).unwrap_or(anchor)
+            };
+            (from, to)
+        }
+        ReportInterval::Week => {
+            let this_week = today - ChronoDuration::days(today.weekday().num_days_from_monday() as i64);
+            let anchor = this_week - ChronoDuration::weeks(back as i64);
+            let from = anchor - ChronoDuration::weeks(periods as i64 - 1);
+            let to = if offset == 0 { today } else { anchor + ChronoDuration::days(6) };
+            (from, to)
+        }
+    }
+}
+
+/// Parses an amount typed by the user. Amounts are stored with two decimal places,
+/// so anything finer is rejected rather than silently rounded.
+fn parse_amount(input: &str) -> Option<Money> {
+    input.parse().ok()
+}
+
+/// Converts an amount to a plotting coordinate. Only charts use this; amounts are
+/// never stored or sent as floats.
+fn plot_value(amount: &Decimal) -> f64 {
+    amount.to_f64().unwrap_or(0.0)
+}
+
+fn mask(secret: &str) -> String {
+    secret.chars().map(|_| '*').collect()
+}
+
 fn next_index(curr: Option<usize>, len: usize) -> usize {
     if len == 0 { return 0; }
     match curr {
@@ -441,20 +721,38 @@ fn prev_index(curr: Option<usize>, len: usize) -> usize {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let settings = match Settings::load() {
+        Ok(Some(settings)) => settings,
+        Ok(None) => return Ok(()),
+        Err(e) => { eprintln!("Error: {:#}", e); std::process::exit(2); }
+    };
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     let _ = execute!(stdout, EnterAlternateScreen, EnableMouseCapture, SetSize(140, 70));
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new();
+    let mut app = App::new(settings);
+    app.resume_session().await;
 
     loop {
+        if app.api.take_session_expired() { app.expire_session(); }
         terminal.draw(|f| ui(f, &app))?;
 
         if event::poll(Duration::from_millis(100))? {
             if let Event::Key(key) = event::read()? {
                 match app.state {
+                    AppState::Login if app.login_challenge.is_some() => {
+                        match key.code {
+                            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => app.input_code.push(c),
+                            KeyCode::Backspace => { app.input_code.pop(); },
+                            KeyCode::Enter => app.try_verify_two_factor().await,
+                            KeyCode::Esc => app.cancel_two_factor(),
+                            _ => {}
+                        }
+                    }
+
                     AppState::Login => {
                         
                         match key.code {
@@ -528,11 +826,62 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             
                             
                             KeyCode::Char('d') => app.init_delete(),
+                            KeyCode::Char('L') if app.focus == Focus::Accounts => app.toggle_liability().await,
+                            KeyCode::Char('l') => app.logout().await,
+                            KeyCode::Char('p') => {
+                                app.message = None;
+                                app.state = AppState::InputPopup(PopupType::ChangePassword { step: 0, current: String::new(), new: String::new(), confirm: String::new() });
+                            },
+                            KeyCode::Char('u') => {
+                                app.message = None;
+                                app.state = AppState::InputPopup(PopupType::ChangeUsername { username: String::new() });
+                            },
+                            KeyCode::Char('D') => {
+                                app.message = None;
+                                app.state = AppState::InputPopup(PopupType::DeleteAccount { step: 0, password: String::new(), confirm: String::new() });
+                            },
+                            KeyCode::Char('v') => {
+                                app.message = None;
+                                app.state = AppState::Reports;
+                                app.refresh_reports().await;
+                            },
                             
                             _ => {}
                         }
                     }
 
+                    AppState::Reports => {
+                        match key.code {
+                            KeyCode::Esc | KeyCode::Char('q') => {
+                                app.message = None;
+                                app.state = AppState::Dashboard;
+                            },
+                            KeyCode::Char('r') => app.refresh_reports().await,
+                            KeyCode::Char('g') => {
+                                app.report_interval = match app.report_interval { ReportInterval::Month => ReportInterval::Week, ReportInterval::Week => ReportInterval::Month };
+                                app.report_offset = 0;
+                                app.refresh_reports().await;
+                            },
+                            KeyCode::Char('[') => {
+                                app.report_offset += 1;
+                                app.refresh_reports().await;
+                            },
+                            KeyCode::Char(']') if app.report_offset > 0 => {
+                                app.report_offset -= 1;
+                                app.refresh_reports().await;
+                            },
+                            KeyCode::Char('+') | KeyCode::Char('=') if app.report_periods < 24 => {
+                                app.report_periods += 1;
+                                app.refresh_reports().await;
+                            },
+                            KeyCode::Char('-') if app.report_periods > 3 => {
+                                app.report_periods -= 1;
+                                app.refresh_reports().await;
+                            },
+                            _ => {}
+                        }
+                    }
+
                     AppState::InputPopup(ref mut popup) => {
                         match key.code {
                             KeyCode::Esc => app.state = AppState::Dashboard,
@@ -544,6 +893,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                     PopupType::AddTransaction { step, .. } => *step = (*step + 1) % 3,
                                     PopupType::Transfer { step, .. } => *step = (*step + 1) % 3,
                                     PopupType::AddBudget { step, .. } => *step = (*step + 1) % 2,
+                                    PopupType::ChangePassword { step, .. } => *step = (*step + 1) % 3,
+                                    PopupType::DeleteAccount { step, .. } => *step = (*step + 1) % 2,
                                     _ => {}
                                 }
                             },
@@ -553,6 +904,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                     PopupType::AddTransaction { step, .. } => *step = if *step == 0 { 2 } else { *step - 1 },
                                     PopupType::Transfer { step, .. } => *step = if *step == 0 { 2 } else { *step - 1 },
                                     PopupType::AddBudget { step, .. } => *step = if *step == 0 { 1 } else { *step - 1 },
+                                    PopupType::ChangePassword { step, .. } => *step = if *step == 0 { 2 } else { *step - 1 },
+                                    PopupType::DeleteAccount { step, .. } => *step = if *step == 0 { 1 } else { *step - 1 },
                                     _ => {}
                                 }
                             },
@@ -565,6 +918,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                     PopupType::AddCategory { name, .. } => name.push(c),
                                     PopupType::AddBudget { step, amount, category_id } => if *step == 0 { amount.push(c) } else { category_id.push(c) },
                                     PopupType::DeleteConfirm { input_name, .. } => input_name.push(c),
+                                    PopupType::ChangePassword { step, current, new, confirm } => { match step { 0 => current.push(c), 1 => new.push(c), 2 => confirm.push(c), _ => {} } },
+                                    PopupType::ChangeUsername { username } => username.push(c),
+                                    PopupType::DeleteAccount { step, password, confirm } => if *step == 0 { password.push(c) } else { confirm.push(c) },
                                 }
                             },
                             KeyCode::Backspace => {
@@ -575,6 +931,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                     PopupType::AddCategory { name, .. } => { name.pop(); },
                                     PopupType::AddBudget { step, amount, category_id } => if *step == 0 { amount.pop(); } else { category_id.pop(); },
                                     PopupType::DeleteConfirm { input_name, .. } => { input_name.pop(); },
+                                    PopupType::ChangePassword { step, current, new, confirm } => { match step { 0 => {current.pop();}, 1 => {new.pop();}, 2 => {confirm.pop();}, _ => {} } },
+                                    PopupType::ChangeUsername { username } => { username.pop(); },
+                                    PopupType::DeleteAccount { step, password, confirm } => if *step == 0 { password.pop(); } else { confirm.pop(); },
                                 }
                             }
                             _ => {}
@@ -595,6 +954,7 @@ fn ui(f: &mut Frame, app: &App) {
     let size = f.size();
     match app.state {
         AppState::Login => render_login(f, app, size),
+        AppState::Reports => render_reports(f, app, size),
         AppState::Dashboard | AppState::InputPopup(_) => {
             render_dashboard(f, app, size);
             if let AppState::InputPopup(ref popup) = app.state {
@@ -606,19 +966,29 @@ fn ui(f: &mut Frame, app: &App) {
 
 
 fn render_login(f: &mut Frame, app: &App, area: Rect) {
-    let chunks = Layout::default().direction(Direction::Vertical).margin(2).constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Length(3), Constraint::Length(3), Constraint::Min(1)]).split(area);
+    let chunks = Layout::default().direction(Direction::Vertical).margin(2).constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Length(3), Constraint::Length(3), Constraint::Min(1), Constraint::Length(1)]).split(area);
+    if app.login_challenge.is_some() {
+        f.render_widget(Paragraph::new("TWO-FACTOR VERIFICATION").style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)).alignment(Alignment::Center), chunks[0]);
+        f.render_widget(Paragraph::new(format!("Signing in as {}", app.input_username)).alignment(Alignment::Center).block(Block::default().borders(Borders::ALL)), chunks[1]);
+        f.render_widget(Paragraph::new(app.input_code.as_str()).block(Block::default().borders(Borders::ALL).title("Authentication Code").border_style(Style::default().fg(Color::Yellow))), chunks[2]);
+        f.render_widget(Paragraph::new("Use (Enter) to verify. (Esc) to go back").alignment(Alignment::Center), chunks[3]);
+        if let Some((msg, color)) = &app.message { f.render_widget(Paragraph::new(msg.as_str()).style(Style::default().fg(*color)).alignment(Alignment::Center).wrap(Wrap { trim: true }), chunks[4]); }
+        f.render_widget(Paragraph::new(format!("Server: {} ({})", app.profile, app.api.base_url())).style(Style::default().fg(Color::DarkGray)).alignment(Alignment::Center), chunks[5]);
+        return;
+    }
     let (title_text, title_color) = if app.is_register_mode { ("REGISTER NEW ACCOUNT", Color::Magenta) } else { ("PERSONAL FINANCE TRACKER - LOGIN", Color::Cyan) };
     f.render_widget(Paragraph::new(title_text).style(Style::default().fg(title_color).add_modifier(Modifier::BOLD)).alignment(Alignment::Center), chunks[0]);
     f.render_widget(Paragraph::new(app.input_username.as_str()).block(Block::default().borders(Borders::ALL).title("Username").border_style(if app.input_mode == InputMode::Username { Style::default().fg(Color::Yellow) } else { Style::default() })), chunks[1]);
     f.render_widget(Paragraph::new(app.input_password.chars().map(|_| '*').collect::<String>()).block(Block::default().borders(Borders::ALL).title("Password").border_style(if app.input_mode == InputMode::Password { Style::default().fg(Color::Yellow) } else { Style::default() })), chunks[2]);
     let mode_txt = if app.is_register_mode { "Switch to Login (Ctrl+r)" } else { "Use (Tab) to switch line.Use (Enter) to submit. Switch to Register (Ctrl+r)" };
     f.render_widget(Paragraph::new(mode_txt).alignment(Alignment::Center), chunks[3]);
-    if let Some((msg, color)) = &app.message { f.render_widget(Paragraph::new(msg.as_str()).style(Style::default().fg(*color)).alignment(Alignment::Center), chunks[4]); }
+    if let Some((msg, color)) = &app.message { f.render_widget(Paragraph::new(msg.as_str()).style(Style::default().fg(*color)).alignment(Alignment::Center).wrap(Wrap { trim: true }), chunks[4]); }
+    f.render_widget(Paragraph::new(format!("Server: {} ({})", app.profile, app.api.base_url())).style(Style::default().fg(Color::DarkGray)).alignment(Alignment::Center), chunks[5]);
 }
 
 fn render_dashboard(f: &mut Frame, app: &App, area: Rect) {
-    let vertical_chunks = Layout::default().direction(Direction::Vertical).constraints([Constraint::Length(3), Constraint::Min(0)]).split(area);
-    let help_text = "Nav: ←/→ Switch Panel | ↑/↓ Select | 'd' Delete | 't' Tx | 'a' Acc | 'x' Transfer | 'c' Cat | 'b' Budget";
+    let vertical_chunks = Layout::default().direction(Direction::Vertical).constraints([Constraint::Length(4), Constraint::Min(0)]).split(area);
+    let help_text = "Nav: ←/→ Switch Panel | ↑/↓ Select | 'd' Delete | 't' Tx | 'a' Acc | 'L' Asset/Liability | 'x' Transfer | 'c' Cat | 'b' Budget | 'v' Reports\nAccount: 'p' Change Password | 'u' Change Username | 'D' Delete My Account | 'l' Logout";
     f.render_widget(Paragraph::new(help_text).style(Style::default().fg(Color::White).bg(Color::Blue)).alignment(Alignment::Center).block(Block::default().borders(Borders::ALL)), vertical_chunks[0]);
 
     let main_chunks = Layout::default().direction(Direction::Horizontal).constraints([Constraint::Percentage(25), Constraint::Percentage(45), Constraint::Percentage(30)]).split(vertical_chunks[1]);
@@ -628,13 +998,20 @@ fn render_dashboard(f: &mut Frame, app: &App, area: Rect) {
 
     
     let account_items: Vec<ListItem> = app.accounts.iter().map(|acc| {
-        let content = format!("[#{}] {}", acc.id, acc.name);
-        ListItem::new(content).style(Style::default().fg(Color::Cyan))
+        let (content, color) = if acc.is_liability {
+            (format!("[#{}] {} (liability)", acc.id, acc.name), Color::LightRed)
+        } else {
+            (format!("[#{}] {}", acc.id, acc.name), Color::Cyan)
+        };
+        ListItem::new(content).style(Style::default().fg(color))
     }).collect();
     let accounts_list = List::new(account_items)
         .block(Block::default().borders(Borders::ALL).title("Accounts").border_style(border_style(app.focus == Focus::Accounts)))
         .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD)).highlight_symbol(">> ");
-    f.render_stateful_widget(accounts_list, main_chunks[0], &mut app.account_list_state.clone());
+    let left_chunks = Layout::default().direction(Direction::Vertical).constraints([Constraint::Min(5), Constraint::Length(7)]).split(main_chunks[0]);
+    f.render_stateful_widget(accounts_list, left_chunks[0], &mut app.account_list_state.clone());
+
+    render_net_worth(f, app, left_chunks[1]);
 
    
     let tx_title = if let Some(acc) = app.get_selected_account() { 
@@ -646,7 +1023,7 @@ fn render_dashboard(f: &mut Frame, app: &App, area: Rect) {
     let header = Row::new(header_cells).height(1).bottom_margin(1);
     let rows = app.transactions.iter().map(|t| {
         let amount_style = if t.r#type == "expense" { Style::default().fg(Color::Red) } else { Style::default().fg(Color::Green) };
-        let cells = vec![Cell::from(t.date.clone()), Cell::from(t.description.clone().unwrap_or_default()), Cell::from(t.category_name.clone().unwrap_or_default()), Cell::from(t.amount.clone()).style(amount_style)];
+        let cells = vec![Cell::from(t.date.to_string()), Cell::from(t.description.clone().unwrap_or_default()), Cell::from(t.category_name.clone().unwrap_or_default()), Cell::from(t.amount.to_string()).style(amount_style)];
         Row::new(cells).height(1)
     });
     f.render_widget(Table::new(rows, [
@@ -683,15 +1060,168 @@ fn render_dashboard(f: &mut Frame, app: &App, area: Rect) {
     }
 }
 
+fn render_net_worth(f: &mut Frame, app: &App, area: Rect) {
+    let values: Vec<f64> = app.net_worth.iter().map(|p| plot_value(&p.net_worth)).collect();
+    let title = match app.net_worth.last() {
+        Some(latest) => format!("Net Worth: {} ({} mo)", latest.net_worth, values.len()),
+        None => "Net Worth".to_string(),
+    };
+
+    // Sparkline bars can't go below zero, so shift the series up to its minimum
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min).min(0.0);
+    let data: Vec<u64> = values.iter().map(|v| ((v - min) * 100.0).round() as u64).collect();
+    let trend_color = match (values.first(), values.last()) {
+        (Some(first), Some(last)) if last < first => Color::Red,
+        _ => Color::Green,
+    };
+
+    f.render_widget(
+        Sparkline::default()
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .data(&data)
+            .style(Style::default().fg(trend_color)),
+        area,
+    );
+}
+
+fn render_reports(f: &mut Frame, app: &App, area: Rect) {
+    let (from, to) = report_range(app.report_interval, app.report_periods, app.report_offset);
+    let help_text = format!(
+        "Reports {} → {} ({} {}s) | Esc Back | 'g' Month/Week | '[' / ']' Older/Newer | '+'/'-' Periods | 'r' Refresh",
+        from, to, app.report_periods, app.report_interval.as_str()
+    );
+    let vertical_chunks = Layout::default().direction(Direction::Vertical).constraints([Constraint::Length(3), Constraint::Percentage(50), Constraint::Min(0)]).split(area);
+    f.render_widget(Paragraph::new(help_text).style(Style::default().fg(Color::White).bg(Color::Blue)).alignment(Alignment::Center).block(Block::default().borders(Borders::ALL)), vertical_chunks[0]);
+
+    let top_chunks = Layout::default().direction(Direction::Horizontal).constraints([Constraint::Percentage(65), Constraint::Percentage(35)]).split(vertical_chunks[1]);
+    let bottom_chunks = Layout::default().direction(Direction::Horizontal).constraints([Constraint::Percentage(55), Constraint::Percentage(45)]).split(vertical_chunks[2]);
+
+    render_cashflow_chart(f, app, top_chunks[0]);
+    render_report_net_worth(f, app, top_chunks[1]);
+    render_category_chart(f, app, bottom_chunks[0]);
+    render_budget_chart(f, app, bottom_chunks[1]);
+
+    if let Some((msg, color)) = &app.message {
+        let msg_area = Rect { x: area.x, y: area.height.saturating_sub(1), width: area.width, height: 1 };
+        f.render_widget(Paragraph::new(msg.as_str()).style(Style::default().bg(*color).fg(Color::Black)), msg_area);
+    }
+}
+
+fn render_cashflow_chart(f: &mut Frame, app: &App, area: Rect) {
+    let Some(cashflow) = &app.cashflow else {
+        f.render_widget(Paragraph::new("No data").block(Block::default().borders(Borders::ALL).title("Cash Flow")), area);
+        return;
+    };
+
+    let income: Vec<(f64, f64)> = cashflow.periods.iter().enumerate().map(|(i, p)| (i as f64, plot_value(&p.income))).collect();
+    let expense: Vec<(f64, f64)> = cashflow.periods.iter().enumerate().map(|(i, p)| (i as f64, plot_value(&p.expense))).collect();
+    let max_y = income.iter().chain(expense.iter()).map(|(_, y)| *y).fold(0.0, f64::max).max(1.0) * 1.1;
+    let max_x = (cashflow.periods.len().max(2) - 1) as f64;
+
+    // Months show as YYYY-MM, weeks as MM-DD
+    let period_label = |p: &CashflowPeriodResponse| match app.report_interval {
+        ReportInterval::Month => p.period_start.format("%Y-%m").to_string(),
+        ReportInterval::Week => p.period_start.format("%m-%d").to_string(),
+    };
+    let x_labels: Vec<Span> = match (cashflow.periods.first(), cashflow.periods.get(cashflow.periods.len() / 2), cashflow.periods.last()) {
+        (Some(first), Some(mid), Some(last)) => vec![Span::raw(period_label(first)), Span::raw(period_label(mid)), Span::raw(period_label(last))],
+        _ => vec![],
+    };
+
+    let title = format!(
+        "Cash Flow | In: {} Out: {} Net: {} | Savings: {}",
+        cashflow.total_income,
+        cashflow.total_expense,
+        cashflow.net,
+        cashflow.savings_rate.as_ref().map(|r| format!("{}%", r)).unwrap_or_else(|| "n/a".to_string())
+    );
+    let datasets = vec![
+        Dataset::default().name("Income").marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(Color::Green)).data(&income),
+        Dataset::default().name("Expense").marker(symbols::Marker::Braille).graph_type(GraphType::Line).style(Style::default().fg(Color::Red)).data(&expense),
+    ];
+    let chart = Chart::new(datasets)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .x_axis(Axis::default().style(Style::default().fg(Color::Gray)).bounds([0.0, max_x]).labels(x_labels))
+        .y_axis(Axis::default().style(Style::default().fg(Color::Gray)).bounds([0.0, max_y]).labels(vec![
+            Span::raw("0"),
+            Span::raw(format!("{:.0}", max_y / 2.0)),
+            Span::raw(format!("{:.0}", max_y)),
+        ]));
+    f.render_widget(chart, area);
+}
+
+fn render_report_net_worth(f: &mut Frame, app: &App, area: Rect) {
+    let values: Vec<f64> = app.report_net_worth.iter().map(|p| plot_value(&p.net_worth)).collect();
+    let title = match (app.report_net_worth.first(), app.report_net_worth.last()) {
+        (Some(first), Some(last)) => format!("Net Worth: {} → {}", first.net_worth, last.net_worth),
+        _ => "Net Worth".to_string(),
+    };
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min).min(0.0);
+    let data: Vec<u64> = values.iter().map(|v| ((v - min) * 100.0).round() as u64).collect();
+    f.render_widget(
+        Sparkline::default()
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .data(&data)
+            .style(Style::default().fg(Color::Cyan)),
+        area,
+    );
+}
+
+fn render_category_chart(f: &mut Frame, app: &App, area: Rect) {
+    // Only top-level categories, since their totals already include subcategories
+    let bars: Vec<Bar> = app.category_totals.iter()
+        .filter(|c| c.parent_id.is_none())
+        .map(|c| (c.category_name.clone().unwrap_or_else(|| "Uncategorised".to_string()), plot_value(&c.expense)))
+        .filter(|(_, expense)| *expense > 0.0)
+        .take(area.height.saturating_sub(2) as usize)
+        .map(|(name, expense)| Bar::default().label(name.into()).value(expense.round() as u64).text_value(format!("{:.2}", expense)))
+        .collect();
+
+    let chart = BarChart::default()
+        .block(Block::default().borders(Borders::ALL).title("Spending by Category"))
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(0)
+        .bar_style(Style::default().fg(Color::Magenta))
+        .value_style(Style::default().fg(Color::White))
+        .data(BarGroup::default().bars(&bars));
+    f.render_widget(chart, area);
+}
+
+fn render_budget_chart(f: &mut Frame, app: &App, area: Rect) {
+    let bars: Vec<Bar> = app.budgets.iter()
+        .take(area.height.saturating_sub(2) as usize)
+        .map(|b| {
+            let percent = if b.amount > Decimal::ZERO { plot_value(&(b.spent / b.amount * Decimal::ONE_HUNDRED)) } else { 0.0 };
+            let color = if b.is_over_budget { Color::Red } else if percent >= 80.0 { Color::Yellow } else { Color::Green };
+            Bar::default()
+                .label(b.category_name.clone().unwrap_or_else(|| "Global".to_string()).into())
+                .value(percent.round() as u64)
+                .text_value(format!("{:.0}% ({}/{})", percent, b.spent, b.amount))
+                .style(Style::default().fg(color))
+        })
+        .collect();
+
+    let chart = BarChart::default()
+        .block(Block::default().borders(Borders::ALL).title("Budget Utilisation (this period)"))
+        .direction(Direction::Horizontal)
+        .bar_width(1)
+        .bar_gap(0)
+        .max(100)
+        .value_style(Style::default().fg(Color::White))
+        .data(BarGroup::default().bars(&bars));
+    f.render_widget(chart, area);
+}
+
 fn render_popup(f: &mut Frame, popup: &PopupType, area: Rect, app: &App) {
-    let width_percent = (area.width * 60 / 100).min(80).max(50);
-    let height_percent = (area.height * 30 / 100).min(30).max(15);
+    let width_percent = (area.width * 60 / 100).clamp(50, 80);
+    let height_percent = (area.height * 30 / 100).clamp(15, 30);
     let area = centered_rect_percent(width_percent, height_percent, area);
     f.render_widget(Clear, area);
     let block = Block::default().borders(Borders::ALL).style(Style::default().bg(Color::DarkGray));
     let st = |s: usize, target: usize| if s == target { Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD) } else { Style::default() };
 
-    let has_error = app.message.as_ref().map_or(false, |(_, color)| *color == Color::Red);
+    let has_error = app.message.as_ref().is_some_and(|(_, color)| *color == Color::Red);
     let constraints = match popup {
         PopupType::AddAccount { .. } => vec![Constraint::Min(3), Constraint::Min(3), if has_error { Constraint::Length(3) } else { Constraint::Length(0) }],
         PopupType::AddTransaction { .. } => vec![Constraint::Min(3), Constraint::Min(3), Constraint::Min(3), Constraint::Length(1), if has_error { Constraint::Length(3) } else { Constraint::Length(0) }],
@@ -699,6 +1229,9 @@ fn render_popup(f: &mut Frame, popup: &PopupType, area: Rect, app: &App) {
         PopupType::AddCategory { .. } => vec![Constraint::Min(3), if has_error { Constraint::Length(3) } else { Constraint::Length(0) }],
         PopupType::AddBudget { .. } => vec![Constraint::Min(3), Constraint::Min(3), if has_error { Constraint::Length(3) } else { Constraint::Length(0) }],
         PopupType::DeleteConfirm { .. } => vec![Constraint::Min(2), Constraint::Min(3), if has_error { Constraint::Length(3) } else { Constraint::Length(0) }],
+        PopupType::ChangePassword { .. } => vec![Constraint::Min(3), Constraint::Min(3), Constraint::Min(3), if has_error { Constraint::Length(3) } else { Constraint::Length(0) }],
+        PopupType::ChangeUsername { .. } => vec![Constraint::Min(3), if has_error { Constraint::Length(3) } else { Constraint::Length(0) }],
+        PopupType::DeleteAccount { .. } => vec![Constraint::Min(2), Constraint::Min(3), Constraint::Min(3), if has_error { Constraint::Length(3) } else { Constraint::Length(0) }],
     };
     
     let layout = Layout::default().direction(Direction::Vertical).margin(2).constraints(constraints).split(area);
@@ -753,6 +1286,29 @@ fn render_popup(f: &mut Frame, popup: &PopupType, area: Rect, app: &App) {
             layout_idx += 1;
             f.render_widget(Paragraph::new(input_name.as_str()).block(Block::default().borders(Borders::ALL).title("Confirmation")).style(Style::default().fg(Color::Red)), layout[layout_idx]);
             layout_idx += 1;
+        },
+        PopupType::ChangePassword { step, current, new, confirm } => {
+            f.render_widget(block.title("Change Password (other devices will be signed out)"), area);
+            f.render_widget(Paragraph::new(mask(current)).block(Block::default().borders(Borders::ALL).title("Current Password")).style(st(*step, 0)), layout[layout_idx]);
+            layout_idx += 1;
+            f.render_widget(Paragraph::new(mask(new)).block(Block::default().borders(Borders::ALL).title("New Password")).style(st(*step, 1)), layout[layout_idx]);
+            layout_idx += 1;
+            f.render_widget(Paragraph::new(mask(confirm)).block(Block::default().borders(Borders::ALL).title("Confirm New Password")).style(st(*step, 2)), layout[layout_idx]);
+            layout_idx += 1;
+        },
+        PopupType::ChangeUsername { username } => {
+            f.render_widget(block.title("Change Username"), area);
+            f.render_widget(Paragraph::new(username.as_str()).block(Block::default().borders(Borders::ALL).title("New Username")).style(st(0, 0)), layout[layout_idx]);
+            layout_idx += 1;
+        },
+        PopupType::DeleteAccount { step, password, confirm } => {
+            f.render_widget(block.title(Span::styled("DELETE ACCOUNT", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))), area);
+            f.render_widget(Paragraph::new("This permanently deletes your user and ALL accounts, transactions and budgets.").style(Style::default().fg(Color::Red)), layout[layout_idx]);
+            layout_idx += 1;
+            f.render_widget(Paragraph::new(mask(password)).block(Block::default().borders(Borders::ALL).title("Password")).style(st(*step, 0)), layout[layout_idx]);
+            layout_idx += 1;
+            f.render_widget(Paragraph::new(confirm.as_str()).block(Block::default().borders(Borders::ALL).title("Type DELETE to confirm")).style(st(*step, 1).fg(Color::Red)), layout[layout_idx]);
+            layout_idx += 1;
         }
     };
 
@@ -0,0 +1,87 @@
+use std::{fs, io::Write, path::PathBuf};
+use anyhow::{Result, anyhow, Context};
+use serde::{Deserialize, Serialize};
+use finance_api_client::AuthTokens;
+use crate::config;
+
+/// On-disk session: the tokens are only reused against the server that issued them.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredSession {
+    base_url: String,
+    token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+}
+
+/// Session token persisted per profile in `<config dir>/finance_tui/sessions/<profile>.json`.
+/// The directory is created `0700` and the file `0600` on Unix.
+#[derive(Debug, Clone)]
+pub struct SessionStore {
+    path: Option<PathBuf>,
+    base_url: String,
+}
+
+impl SessionStore {
+    pub fn new(profile: &str, base_url: &str) -> Self {
+        let file_name: String = profile.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect();
+        Self {
+            path: config::config_dir().map(|dir| dir.join("sessions").join(format!("{}.json", file_name))),
+            base_url: base_url.to_string(),
+        }
+    }
+
+    /// Saved tokens for this profile, if they exist and were issued by the same server.
+    pub fn load(&self) -> Option<AuthTokens> {
+        let text = fs::read_to_string(self.path.as_ref()?).ok()?;
+        let stored: StoredSession = serde_json::from_str(&text).ok()?;
+        (stored.base_url == self.base_url && !stored.token.is_empty())
+            .then_some(AuthTokens { access: stored.token, refresh: stored.refresh_token })
+    }
+
+    pub fn save(&self, tokens: &AuthTokens) -> Result<()> {
+        let path = self.path.as_ref().ok_or_else(|| anyhow!("No config directory available"))?;
+        let dir = path.parent().ok_or_else(|| anyhow!("Invalid session path {}", path.display()))?;
+        create_private_dir(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let body = serde_json::to_string(&StoredSession {
+            base_url: self.base_url.clone(),
+            token: tokens.access.clone(),
+            refresh_token: tokens.refresh.clone(),
+        })?;
+        let mut file = open_private_file(path).with_context(|| format!("Failed to write {}", path.display()))?;
+        file.write_all(body.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        if let Some(path) = &self.path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn create_private_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+}
+
+#[cfg(not(unix))]
+fn create_private_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)
+}
+
+#[cfg(unix)]
+fn open_private_file(path: &std::path::Path) -> std::io::Result<fs::File> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let file = fs::OpenOptions::new().write(true).create(true).truncate(true).mode(0o600).open(path)?;
+    // `mode` only applies on creation; tighten files left over from older versions too.
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    Ok(file)
+}
+
+#[cfg(not(unix))]
+fn open_private_file(path: &std::path::Path) -> std::io::Result<fs::File> {
+    fs::OpenOptions::new().write(true).create(true).truncate(true).open(path)
+}
@@ -1,11 +1,15 @@
+import os
 import requests
 import random
 from datetime import datetime, timedelta
 
 # Configuration
-BASE_URL = "http://localhost:3000/api"
+BASE_URL = "http://localhost:3000/api/v1"
 USERNAME = "demo1"  # You can modify this to create different users
 PASSWORD = "password123"
+# Personal API token (POST /api/tokens) with the read, accounts:write, categories:write,
+# transactions:write and budgets:write scopes; when set, no password login is needed
+API_TOKEN = os.environ.get("FINANCE_API_TOKEN")
 
 session = requests.Session()
 
@@ -20,6 +24,11 @@ def print_error(msg):
 
 # 1. Register or Login
 def auth():
+    if API_TOKEN:
+        session.headers.update({"Authorization": f"Bearer {API_TOKEN}"})
+        print_success("Using API token from FINANCE_API_TOKEN")
+        return True
+
     print_step(f"Attempting to register user: {USERNAME}...")
     # Attempt registration
     reg_res = session.post(f"{BASE_URL}/auth/register", json={
@@ -29,7 +38,7 @@ def auth():
     
     if reg_res.status_code == 200:
         print_success("Registration successful")
-    elif reg_res.status_code == 400 and "already exists" in reg_res.text:
+    elif reg_res.status_code == 409:
         print_step("User already exists, logging in directly...")
     else:
         print_error(f"Registration failed: {reg_res.text}")